@@ -0,0 +1,109 @@
+//! AMD Wraith Prism cooler RGB control.
+//!
+//! The cooler's fan, logo and ring are mapped to the `cpu`, `io` and `header0`
+//! zones respectively.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::controller::{HidController, Packet};
+use crate::{strip, Config, Duration, Effect, Zone};
+
+/// Size of a single HID report, including the report ID.
+const PACKET_SIZE: usize = 65;
+
+pub struct AmdWraithPrism;
+
+impl HidController for AmdWraithPrism {
+    fn name(&self) -> &str {
+        "AMD Wraith Prism"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x2516)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x0051)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Cpu, Zone::Header0]
+    }
+
+    fn physical_zones(&self) -> &[Zone] {
+        // Logo in the center, surrounded by the fan and the outer ring.
+        &[Zone::Io, Zone::Cpu, Zone::Header0]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Cycle]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+        let channel = channel_bytes(config.zone)?;
+
+        // The cooler has no off mode, so it is emulated with a black static color.
+        let (mode, color) = match config.effect {
+            Effect::Off => (effect_bytes(Effect::Static)?, Default::default()),
+            effect => (effect_bytes(effect)?, color),
+        };
+
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+
+        // Report ID and channel effect command.
+        buf.put_slice(&[0x00, 0x51, 0x2c, 0x01, 0x00]);
+
+        // Channel.
+        buf.put_u8(channel);
+
+        // Effect speed, mode and brightness.
+        buf.put_u8(speed_bytes(config.fade_in_time));
+        buf.put_u8(0x20);
+        buf.put_u8(mode);
+        buf.put_u8(0xff);
+        buf.put_u8(config.max_brightness.0);
+
+        // Color data.
+        buf.put_slice(&[color.r, color.g, color.b]);
+
+        buf.resize(PACKET_SIZE, 0);
+        let effect_packet = buf.freeze().into();
+
+        // Packet to apply the submitted configuration.
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+        buf.put_slice(&[0x00, 0x51, 0x28, 0x00, 0x00, 0xe0]);
+        buf.resize(PACKET_SIZE, 0);
+        let apply_packet = buf.freeze().into();
+
+        Ok(vec![effect_packet, apply_packet])
+    }
+}
+
+/// Convert a zone to the cooler's LED channel.
+fn channel_bytes(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Header0 => Ok(0x00),
+        Zone::Io => Ok(0x05),
+        Zone::Cpu => Ok(0x06),
+        zone => Err(format!("unsupported zone: {zone:?}").into()),
+    }
+}
+
+/// Convert effect type to Wraith Prism format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Static => Ok(0xff),
+        Effect::Pulse => Ok(0x01),
+        Effect::Cycle => Ok(0x00),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Convert effect duration to the cooler's speed, with higher values being
+/// slower.
+fn speed_bytes(duration: Duration) -> u8 {
+    (duration.0 / 40).min(u8::MAX as u16) as u8
+}
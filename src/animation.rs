@@ -0,0 +1,251 @@
+//! Keyframe animations.
+//!
+//! Animations are TOML files containing a list of keyframes for each zone:
+//!
+//! ```toml
+//! device = "x670ef"
+//! repeat = false
+//!
+//! [[track]]
+//! zone = "io"
+//!
+//! [[track.keyframe]]
+//! color = "0x100000"
+//! brightness = 16
+//!
+//! [[track.keyframe]]
+//! color = "0xffa040"
+//! fade_time = 600000
+//! ```
+//!
+//! Each keyframe fades from the previous keyframe to its color and brightness
+//! over `fade_time` milliseconds and then holds it for `hold_time`
+//! milliseconds. Repeating animations fade from the last keyframe back to the
+//! first one.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use std::{fs, thread};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use serde::Deserialize;
+
+use crate::effect::FRAME_INTERVAL;
+use crate::reactive::Writer;
+use crate::{zone_defaults, Brightness, Config, Device, Effect, Rgb, Zone};
+
+/// Get clap CLI parameters for the animate subcommand.
+pub fn subcommand() -> Command {
+    Command::new("animate")
+        .about("Play a keyframe animation file")
+        .arg(
+            Arg::new("file")
+                .help("Animation file")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("repeat")
+                .help("Loop the animation until rgbfusion is killed")
+                .long("repeat")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+/// Run the animate subcommand.
+pub fn animate(matches: &ArgMatches) {
+    if let Err(err) = play(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Play an animation until it is finished.
+fn play(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let path = matches.get_one::<PathBuf>("file").unwrap();
+    let animation = Animation::load(path)?;
+    let repeat = animation.repeat || matches.get_flag("repeat");
+
+    let device = match animation.device {
+        Some(device) if !matches.contains_id("device") => device,
+        _ => crate::required_device(matches)?,
+    };
+
+    // Ensure all zones can display static colors.
+    let mut configs = Vec::new();
+    for track in &animation.tracks {
+        let mut config =
+            Config { device, zone: track.zone, effect: Effect::Static, ..Default::default() };
+        zone_defaults::apply(&mut config);
+        device.controller().validate(&config)?;
+        configs.push(config);
+    }
+
+    let length = animation.tracks.iter().map(Track::length).max().unwrap_or_default();
+    if repeat {
+        println!("Playing animation, press Ctrl+C to stop.");
+    } else {
+        println!("Playing animation for {:.1}s.", length as f32 / 1000.);
+    }
+
+    let mut writer = Writer::default();
+    let mut written = vec![None; configs.len()];
+    let start = Instant::now();
+    loop {
+        let elapsed = start.elapsed().as_millis() as u64;
+
+        for ((track, config), written) in
+            animation.tracks.iter().zip(&mut configs).zip(&mut written)
+        {
+            let state = track.state(elapsed, repeat);
+            if *written == Some(state) {
+                continue;
+            }
+
+            (config.color, config.max_brightness) = state;
+            writer.write(config)?;
+            *written = Some(state);
+        }
+
+        if !repeat && elapsed >= length {
+            return Ok(());
+        }
+
+        thread::sleep(FRAME_INTERVAL);
+    }
+}
+
+/// Animation file format.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Animation {
+    /// Device all zones belong to, detected automatically if omitted.
+    device: Option<Device>,
+
+    /// Loop the animation until rgbfusion is killed.
+    #[serde(default)]
+    repeat: bool,
+
+    #[serde(rename = "track")]
+    tracks: Vec<Track>,
+}
+
+impl Animation {
+    /// Load an animation file.
+    fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("unable to read {}: {err}", path.display()))?;
+        let animation: Self = toml::from_str(&content)
+            .map_err(|err| format!("invalid animation {}: {err}", path.display()))?;
+
+        if let Some(track) = animation.tracks.iter().find(|track| track.keyframes.is_empty()) {
+            return Err(format!("no keyframes for zone {:?}", track.zone).into());
+        }
+
+        Ok(animation)
+    }
+}
+
+/// Keyframes of a single zone.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Track {
+    zone: Zone,
+    #[serde(rename = "keyframe")]
+    keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Duration of all keyframes in milliseconds.
+    fn length(&self) -> u64 {
+        self.keyframes.iter().map(|keyframe| keyframe.fade_time + keyframe.hold_time).sum()
+    }
+
+    /// Get the color and brightness at a point in time.
+    fn state(&self, elapsed: u64, repeat: bool) -> (Rgb, Brightness) {
+        let (first, last) = (&self.keyframes[0], &self.keyframes[self.keyframes.len() - 1]);
+
+        let length = self.length();
+        let mut time = match length {
+            0 => return last.state(),
+            _ if repeat => elapsed % length,
+            _ => elapsed.min(length),
+        };
+
+        let mut previous = if repeat { last } else { first };
+        for keyframe in &self.keyframes {
+            if time < keyframe.fade_time {
+                let position = time as f32 / keyframe.fade_time as f32;
+                let color = previous.color.interpolate(keyframe.color, position);
+                let brightness = previous.brightness.interpolate(keyframe.brightness, position);
+                return (color, brightness);
+            }
+            time -= keyframe.fade_time;
+
+            if time < keyframe.hold_time {
+                return keyframe.state();
+            }
+            time -= keyframe.hold_time;
+
+            previous = keyframe;
+        }
+
+        last.state()
+    }
+}
+
+/// Target color and brightness of a zone.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Keyframe {
+    color: Rgb,
+    #[serde(default = "Brightness::max_value")]
+    brightness: Brightness,
+    /// Time in milliseconds to fade from the previous keyframe.
+    #[serde(default)]
+    fade_time: u64,
+    /// Time in milliseconds to hold the keyframe.
+    #[serde(default)]
+    hold_time: u64,
+}
+
+impl Keyframe {
+    fn state(&self) -> (Rgb, Brightness) {
+        (self.color, self.brightness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyframe_interpolation() {
+        let animation: Animation = toml::from_str(
+            r#"
+                [[track]]
+                zone = "io"
+
+                [[track.keyframe]]
+                color = "0x000000"
+                brightness = 0
+                hold_time = 100
+
+                [[track.keyframe]]
+                color = "0xff0000"
+                fade_time = 200
+            "#,
+        )
+        .unwrap();
+        let track = &animation.tracks[0];
+
+        let black = Rgb { r: 0, g: 0, b: 0 };
+        let red = Rgb { r: 255, g: 0, b: 0 };
+        assert_eq!(track.state(50, false), (black, Brightness(0)));
+        assert_eq!(track.state(200, false), (Rgb { r: 128, g: 0, b: 0 }, Brightness(128)));
+        assert_eq!(track.state(500, false), (red, Brightness(255)));
+
+        // Repeating animations fade back to the first keyframe.
+        assert_eq!(track.state(350, true), (black, Brightness(0)));
+    }
+}
@@ -0,0 +1,149 @@
+//! ASRock Polychrome USB motherboard RGB control.
+//!
+//! The LED count of both addressable headers can be configured using the
+//! `header` subcommand.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::controller::{HidController, Packet};
+use crate::strip::HeaderConfig;
+use crate::{strip, Config, Duration, Effect, Zone};
+
+/// Size of a single HID report, including the report ID.
+const PACKET_SIZE: usize = 65;
+
+/// Maximum number of LEDs on an addressable header.
+const MAX_ADDRESSABLE_LEDS: u16 = 80;
+
+const WRITE_ZONE: u8 = 0x10;
+const COMMIT: u8 = 0x12;
+const WRITE_HEADER: u8 = 0x15;
+
+pub struct AsrockPolychrome;
+
+impl HidController for AsrockPolychrome {
+    fn name(&self) -> &str {
+        "ASRock Polychrome"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x26CE)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x01A2)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Cpu, Zone::Audio, Zone::Chipset, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle, Effect::Rainbow]
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+        buf.put_slice(&[0x00, WRITE_ZONE, zone_bytes(config.zone)?]);
+        buf.put_u8(effect_bytes(config.effect)?);
+        buf.put_slice(&[color.r, color.g, color.b]);
+        buf.put_u8(speed_bytes(config.fade_in_time));
+
+        // Only update the selected zone.
+        buf.put_slice(&[0xff, 0x00]);
+
+        buf.resize(PACKET_SIZE, 0);
+
+        Ok(vec![buf.freeze().into()])
+    }
+
+    fn commit_bytes(&self) -> Vec<Packet> {
+        vec![commit_packet()]
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(MAX_ADDRESSABLE_LEDS as usize)
+    }
+
+    fn header_bytes(
+        &self,
+        headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(header_packets(headers))
+    }
+}
+
+/// Convert the LED counts of the addressable headers to packets.
+fn header_packets(headers: &[(Zone, HeaderConfig)]) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let mut packets = Vec::new();
+    for (zone, config) in headers {
+        let zone = match zone {
+            Zone::Header0 | Zone::Header1 => zone_bytes(*zone)?,
+            zone => return Err(format!("zone {zone:?} is not an addressable header").into()),
+        };
+
+        if config.led_count > MAX_ADDRESSABLE_LEDS {
+            return Err(format!(
+                "addressable headers support at most {MAX_ADDRESSABLE_LEDS} LEDs, got {}",
+                config.led_count
+            )
+            .into());
+        }
+
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+        buf.put_slice(&[0x00, WRITE_HEADER, zone, config.led_count as u8]);
+        buf.resize(PACKET_SIZE, 0);
+        packets.push(buf.freeze().into());
+    }
+
+    packets.push(commit_packet());
+
+    Ok(packets)
+}
+
+/// Packet persisting all changes across reboots.
+fn commit_packet() -> Packet {
+    let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+    buf.put_slice(&[0x00, COMMIT]);
+    buf.resize(PACKET_SIZE, 0);
+    buf.freeze().into()
+}
+
+/// Convert zone to Polychrome format.
+fn zone_bytes(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Cpu => Ok(0x00),
+        Zone::Header0 => Ok(0x02),
+        Zone::Header1 => Ok(0x03),
+        Zone::Chipset => Ok(0x04),
+        Zone::Io => Ok(0x05),
+        Zone::Audio => Ok(0x07),
+    }
+}
+
+/// Convert effect type to Polychrome format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Off => Ok(0x00),
+        Effect::Static => Ok(0x01),
+        Effect::Pulse => Ok(0x02),
+        Effect::Flash => Ok(0x03),
+        Effect::Cycle => Ok(0x04),
+        Effect::Rainbow => Ok(0x0e),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Convert effect duration to the board's speed, with higher values being
+/// slower.
+fn speed_bytes(duration: Duration) -> u8 {
+    (duration.0 / 20).min(u8::MAX as u16) as u8
+}
@@ -0,0 +1,124 @@
+//! ASUS Aura DRAM control.
+//!
+//! Aura DRAM sticks share the register map of the Aura SMBus controller. They
+//! all start at the same address and are moved to an address of their own by
+//! writing their slot index and new address, before all sticks are
+//! controlled together through the IO zone.
+
+use std::error::Error;
+use std::io;
+use std::path::Path;
+
+use hidapi::HidApi;
+
+use crate::asus_aura_smbus::{self, AsusAuraSmbus};
+use crate::controller::{DeviceHandle, HidController, Packet};
+use crate::smbus::{self, SmbusDevice};
+use crate::{Config, Effect, HidSelector, Zone};
+
+/// SMBus address of sticks which haven't been assigned an address yet.
+const UNASSIGNED_ADDRESS: u8 = 0x77;
+
+/// SMBus addresses assigned to the sticks.
+const ADDRESSES: [u8; 8] = [0x70, 0x71, 0x72, 0x73, 0x74, 0x75, 0x76, 0x78];
+
+/// Maximum number of LEDs per stick, colors of missing LEDs are ignored.
+const LED_COUNT: usize = 8;
+
+const REG_SLOT_INDEX: u16 = 0x80f8;
+const REG_I2C_ADDRESS: u16 = 0x80f9;
+
+pub struct AsusAuraDram;
+
+impl HidController for AsusAuraDram {
+    fn name(&self) -> &str {
+        "ASUS Aura DRAM"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        &ADDRESSES
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        AsusAuraSmbus.supported_effects()
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn open(&self, _api: &HidApi, _selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        open_sticks("Aura DRAM")
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        asus_aura_smbus::effect_packets(config, LED_COUNT)
+    }
+}
+
+/// Open all Aura DRAM sticks on the first adapter with any of them.
+///
+/// The `name` is used for the error when no sticks are found.
+pub fn open_sticks(name: &str) -> Result<DeviceHandle, Box<dyn Error>> {
+    let mut open_error = None;
+    for adapter in smbus::adapters(asus_aura_smbus::ADAPTERS) {
+        // Failing to assign addresses is fine, if sticks already have one.
+        let _ = assign_addresses(&adapter);
+
+        let sticks: Vec<_> = ADDRESSES
+            .iter()
+            .copied()
+            .filter(|address| match SmbusDevice::open(&adapter, &[*address]) {
+                Ok(device) => asus_aura_smbus::is_aura(&device),
+                Err(err) => {
+                    open_error.get_or_insert(err);
+                    false
+                },
+            })
+            .collect();
+
+        if !sticks.is_empty() {
+            return Ok(DeviceHandle::Smbus(SmbusDevice::open(&adapter, &sticks)?));
+        }
+    }
+
+    // Report why the adapters couldn't be probed, like missing permissions.
+    match open_error {
+        Some(err) => Err(err),
+        None => Err(format!("no {name} found, is the `i2c-dev` module loaded?").into()),
+    }
+}
+
+/// Move all sticks from the shared address to an address of their own.
+fn assign_addresses(adapter: &Path) -> Result<(), Box<dyn Error>> {
+    let device = SmbusDevice::open(adapter, &[UNASSIGNED_ADDRESS])?;
+
+    for (slot, address) in ADDRESSES.iter().enumerate() {
+        // Stop once no stick is left at the shared address.
+        if !asus_aura_smbus::is_aura(&device) {
+            break;
+        }
+
+        assign_address(&device, slot as u8, *address)?;
+    }
+
+    Ok(())
+}
+
+/// Assign an address to the stick in a slot.
+fn assign_address(device: &SmbusDevice, slot: u8, address: u8) -> io::Result<()> {
+    asus_aura_smbus::write_register(device, REG_SLOT_INDEX, slot)?;
+    asus_aura_smbus::write_register(device, REG_I2C_ADDRESS, address << 1)
+}
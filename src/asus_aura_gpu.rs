@@ -0,0 +1,117 @@
+//! ASUS ROG Strix GPU Aura control.
+//!
+//! The Aura controller is connected to one of the i2c adapters of the graphics
+//! card. Its registers are written directly, unlike the motherboard's Aura
+//! SMBus controller. The whole card is controlled through the IO zone.
+
+use std::error::Error;
+
+use bytes::Bytes;
+use hidapi::HidApi;
+
+use crate::controller::{DeviceHandle, HidController, Packet, Transport};
+use crate::smbus::{self, SmbusDevice};
+use crate::{strip, Config, Effect, HidSelector, Rgb, Zone};
+
+/// Name prefixes of graphics card i2c adapters.
+const ADAPTERS: &[&str] = &["NVIDIA i2c adapter", "AMDGPU DM i2c"];
+
+/// i2c address of the Aura controller.
+const ADDRESS: u8 = 0x29;
+
+/// Vendor ID reported by ASUS controllers.
+const VENDOR_ID: [u8; 2] = [0x15, 0x89];
+
+const REG_VENDOR_ID: u8 = 0x20;
+const REG_RED: u8 = 0x04;
+const REG_GREEN: u8 = 0x05;
+const REG_BLUE: u8 = 0x06;
+const REG_MODE: u8 = 0x07;
+const REG_APPLY: u8 = 0x0e;
+
+pub struct AsusAuraGpu;
+
+impl HidController for AsusAuraGpu {
+    fn name(&self) -> &str {
+        "ASUS ROG Strix GPU"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        &[ADDRESS]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle]
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn open(&self, _api: &HidApi, _selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        for adapter in smbus::adapters(ADAPTERS) {
+            let device = match SmbusDevice::open(&adapter, &[ADDRESS]) {
+                Ok(device) => device,
+                Err(_) => continue,
+            };
+
+            // Ignore other devices on the card using the same address.
+            let vendor_id =
+                [REG_VENDOR_ID, REG_VENDOR_ID + 1].map(|reg| device.read_byte_data(reg));
+            if let [Ok(high), Ok(low)] = vendor_id {
+                if [high, low] == VENDOR_ID {
+                    return Ok(DeviceHandle::Smbus(device));
+                }
+            }
+        }
+
+        Err("no ASUS GPU Aura controller found, is the `i2c-dev` module loaded?".into())
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        // The card has no off mode, so it is emulated with a black static color.
+        let (mode, color) = match config.effect {
+            Effect::Off => (effect_bytes(Effect::Static)?, Rgb::default()),
+            effect => (effect_bytes(effect)?, config.color),
+        };
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(color);
+
+        let writes =
+            [(REG_RED, color.r), (REG_GREEN, color.g), (REG_BLUE, color.b), (REG_MODE, mode)];
+        let mut packets: Vec<_> =
+            writes.iter().map(|(reg, value)| register_packet(*reg, *value)).collect();
+
+        // Apply the submitted configuration.
+        packets.push(register_packet(REG_APPLY, 0x01));
+
+        Ok(packets)
+    }
+}
+
+/// Convert a register write to an SMBus packet.
+fn register_packet(register: u8, value: u8) -> Packet {
+    Packet::new(Transport::SmbusByte, Bytes::copy_from_slice(&[register, value]))
+}
+
+/// Convert effect type to ASUS GPU format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Static => Ok(1),
+        Effect::Pulse => Ok(2),
+        Effect::Flash => Ok(3),
+        Effect::Cycle => Ok(4),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
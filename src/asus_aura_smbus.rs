@@ -0,0 +1,157 @@
+//! ASUS Aura SMBus control.
+//!
+//! Boards from before 2018 connect their Aura controller to the SMBus instead
+//! of USB. Registers are selected by writing their byte-swapped address to
+//! command `0x00`, followed by writing the value to command `0x01`.
+//!
+//! All onboard LEDs are controlled together through the IO zone.
+
+use std::error::Error;
+use std::io;
+
+use bytes::Bytes;
+use hidapi::HidApi;
+
+use crate::controller::{DeviceHandle, HidController, Packet, Transport};
+use crate::smbus::{self, SmbusDevice};
+use crate::{strip, Config, Effect, HidSelector, Zone};
+
+/// Name prefixes of motherboard SMBus adapters, like the PIIX4 or I801.
+pub const ADAPTERS: &[&str] = &["SMBus"];
+
+/// SMBus address of the Aura controller.
+const ADDRESS: u8 = 0x4e;
+
+/// Number of onboard LEDs.
+const LED_COUNT: usize = 5;
+
+const REG_DEVICE_NAME: u16 = 0x1000;
+const REG_COLORS_EFFECT: u16 = 0x8010;
+const REG_DIRECT: u16 = 0x8020;
+const REG_MODE: u16 = 0x8021;
+const REG_APPLY: u16 = 0x80a0;
+
+/// Apply register value to show the written configuration.
+const APPLY: u8 = 0x01;
+
+/// Apply register value to persist the configuration across reboots.
+const SAVE: u8 = 0xaa;
+
+pub struct AsusAuraSmbus;
+
+impl HidController for AsusAuraSmbus {
+    fn name(&self) -> &str {
+        "ASUS Aura SMBus"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        &[ADDRESS]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle, Effect::Rainbow]
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn open(&self, _api: &HidApi, _selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        for adapter in smbus::adapters(ADAPTERS) {
+            let device = match SmbusDevice::open(&adapter, &[ADDRESS]) {
+                Ok(device) => device,
+                Err(_) => continue,
+            };
+
+            // Ignore unrelated devices sharing the address on other adapters.
+            if is_aura(&device) {
+                return Ok(DeviceHandle::Smbus(device));
+            }
+        }
+
+        Err("no Aura SMBus controller found, is the `i2c-dev` module loaded?".into())
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        effect_packets(config, LED_COUNT)
+    }
+}
+
+/// Convert a config to the register writes of an Aura controller with a
+/// number of LEDs.
+pub fn effect_packets(config: &Config, led_count: usize) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+
+    let mut packets = Vec::new();
+
+    // Disable direct mode, so the effect is rendered by the controller.
+    packets.append(&mut register_packets(REG_DIRECT, &[0x00]));
+
+    // Effect.
+    packets.append(&mut register_packets(REG_MODE, &[effect_bytes(config.effect)?]));
+
+    // Color of every LED, in RBG order.
+    let colors: Vec<_> = (0..led_count).flat_map(|_| [color.r, color.b, color.g]).collect();
+    packets.append(&mut register_packets(REG_COLORS_EFFECT, &colors));
+
+    // Apply and persist the configuration.
+    packets.append(&mut register_packets(REG_APPLY, &[APPLY]));
+    packets.append(&mut register_packets(REG_APPLY, &[SAVE]));
+
+    Ok(packets)
+}
+
+/// Check if a device is an Aura controller, by reading its device name.
+pub fn is_aura(device: &SmbusDevice) -> bool {
+    matches!(read_register(device, REG_DEVICE_NAME), Ok(byte) if byte.is_ascii_alphanumeric())
+}
+
+/// Convert register writes to SMBus packets.
+pub fn register_packets(register: u16, values: &[u8]) -> Vec<Packet> {
+    let mut packets = Vec::new();
+
+    for (i, value) in values.iter().enumerate() {
+        let [low, high] = (register + i as u16).swap_bytes().to_le_bytes();
+        packets.push(Packet::new(Transport::SmbusWord, Bytes::copy_from_slice(&[0x00, low, high])));
+        packets.push(Packet::new(Transport::SmbusByte, Bytes::copy_from_slice(&[0x01, *value])));
+    }
+
+    packets
+}
+
+/// Write a single controller register.
+pub fn write_register(device: &SmbusDevice, register: u16, value: u8) -> io::Result<()> {
+    device.write_word_data(0x00, register.swap_bytes())?;
+    device.write_byte_data(0x01, value)
+}
+
+/// Read a single controller register.
+pub fn read_register(device: &SmbusDevice, register: u16) -> io::Result<u8> {
+    device.write_word_data(0x00, register.swap_bytes())?;
+    device.read_byte_data(0x81)
+}
+
+/// Convert effect type to Aura SMBus format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Off => Ok(0),
+        Effect::Static => Ok(1),
+        Effect::Pulse => Ok(2),
+        Effect::Flash => Ok(3),
+        Effect::Cycle => Ok(4),
+        Effect::Rainbow => Ok(5),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
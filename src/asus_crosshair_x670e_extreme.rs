@@ -0,0 +1,53 @@
+//! ASUS ROG Crosshair X670E Extreme Aura control.
+//!
+//! The board shares its zone layout with the Crosshair X670E Hero.
+
+use std::error::Error;
+
+use crate::asus_crosshair_x670e_hero::AsusRogCrosshairX670EHero;
+use crate::controller::{HidController, Packet};
+use crate::{Config, Effect, Rgb, Zone};
+
+pub struct AsusRogCrosshairX670EExtreme;
+
+impl HidController for AsusRogCrosshairX670EExtreme {
+    fn name(&self) -> &str {
+        "ASUS ROG Crosshair X670E Extreme"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x0B05)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x1A53)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        AsusRogCrosshairX670EHero.supported_zones()
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        AsusRogCrosshairX670EHero.supported_effects()
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        AsusRogCrosshairX670EHero.config_bytes(config)
+    }
+
+    fn commit_bytes(&self) -> Vec<Packet> {
+        AsusRogCrosshairX670EHero.commit_bytes()
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        AsusRogCrosshairX670EHero.max_leds()
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        AsusRogCrosshairX670EHero.led_bytes(zone, colors)
+    }
+}
@@ -0,0 +1,54 @@
+//! ASUS ROG Crosshair X670E Hero Aura control.
+//!
+//! The board uses the common X670E Aura protocol. The lighting next to its OLED
+//! display is controlled through the chipset zone.
+
+use std::error::Error;
+
+use crate::asus_strix_x670e_f::{self, AsusRogStrixX670EF};
+use crate::controller::{HidController, Packet};
+use crate::{Config, Effect, Rgb, Zone};
+
+pub struct AsusRogCrosshairX670EHero;
+
+impl HidController for AsusRogCrosshairX670EHero {
+    fn name(&self) -> &str {
+        "ASUS ROG Crosshair X670E Hero"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x0B05)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x1A52)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Chipset, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        AsusRogStrixX670EF.supported_effects()
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        asus_strix_x670e_f::aura_packets(config)
+    }
+
+    fn commit_bytes(&self) -> Vec<Packet> {
+        AsusRogStrixX670EF.commit_bytes()
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(asus_strix_x670e_f::MAX_DIRECT_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(asus_strix_x670e_f::direct_packets(zone, colors))
+    }
+}
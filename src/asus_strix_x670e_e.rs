@@ -0,0 +1,58 @@
+//! ASUS ROG Strix X670E-E Aura control.
+//!
+//! The board uses the same Aura protocol as the X670E-F, with an additional
+//! ARGB Gen2 header.
+
+use std::error::Error;
+
+use crate::asus_strix_x670e_f::{self, AsusRogStrixX670EF};
+use crate::controller::{HidController, Packet};
+use crate::{Config, Effect, Rgb, Zone};
+
+pub struct AsusRogStrixX670EE;
+
+impl HidController for AsusRogStrixX670EE {
+    fn name(&self) -> &str {
+        "ASUS ROG Strix X670E-E"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x0B05)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x1AA6)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        AsusRogStrixX670EF.supported_effects()
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        asus_strix_x670e_f::aura_packets(config)
+    }
+
+    fn commit_bytes(&self) -> Vec<Packet> {
+        AsusRogStrixX670EF.commit_bytes()
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(asus_strix_x670e_f::MAX_DIRECT_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        // Only the Gen2 header is addressable.
+        match zone {
+            Zone::Header1 => Some(asus_strix_x670e_f::direct_packets(zone, colors)),
+            zone => Some(Err(format!("zone {zone:?} is not an addressable header").into())),
+        }
+    }
+}
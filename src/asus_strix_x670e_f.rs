@@ -3,40 +3,206 @@
 use std::error::Error;
 
 use bytes::{BufMut, Bytes, BytesMut};
+use hidapi::HidDevice;
 
-use crate::controller::HidController;
-use crate::{Config, Effect, Rgb, Zone};
+use crate::controller::{HidController, Packet};
+use crate::{strip, Config, Effect, Rgb, Zone};
 
 const IO_MASK: u8 = 0x04 | 0x02 | 0x01;
 const CPU_MASK: u8 = 0x20;
 const GPU_MASK: u8 = 0x40;
+const OLED_MASK: u8 = 0x10 | 0x08;
+const GEN2_MASK: u8 = 0x80;
+
+/// Colors fitting into a single direct mode packet.
+const DIRECT_LEDS_PER_PACKET: usize = 20;
+
+/// Maximum number of LEDs on a Gen2 header.
+pub const MAX_DIRECT_LEDS: usize = 120;
+
+/// Size of the 0xEC state query report, including the report ID.
+const STATE_REPORT_SIZE: usize = 65;
+
+/// Offset of the effect of the first channel in the state report.
+const STATE_EFFECT_OFFSET: usize = 2;
+
+/// Offset of the color of the first LED in the state report.
+const STATE_COLOR_OFFSET: usize = 6;
 
 pub struct AsusRogStrixX670EF;
 
 impl HidController for AsusRogStrixX670EF {
-    fn vendor_id(&self) -> u16 {
-        0x0B05
+    fn name(&self) -> &str {
+        "ASUS ROG Strix X670E-F"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x0B05)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x19AF)
     }
 
-    fn product_id(&self) -> u16 {
-        0x19AF
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Header0]
     }
 
-    fn config_bytes(&self, config: &Config) -> Result<Vec<Bytes>, Box<dyn Error>> {
-        let effect = effect_bytes(config.effect);
-        let zone = zone_bytes(config.zone)?;
+    fn supported_effects(&self) -> &[Effect] {
+        &[
+            Effect::Off,
+            Effect::Static,
+            Effect::Pulse,
+            Effect::Flash,
+            Effect::Cycle,
+            Effect::Rainbow,
+            Effect::ChaseFade,
+            Effect::Chase,
+        ]
+    }
 
-        // Set LED effect.
-        let effect_bytes = Bytes::copy_from_slice(&[0xec, 0x35, zone, 0x00, 0x00, effect]);
+    // Aura has no brightness command, so colors are scaled before encoding.
+    fn supports_brightness(&self) -> bool {
+        false
+    }
 
-        // Set LED color.
-        let color_bytes = color_bytes(config.zone, config.color)?;
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        aura_packets(config)
+    }
 
-        // Commit to persist across reboots.
-        let commit_bytes = Bytes::copy_from_slice(&[0xec, 0x3f, 0x55]);
+    fn commit_bytes(&self) -> Vec<Packet> {
+        vec![Bytes::from_static(&[0xec, 0x3f, 0x55]).into()]
+    }
 
-        Ok(vec![effect_bytes, color_bytes, commit_bytes])
+    fn verify(&self, device: &HidDevice, config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+        Some(verify_state(device, config))
     }
+
+    fn supports_read_back(&self) -> bool {
+        true
+    }
+}
+
+/// Query the current state of the controller and compare it to a config.
+fn verify_state(device: &HidDevice, config: &Config) -> Result<(), Box<dyn Error>> {
+    let mut report = [0; STATE_REPORT_SIZE];
+    report[0] = 0xec;
+    let len = device.get_feature_report(&mut report)?;
+    check_state(&report[..len], config)
+}
+
+/// Compare the effect and LED colors of a state report to a config.
+///
+/// The report contains the effect of every channel, followed by the colors of
+/// all LEDs in the order of the zone mask bits.
+fn check_state(report: &[u8], config: &Config) -> Result<(), Box<dyn Error>> {
+    let channel = zone_bytes(config.zone)? as usize;
+    let mask = zone_mask(config.zone)?;
+
+    let mut mismatches = Vec::new();
+
+    let effect = effect_bytes(config.effect);
+    match report.get(STATE_EFFECT_OFFSET + channel) {
+        Some(actual) if *actual == effect => (),
+        Some(actual) => mismatches.push(format!("effect is {actual}, expected {effect}")),
+        None => return Err("state report is truncated".into()),
+    }
+
+    // Colors are meaningless while the zone is off.
+    let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+    for led in (0..u8::BITS as usize).filter(|led| mask & 1 << led != 0) {
+        if config.effect == Effect::Off {
+            break;
+        }
+
+        let offset = STATE_COLOR_OFFSET + led * 3;
+        let actual = match report.get(offset..offset + 3) {
+            Some(&[r, g, b]) => Rgb { r, g, b },
+            _ => return Err("state report is truncated".into()),
+        };
+
+        if actual != color {
+            mismatches.push(format!("LED {led} is {actual}, expected {color}"));
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches.join(", ").into())
+    }
+}
+
+/// Convert a config to the packets of an ASUS Aura controller.
+///
+/// The X670E boards share their protocol, but not all of them have every zone.
+pub fn aura_packets(config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+    let color_bytes = color_bytes(config.zone, color)?;
+    zone_packets(config, color_bytes)
+}
+
+/// Convert a config to the packets of an ASUS Aura controller with a
+/// different LED layout, coloring all LEDs selected by the mask.
+pub fn masked_packets(config: &Config, mask: u8) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+
+    let mut buf = BytesMut::new();
+    buf.put_slice(&[0xec, 0x36, 0x00, mask, 0x00]);
+    for led in 0..u8::BITS {
+        let color = if mask & 1 << led != 0 { color } else { Rgb::default() };
+        buf.put_slice(&[color.r, color.g, color.b]);
+    }
+
+    zone_packets(config, buf.freeze())
+}
+
+/// Wrap the color packet of a zone with its effect packet.
+fn zone_packets(config: &Config, color_bytes: Bytes) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let effect = effect_bytes(config.effect);
+    let zone = zone_bytes(config.zone)?;
+
+    // Set LED effect.
+    let effect_bytes = Bytes::copy_from_slice(&[0xec, 0x35, zone, 0x00, 0x00, effect]);
+
+    Ok(vec![effect_bytes.into(), color_bytes.into()])
+}
+
+/// Convert individual LED colors to direct mode packets for an ARGB header.
+///
+/// The last packet applies the colors of the entire header at once.
+pub fn direct_packets(zone: Zone, colors: &[Rgb]) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let channel = match zone {
+        Zone::Header0 | Zone::Header1 => zone_bytes(zone)?,
+        zone => return Err(format!("zone {zone:?} is not an addressable header").into()),
+    };
+
+    if colors.len() > MAX_DIRECT_LEDS {
+        return Err(format!("Gen2 headers support at most {MAX_DIRECT_LEDS} LEDs").into());
+    }
+
+    // Switch the header to direct mode.
+    let mut packets = vec![Bytes::copy_from_slice(&[0xec, 0x35, channel, 0x00, 0x00, 0xff]).into()];
+
+    let chunks = colors.chunks(DIRECT_LEDS_PER_PACKET);
+    let last = chunks.len().saturating_sub(1);
+    for (i, chunk) in chunks.enumerate() {
+        let mut buf = BytesMut::new();
+
+        // Channel with apply flag, first LED and LED count.
+        let apply = if i == last { 0x80 } else { 0x00 };
+        buf.put_slice(&[0xec, 0x40, apply | channel]);
+        buf.put_u8((i * DIRECT_LEDS_PER_PACKET) as u8);
+        buf.put_u8(chunk.len() as u8);
+
+        for color in chunk {
+            buf.put_slice(&[color.r, color.g, color.b]);
+        }
+
+        packets.push(buf.freeze().into());
+    }
+
+    Ok(packets)
 }
 
 /// Convert effect type to ASUS Aura format.
@@ -58,6 +224,8 @@ fn zone_bytes(zone: Zone) -> Result<u8, Box<dyn Error>> {
     match zone {
         Zone::Io => Ok(0x00),
         Zone::Header0 => Ok(0x01),
+        Zone::Header1 => Ok(0x02),
+        Zone::Chipset => Ok(0x03),
         zone => Err(format!("unsupported zone: {zone:?}").into()),
     }
 }
@@ -67,6 +235,8 @@ fn zone_mask(zone: Zone) -> Result<u8, Box<dyn Error>> {
     match zone {
         Zone::Io => Ok(IO_MASK),
         Zone::Header0 => Ok(CPU_MASK | GPU_MASK),
+        Zone::Header1 => Ok(GEN2_MASK),
+        Zone::Chipset => Ok(OLED_MASK),
         zone => Err(format!("unsupported zone: {zone:?}").into()),
     }
 }
@@ -86,8 +256,13 @@ fn color_bytes(zone: Zone, color: Rgb) -> Result<Bytes, Box<dyn Error>> {
         buf.put_u8(color.b);
     }
 
-    // Padding.
-    buf.put_slice(&[0x00; 6]);
+    // OLED lighting colors, only present on some boards.
+    let oled_color = if zone == Zone::Chipset { color } else { Rgb::default() };
+    for _ in 0..2 {
+        buf.put_u8(oled_color.r);
+        buf.put_u8(oled_color.g);
+        buf.put_u8(oled_color.b);
+    }
 
     // CPU color.
     buf.put_u8(color.r);
@@ -99,5 +274,55 @@ fn color_bytes(zone: Zone, color: Rgb) -> Result<Bytes, Box<dyn Error>> {
     buf.put_u8(color.g);
     buf.put_u8(color.b);
 
+    // ARGB Gen2 header color, only present on some boards.
+    if zone == Zone::Header1 {
+        buf.put_u8(color.r);
+        buf.put_u8(color.g);
+        buf.put_u8(color.b);
+    }
+
     Ok(buf.freeze())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direct_mode_chunks() {
+        let colors = vec![Rgb { r: 1, g: 2, b: 3 }; 25];
+        let packets = direct_packets(Zone::Header1, &colors).unwrap();
+
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[0].bytes[..], [0xec, 0x35, 0x02, 0x00, 0x00, 0xff]);
+        assert_eq!(packets[1].bytes[..8], [0xec, 0x40, 0x02, 0, 20, 1, 2, 3]);
+        assert_eq!(packets[2].bytes[..], [
+            0xec, 0x40, 0x82, 20, 5, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3
+        ]);
+
+        assert!(direct_packets(Zone::Io, &colors).is_err());
+    }
+
+    #[test]
+    fn state_report() {
+        let config = Config {
+            zone: Zone::Header0,
+            effect: Effect::Static,
+            color: Rgb { r: 1, g: 2, b: 3 },
+            ..Default::default()
+        };
+
+        let mut report = vec![0; STATE_REPORT_SIZE];
+        report[..2].copy_from_slice(&[0xec, 0x3b]);
+        report[STATE_EFFECT_OFFSET + 1] = 1;
+        report[STATE_COLOR_OFFSET + 15..STATE_COLOR_OFFSET + 21]
+            .copy_from_slice(&[1, 2, 3, 1, 2, 3]);
+        assert!(check_state(&report, &config).is_ok());
+
+        report[STATE_COLOR_OFFSET + 20] = 0;
+        let err = check_state(&report, &config).unwrap_err().to_string();
+        assert!(err.starts_with("LED 6 is"));
+
+        assert!(check_state(&report[..4], &config).is_err());
+    }
+}
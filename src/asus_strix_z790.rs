@@ -0,0 +1,74 @@
+//! ASUS ROG Strix Z790 Aura control.
+//!
+//! The Z790 boards use the X670E Aura protocol, but have an additional onboard
+//! LED and a separately controlled chipset heatsink.
+
+use std::error::Error;
+
+use crate::asus_strix_x670e_f::{self, AsusRogStrixX670EF};
+use crate::controller::{HidController, Packet};
+use crate::{Config, Effect, Rgb, Zone};
+
+const IO_MASK: u8 = 0x08 | 0x04 | 0x02 | 0x01;
+const HEADER0_MASK: u8 = 0x20 | 0x10;
+const HEADER1_MASK: u8 = 0x40;
+const CHIPSET_MASK: u8 = 0x80;
+
+pub struct AsusRogStrixZ790;
+
+impl HidController for AsusRogStrixZ790 {
+    fn name(&self) -> &str {
+        "ASUS ROG Strix Z790"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x0B05)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x1B4C)
+    }
+
+    fn product_ids(&self) -> Vec<u16> {
+        vec![0x1B4C, 0x1B4D]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Chipset, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        AsusRogStrixX670EF.supported_effects()
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        asus_strix_x670e_f::masked_packets(config, zone_mask(config.zone)?)
+    }
+
+    fn commit_bytes(&self) -> Vec<Packet> {
+        AsusRogStrixX670EF.commit_bytes()
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(asus_strix_x670e_f::MAX_DIRECT_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(asus_strix_x670e_f::direct_packets(zone, colors))
+    }
+}
+
+/// Get the LEDs of a zone.
+fn zone_mask(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Io => Ok(IO_MASK),
+        Zone::Chipset => Ok(CHIPSET_MASK),
+        Zone::Header0 => Ok(HEADER0_MASK),
+        Zone::Header1 => Ok(HEADER1_MASK),
+        zone => Err(format!("unsupported zone: {zone:?}").into()),
+    }
+}
@@ -0,0 +1,181 @@
+//! Audio visualization.
+//!
+//! Audio is captured from the default PulseAudio or PipeWire monitor using
+//! `parec`, with the spectrum being split into one logarithmic frequency band
+//! per zone.
+
+use std::error::Error;
+use std::io::Read;
+use std::ops::Range;
+use std::process::{Command as Process, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use clap::{Arg, ArgMatches};
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+use crate::reactive::Source;
+
+/// Capture sample rate in Hz.
+const SAMPLE_RATE: usize = 44100;
+
+/// Number of samples used for each spectrum.
+const FFT_SIZE: usize = 2048;
+
+/// Number of samples read from the capture process at once.
+const CHUNK_SIZE: usize = 512;
+
+/// Frequency range split into bands.
+const MIN_FREQUENCY: f32 = 40.;
+const MAX_FREQUENCY: f32 = 16000.;
+
+/// Band amplitude mapped to the lowest level.
+const FLOOR_DB: f32 = -60.;
+
+/// Factor applied to a band's level each frame when the volume drops.
+const DECAY: f32 = 0.85;
+
+/// Get clap CLI parameters for the audio mode.
+pub fn args() -> Vec<Arg> {
+    vec![Arg::new("audio-source")
+        .help("PulseAudio or PipeWire source captured by the audio mode")
+        .long("audio-source")
+        .requires("mode")
+        .default_value("@DEFAULT_MONITOR@")]
+}
+
+/// Audio levels of the monitored source.
+pub struct Audio {
+    receiver: Receiver<Vec<f32>>,
+    fft: Arc<dyn Fft<f32>>,
+    samples: Vec<f32>,
+    window: Vec<f32>,
+    bands: Vec<Range<usize>>,
+    levels: Vec<f32>,
+    interval: Duration,
+}
+
+impl Audio {
+    pub fn new(matches: &ArgMatches, bands: usize) -> Result<Self, Box<dyn Error>> {
+        let source = matches.get_one::<String>("audio-source").unwrap();
+        let fps = *matches.get_one::<u16>("fps").unwrap();
+
+        let mut capture = Process::new("parec")
+            .args(["--format=s16le", "--channels=1", "--latency-msec=10"])
+            .arg(format!("--rate={SAMPLE_RATE}"))
+            .arg(format!("--device={source}"))
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("unable to start parec: {err}"))?;
+        let mut stdout = capture.stdout.take().unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buf = [0; CHUNK_SIZE * 2];
+            while stdout.read_exact(&mut buf).is_ok() {
+                let chunk = buf
+                    .chunks_exact(2)
+                    .map(|sample| i16::from_le_bytes([sample[0], sample[1]]) as f32 / 32768.)
+                    .collect();
+
+                if sender.send(chunk).is_err() {
+                    break;
+                }
+            }
+
+            let _ = capture.kill();
+            let _ = capture.wait();
+        });
+
+        // Hann window to reduce spectral leakage.
+        let window = (0..FFT_SIZE)
+            .map(|i| {
+                let phase = 2. * std::f32::consts::PI * i as f32 / FFT_SIZE as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+
+        Ok(Self {
+            fft: FftPlanner::new().plan_fft_forward(FFT_SIZE),
+            interval: Duration::from_secs(1) / fps as u32,
+            levels: vec![0.; bands],
+            samples: vec![0.; FFT_SIZE],
+            bands: frequency_bands(bands),
+            receiver,
+            window,
+        })
+    }
+
+    /// Move all captured samples into the analysis window.
+    fn receive(&mut self) -> Result<(), Box<dyn Error>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(chunk) => {
+                    let len = chunk.len().min(FFT_SIZE);
+                    self.samples.drain(..len);
+                    self.samples.extend_from_slice(&chunk[chunk.len() - len..]);
+                },
+                Err(TryRecvError::Empty) => return Ok(()),
+                Err(TryRecvError::Disconnected) => return Err("audio capture stopped".into()),
+            }
+        }
+    }
+}
+
+impl Source for Audio {
+    fn name(&self) -> String {
+        String::from("audio")
+    }
+
+    fn sample(&mut self, levels: &mut [f32]) -> Result<(), Box<dyn Error>> {
+        self.receive()?;
+
+        let mut spectrum: Vec<_> = self
+            .samples
+            .iter()
+            .zip(&self.window)
+            .map(|(sample, window)| Complex::new(sample * window, 0.))
+            .collect();
+        self.fft.process(&mut spectrum);
+
+        // Normalize, so a full scale sine has an amplitude of one.
+        let scale = 2. / self.window.iter().sum::<f32>();
+
+        for ((band, level), output) in self.bands.iter().zip(&mut self.levels).zip(levels) {
+            let amplitude = spectrum[band.clone()].iter().map(|bin| bin.norm()).fold(0., f32::max);
+            let db = 20. * (amplitude * scale).max(f32::MIN_POSITIVE).log10();
+            let new_level = (1. - db / FLOOR_DB).clamp(0., 1.);
+
+            // Rise immediately, but fall off slowly to reduce flickering.
+            *level = new_level.max(*level * DECAY);
+            *output = *level;
+        }
+
+        Ok(())
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn hysteresis(&self) -> f32 {
+        0.01
+    }
+}
+
+/// Split the spectrum into logarithmically spaced ranges of FFT bins.
+fn frequency_bands(count: usize) -> Vec<Range<usize>> {
+    let bin = |frequency: f32| (frequency * FFT_SIZE as f32 / SAMPLE_RATE as f32) as usize;
+    let edge =
+        |i: usize| MIN_FREQUENCY * (MAX_FREQUENCY / MIN_FREQUENCY).powf(i as f32 / count as f32);
+
+    (0..count)
+        .map(|i| {
+            let start = bin(edge(i));
+            start..bin(edge(i + 1)).max(start + 1)
+        })
+        .collect()
+}
@@ -0,0 +1,91 @@
+//! Per-zone white-point calibration.
+//!
+//! Different LEDs on the same board often show visibly different tints for the
+//! same color. Scaling factors for the red, green and blue channel of each
+//! zone can be configured in `~/.config/rgbfusion/calibration.toml`:
+//!
+//! ```toml
+//! [trx40]
+//! io = [1.0, 0.85, 0.7]
+//! header0 = [0.9, 1.0, 1.0]
+//! ```
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+
+use crate::{Config, Device, Rgb, Zone};
+
+/// Channel scaling factors of a single zone.
+struct ZoneCalibration {
+    device: Device,
+    zone: Zone,
+    factors: [f32; 3],
+}
+
+/// Calibration loaded from the configuration directory.
+static CALIBRATION: OnceLock<Vec<ZoneCalibration>> = OnceLock::new();
+
+/// Get the calibration of all zones.
+fn calibration() -> &'static [ZoneCalibration] {
+    CALIBRATION.get_or_init(|| match load() {
+        Ok(calibration) => calibration,
+        Err(err) => {
+            eprintln!("Ignoring calibration: {err}");
+            Vec::new()
+        },
+    })
+}
+
+/// Load the calibration file.
+fn load() -> Result<Vec<ZoneCalibration>, Box<dyn Error>> {
+    let path = match crate::config_dir() {
+        Some(config_dir) => config_dir.join("calibration.toml"),
+        None => return Ok(Vec::new()),
+    };
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let devices: BTreeMap<String, BTreeMap<String, [f32; 3]>> = toml::from_str(&content)?;
+
+    let mut calibration = Vec::new();
+    for (device, zones) in devices {
+        let device = device.parse::<Device>()?;
+        for (zone, factors) in zones {
+            let zone = Zone::from_str(&zone, true).map_err(|_| format!("unknown zone: {zone}"))?;
+            if factors.iter().any(|factor| !(0.0..=1.).contains(factor)) {
+                return Err(format!("calibration of {device:?} {zone:?} must be in 0..=1").into());
+            }
+            calibration.push(ZoneCalibration { device, zone, factors });
+        }
+    }
+
+    Ok(calibration)
+}
+
+/// Scale all colors of a config by its zone's calibration.
+pub fn correct(config: &Config) -> Config {
+    let calibration = calibration()
+        .iter()
+        .find(|calibration| calibration.device == config.device && calibration.zone == config.zone);
+    match calibration {
+        Some(calibration) => apply(config, calibration.factors),
+        None => config.clone(),
+    }
+}
+
+/// Scale all colors of a config.
+fn apply(config: &Config, [r, g, b]: [f32; 3]) -> Config {
+    let channel = |value: u8, factor: f32| (value as f32 * factor).round() as u8;
+    config.map_colors(|color| Rgb {
+        r: channel(color.r, r),
+        g: channel(color.g, g),
+        b: channel(color.b, b),
+    })
+}
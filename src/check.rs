@@ -0,0 +1,133 @@
+//! Validation of configuration files and profiles.
+//!
+//! Every zone is checked against the capabilities of its controller and
+//! encoded without writing anything, so all problems of a file are reported at
+//! once.
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::process;
+
+use clap::{Arg, ArgMatches, Command};
+
+use crate::config_file::DeviceConfigs;
+use crate::{effect, profile, Config, Device, Effect};
+
+/// Get clap CLI parameters for the check subcommand.
+pub fn subcommand() -> Command {
+    Command::new("check")
+        .about("Validate a configuration file or profile without applying it")
+        .arg(Arg::new("file").help("Configuration file or profile name").required(true))
+}
+
+/// Run the check subcommand.
+pub fn check(matches: &ArgMatches) {
+    match run(matches) {
+        Ok(0) => println!("\x1b[32mNo problems found.\x1b[0m"),
+        Ok(problems) => {
+            let noun = if problems == 1 { "problem" } else { "problems" };
+            eprintln!("\nFound {problems} {noun}.");
+            process::exit(1);
+        },
+        Err(err) => {
+            eprintln!("\x1b[31mError:\x1b[0m {err}");
+            process::exit(1);
+        },
+    }
+}
+
+/// Report all problems of a file, returning their number.
+fn run(matches: &ArgMatches) -> Result<usize, Box<dyn Error>> {
+    let name = matches.get_one::<String>("file").unwrap();
+    let path = match PathBuf::from(name) {
+        path if path.exists() => path,
+        _ => profile::profile_path(name)
+            .ok()
+            .filter(|path| path.exists())
+            .ok_or_else(|| format!("no configuration file or profile named '{name}'"))?,
+    };
+
+    let file = DeviceConfigs::load(&path)?;
+    let selected = matches.get_one::<Device>("device").copied();
+
+    let mut problems = 0;
+    for (i, file) in file.files.into_iter().enumerate() {
+        let device = match file.device.or(selected) {
+            Some(device) => device,
+            None => {
+                error(
+                    &format!("device {}", i + 1),
+                    "no device given, use `--device` to select one",
+                );
+                problems += 1;
+                continue;
+            },
+        };
+
+        if !file.applies() {
+            println!("{device:?}: conditions don't match this system, it will be skipped");
+        }
+
+        let configs = file.into_unvalidated_configs(device);
+        for (j, config) in configs.iter().enumerate() {
+            let location = format!("{device:?} zone {:?}", config.zone);
+
+            if configs[j + 1..].iter().any(|other| other.zone == config.zone) {
+                warning(&location, "configured multiple times, only the last config is kept");
+            }
+
+            if effect::is_emulated(config) {
+                let effect = config.effect;
+                warning(&location, &format!("{effect:?} is emulated, which requires a daemon"));
+            }
+
+            let zone_problems = check_config(config);
+            for problem in &zone_problems {
+                error(&location, problem);
+            }
+            problems += zone_problems.len();
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Get all problems of a single zone config.
+fn check_config(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if let Err(err) = config.device.controller().validate(config) {
+        problems.push(err.to_string());
+        return problems;
+    }
+
+    if config.min_brightness.0 > config.max_brightness.0 {
+        problems.push(format!(
+            "min_brightness {} is above max_brightness {}",
+            config.min_brightness, config.max_brightness
+        ));
+    }
+
+    let animated = !matches!(config.effect, Effect::Off | Effect::Static);
+    let timings = [config.fade_in_time, config.fade_out_time, config.hold_time];
+    if animated && timings.iter().all(|timing| timing.0 == 0) {
+        problems.push(format!("{:?} effect needs a fade or hold time above 0", config.effect));
+    }
+
+    // Encode the config to catch errors specific to the controller.
+    if !effect::is_emulated(config) {
+        if let Err(err) = crate::config_packets(config) {
+            problems.push(err.to_string());
+        }
+    }
+
+    problems
+}
+
+fn error(location: &str, message: &str) {
+    eprintln!("\x1b[31mError:\x1b[0m {location}: {message}");
+}
+
+fn warning(location: &str, message: &str) {
+    eprintln!("\x1b[33mWarning:\x1b[0m {location}: {message}");
+}
@@ -0,0 +1,179 @@
+//! Conditions for applying parts of a configuration file.
+//!
+//! Every device of a profile can be limited to certain systems or times:
+//!
+//! ```toml
+//! [[device]]
+//! device = "trx40"
+//! when = { hostname = "desktop", power = "ac", weekdays = "mon-fri" }
+//! ```
+//!
+//! Devices whose conditions don't match are skipped, which allows sharing a
+//! single profile between multiple machines.
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::schedule::LocalTime;
+
+/// Names of the weekdays, starting with Sunday.
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Requirements for applying a configuration.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Condition {
+    /// Hostname of the system, ignoring case.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hostname: Option<String>,
+    /// Active power source.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    power: Option<Power>,
+    /// Days of the week.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weekdays: Option<Weekdays>,
+}
+
+impl Condition {
+    /// Check if the condition is met by the current system.
+    pub fn matches(&self) -> bool {
+        let hostname =
+            self.hostname.as_ref().is_none_or(|name| hostname().eq_ignore_ascii_case(name));
+        let power = self.power.is_none_or(|power| power == Power::current());
+        let weekdays = self.weekdays.is_none_or(|days| days.contains(LocalTime::now().weekday));
+        hostname && power && weekdays
+    }
+}
+
+/// Get the hostname of the system.
+fn hostname() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname").unwrap_or_default().trim().into()
+}
+
+/// System power source.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Copy, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum Power {
+    Ac,
+    Battery,
+}
+
+impl Power {
+    /// Get the active power source.
+    ///
+    /// Systems without any mains power supply, like most desktops, are always
+    /// considered to be on AC.
+    fn current() -> Self {
+        let read = |path: &Path, name: &str| {
+            fs::read_to_string(path.join(name)).map(|value| value.trim().to_string())
+        };
+
+        let mut mains = false;
+        for path in fs::read_dir("/sys/class/power_supply")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+        {
+            if read(&path, "type").is_ok_and(|kind| kind == "Mains") {
+                mains = true;
+                if read(&path, "online").is_ok_and(|online| online == "1") {
+                    return Self::Ac;
+                }
+            }
+        }
+
+        if mains {
+            Self::Battery
+        } else {
+            Self::Ac
+        }
+    }
+}
+
+/// Set of weekdays, like `mon-fri` or `sat,sun`.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct Weekdays(u8);
+
+impl Weekdays {
+    /// Check if a weekday is part of the set, starting with Sunday as 0.
+    fn contains(self, weekday: i32) -> bool {
+        (0..7).contains(&weekday) && self.0 & (1 << weekday) != 0
+    }
+}
+
+impl FromStr for Weekdays {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let day = |name: &str| {
+            let name = name.trim().to_lowercase();
+            WEEKDAYS
+                .iter()
+                .position(|day| name.starts_with(day))
+                .ok_or_else(|| format!("unknown weekday '{name}'"))
+        };
+
+        let mut days = 0;
+        for range in s.split(',') {
+            let (start, end) = match range.split_once('-') {
+                Some((start, end)) => (day(start)?, day(end)?),
+                None => (day(range)?, day(range)?),
+            };
+
+            // Ranges can wrap around the end of the week, like `fri-mon`.
+            let mut weekday = start;
+            loop {
+                days |= 1 << weekday;
+                if weekday == end {
+                    break;
+                }
+                weekday = (weekday + 1) % 7;
+            }
+        }
+
+        Ok(Self(days))
+    }
+}
+
+impl Display for Weekdays {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let days: Vec<_> =
+            (0..7).filter(|day| self.contains(*day)).map(|day| WEEKDAYS[day as usize]).collect();
+        f.write_str(&days.join(","))
+    }
+}
+
+impl Serialize for Weekdays {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Weekdays {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let days = String::deserialize(deserializer)?;
+        Weekdays::from_str(&days).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weekday_ranges() {
+        let workdays = Weekdays::from_str("mon-fri").unwrap();
+        assert!(workdays.contains(1) && workdays.contains(5));
+        assert!(!workdays.contains(0) && !workdays.contains(6));
+
+        assert_eq!(Weekdays::from_str("fri-mon").unwrap().to_string(), "sun,mon,fri,sat");
+        assert_eq!(Weekdays::from_str("Saturday, sun").unwrap().to_string(), "sun,sat");
+        assert!(Weekdays::from_str("someday").is_err());
+    }
+}
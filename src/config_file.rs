@@ -0,0 +1,271 @@
+//! TOML configuration files.
+//!
+//! A configuration file applies one or more zone configurations at once:
+//!
+//! ```toml
+//! device = "x670ef"
+//!
+//! [[zone]]
+//! zone = "io"
+//! effect = "static"
+//! color = "0xff0000"
+//!
+//! [[zone]]
+//! zone = "header0"
+//! effect = "pulse"
+//! color = "0x0000ff"
+//! min_brightness = 32
+//! hold_time = 500
+//! ```
+//!
+//! The same schema is used for profiles, saved state, daemon requests and the
+//! HTTP API. Files without a `version` are treated as the current version,
+//! while files written by a newer release are rejected.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use clap::ArgMatches;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::condition::Condition;
+use crate::{Config, Device};
+
+/// Current version of the configuration schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Zone configurations loaded from a file.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Schema version the file was written with.
+    #[serde(default = "schema_version", deserialize_with = "deserialize_version")]
+    version: u32,
+
+    /// Device all zones belong to, detected automatically if omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<Device>,
+
+    /// Requirements for applying the zones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    when: Option<Condition>,
+
+    #[serde(default, rename = "zone")]
+    zones: Vec<Config>,
+}
+
+impl ConfigFile {
+    /// Create a configuration file without any zones.
+    pub fn new(device: Device) -> Self {
+        Self { version: SCHEMA_VERSION, device: Some(device), when: None, zones: Vec::new() }
+    }
+
+    /// Write the configuration file to disk.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string(self)?)
+            .map_err(|err| format!("unable to write {}: {err}", path.display()).into())
+    }
+
+    /// Add a zone configuration, replacing any previous config for the same
+    /// zone.
+    pub fn insert(&mut self, config: Config) {
+        match self.zones.iter_mut().find(|zone| zone.zone == config.zone) {
+            Some(zone) => *zone = config,
+            None => self.zones.push(config),
+        }
+    }
+
+    /// Load a configuration file.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("unable to read {}: {err}", path.display()))?;
+        toml::from_str(&content)
+            .map_err(|err| format!("invalid config {}: {err}", path.display()).into())
+    }
+
+    /// Get all zone configurations, with the device resolved.
+    ///
+    /// The `--device` CLI option takes precedence over the device in the file.
+    pub fn configs(self, matches: &ArgMatches) -> Result<Vec<Config>, Box<dyn Error>> {
+        let device = match self.device {
+            Some(device) if !matches.contains_id("device") => device,
+            _ => crate::required_device(matches)?,
+        };
+
+        self.into_configs(device)
+    }
+
+    /// Get all zone configurations for a specific device.
+    pub fn into_configs(self, device: Device) -> Result<Vec<Config>, Box<dyn Error>> {
+        let configs = self.into_unvalidated_configs(device);
+        for config in &configs {
+            device.controller().validate(config)?;
+        }
+
+        Ok(configs)
+    }
+
+    /// Get all zone configurations for a device, without checking if the
+    /// device supports them.
+    pub fn into_unvalidated_configs(self, device: Device) -> Vec<Config> {
+        let mut configs = self.zones;
+        for config in &mut configs {
+            config.device = device;
+        }
+        configs
+    }
+
+    /// Check if the file's conditions are met by the current system.
+    pub fn applies(&self) -> bool {
+        self.when.as_ref().is_none_or(Condition::matches)
+    }
+}
+
+/// Zone configurations of multiple devices.
+///
+/// Files with a single device use the [`ConfigFile`] format, while multiple
+/// devices are stored in separate `[[device]]` tables.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceConfigs {
+    #[serde(default, rename = "device")]
+    pub files: Vec<ConfigFile>,
+}
+
+impl DeviceConfigs {
+    /// Write the configuration file to disk.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        match self.files.as_slice() {
+            [file] => file.save(path),
+            _ => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                fs::write(path, toml::to_string(self)?)
+                    .map_err(|err| format!("unable to write {}: {err}", path.display()).into())
+            },
+        }
+    }
+
+    /// Load a configuration file with one or more devices.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("unable to read {}: {err}", path.display()))?;
+
+        // Multiple devices are stored as array of tables.
+        let table: toml::Table = toml::from_str(&content)
+            .map_err(|err| format!("invalid config {}: {err}", path.display()))?;
+        let result = match table.get("device") {
+            Some(toml::Value::Array(_)) => toml::from_str(&content),
+            _ => toml::from_str(&content).map(|file| Self { files: vec![file] }),
+        };
+
+        result.map_err(|err| format!("invalid config {}: {err}", path.display()).into())
+    }
+
+    /// Get the configuration of a device, adding it if it doesn't exist yet.
+    pub fn entry(&mut self, device: Device) -> &mut ConfigFile {
+        match self.files.iter().position(|file| file.device == Some(device)) {
+            Some(index) => &mut self.files[index],
+            None => {
+                self.files.push(ConfigFile::new(device));
+                self.files.last_mut().unwrap()
+            },
+        }
+    }
+
+    /// Remove the configuration of a device.
+    pub fn take(&mut self, device: Device) -> Option<ConfigFile> {
+        let index = self.files.iter().position(|file| file.device == Some(device))?;
+        Some(self.files.remove(index))
+    }
+
+    /// Get all zone configurations, grouped by device.
+    ///
+    /// Devices whose conditions don't match the system are skipped. With
+    /// multiple devices, the `--device` CLI option selects one of them.
+    pub fn configs(self, matches: &ArgMatches) -> Result<Vec<Vec<Config>>, Box<dyn Error>> {
+        let multiple = self.files.len() > 1;
+        let mut files: Vec<_> = self.files.into_iter().filter(ConfigFile::applies).collect();
+
+        if files.is_empty() {
+            return Err("no configuration matches the conditions of this system".into());
+        } else if !multiple {
+            return Ok(vec![files.remove(0).configs(matches)?]);
+        }
+
+        let selected = matches.get_one::<Device>("device");
+        let mut configs = Vec::new();
+        for file in files {
+            let device = file.device.ok_or("device must be specified for every [[device]]")?;
+            if selected.is_none_or(|selected| *selected == device) {
+                configs.push(file.into_configs(device)?);
+            }
+        }
+
+        match (configs.is_empty(), selected) {
+            (true, Some(device)) => Err(format!("no configuration for device {device:?}").into()),
+            _ => Ok(configs),
+        }
+    }
+}
+
+fn schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+/// Reject files written with an unknown schema version.
+fn deserialize_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    match u32::deserialize(deserializer)? {
+        version @ 1..=SCHEMA_VERSION => Ok(version),
+        version => Err(D::Error::custom(format!(
+            "unsupported schema version {version}, expected at most {SCHEMA_VERSION}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RgbDevice, Zone};
+
+    #[test]
+    fn schema_version() {
+        let file = toml::to_string(&ConfigFile::new(Device::Builtin(RgbDevice::Trx40))).unwrap();
+        assert!(file.starts_with("version = 1\n"));
+
+        let file: ConfigFile = toml::from_str("device = \"trx40\"").unwrap();
+        assert_eq!(file.version, SCHEMA_VERSION);
+
+        assert!(toml::from_str::<ConfigFile>("version = 2").is_err());
+        assert!(toml::from_str::<ConfigFile>("version = 0").is_err());
+    }
+
+    #[test]
+    fn multiple_devices() {
+        let path =
+            std::env::temp_dir().join(format!("rgbfusion-devices-{}.toml", std::process::id()));
+
+        // Files with a single device use the plain config format.
+        fs::write(&path, "device = \"trx40\"\n[[zone]]\nzone = \"io\"\n").unwrap();
+        let mut configs = DeviceConfigs::load(&path).unwrap();
+        assert_eq!(configs.files.len(), 1);
+
+        let device = Device::Builtin(RgbDevice::Z390);
+        configs.entry(device).insert(Config { device, zone: Zone::Cpu, ..Default::default() });
+        configs.save(&path).unwrap();
+
+        let mut configs = DeviceConfigs::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(configs.take(Device::Builtin(RgbDevice::Trx40)).is_some());
+        assert!(configs.take(device).is_some());
+        assert!(configs.files.is_empty());
+    }
+}
@@ -1,19 +1,517 @@
 //! RGB controller abstraction.
 
 use std::error::Error;
+use std::sync::{Arc, Mutex};
 
 use bytes::Bytes;
+use clap::ValueEnum;
+use hidapi::{HidApi, HidDevice};
+use serde::{Deserialize, Serialize};
 
-use crate::Config;
+use crate::smbus::SmbusDevice;
+use crate::strip::HeaderConfig;
+use crate::{strip, Config, Duration, Effect, HidSelector, Rgb, Speed, Zone};
+
+/// Largest feature report which can be updated in place.
+const MAX_FEATURE_REPORT_SIZE: usize = 1024;
+
+/// How an effect is rendered on a controller.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum EffectMode {
+    /// Effect is handled by the controller's firmware.
+    Hardware,
+    /// Effect is rendered in software by writing static colors.
+    Emulated,
+}
+
+/// Transfer used for sending a packet.
+#[derive(ValueEnum, Serialize, Deserialize, Default, PartialEq, Eq, Debug, Copy, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transport {
+    /// Interrupt write to the output endpoint.
+    #[default]
+    Write,
+    /// Feature report sent over the control endpoint.
+    FeatureReport,
+    /// Feature report read from the control endpoint, with the report ID as
+    /// first byte.
+    GetFeatureReport,
+    /// SMBus byte write, with the command as first byte.
+    #[value(skip)]
+    SmbusByte,
+    /// SMBus little-endian word write, with the command as first byte.
+    #[value(skip)]
+    SmbusWord,
+    /// SMBus byte write to a single device, with its address and the command
+    /// as first two bytes.
+    #[value(skip)]
+    SmbusAddressedByte,
+    /// Feature report read from the device and written back with some bytes
+    /// replaced, with the report ID and offset of the replacement as first
+    /// two bytes.
+    #[value(skip)]
+    PatchFeatureReport,
+}
+
+/// Open connection to a controller.
+pub enum DeviceHandle {
+    /// HID device, shared with the threads running its timed operations.
+    Hid(Arc<Mutex<HidDevice>>),
+    Smbus(SmbusDevice),
+}
+
+impl DeviceHandle {
+    pub fn hid(device: HidDevice) -> Self {
+        Self::Hid(Arc::new(Mutex::new(device)))
+    }
+}
+
+/// Controller-specific packet.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Packet {
+    pub transport: Transport,
+    pub bytes: Bytes,
+}
+
+impl Packet {
+    pub fn new(transport: Transport, bytes: Bytes) -> Self {
+        Self { transport, bytes }
+    }
+
+    /// Send the packet to a device.
+    pub fn send(&self, device: &DeviceHandle) -> Result<(), Box<dyn Error>> {
+        match (device, self.transport, &self.bytes[..]) {
+            (DeviceHandle::Hid(device), Transport::Write, bytes) => {
+                device.lock().unwrap().write(bytes).map(drop)?;
+            },
+            (DeviceHandle::Hid(device), Transport::FeatureReport, bytes) => {
+                device.lock().unwrap().send_feature_report(bytes)?;
+            },
+            (DeviceHandle::Hid(device), Transport::GetFeatureReport, bytes) => {
+                device.lock().unwrap().get_feature_report(&mut bytes.to_vec()).map(drop)?;
+            },
+            (
+                DeviceHandle::Hid(device),
+                Transport::PatchFeatureReport,
+                &[id, offset, ref bytes @ ..],
+            ) => {
+                let device = device.lock().unwrap();
+                let mut report = vec![0; MAX_FEATURE_REPORT_SIZE];
+                report[0] = id;
+                let len = device.get_feature_report(&mut report)?;
+                report.truncate(len);
+
+                let range = offset as usize..offset as usize + bytes.len();
+                report.get_mut(range).ok_or("patch exceeds feature report")?.copy_from_slice(bytes);
+                device.send_feature_report(&report)?;
+            },
+            (DeviceHandle::Smbus(device), Transport::SmbusByte, &[command, value]) => {
+                device.write_byte_data(command, value)?;
+            },
+            (DeviceHandle::Smbus(device), Transport::SmbusWord, &[command, low, high]) => {
+                device.write_word_data(command, u16::from_le_bytes([low, high]))?;
+            },
+            (
+                DeviceHandle::Smbus(device),
+                Transport::SmbusAddressedByte,
+                &[address, command, value],
+            ) => {
+                device.write_byte_data_to(address, command, value)?;
+            },
+            (_, transport, _) => {
+                return Err(format!("invalid {transport:?} packet for this device").into());
+            },
+        }
+        Ok(())
+    }
+}
+
+impl From<Bytes> for Packet {
+    fn from(bytes: Bytes) -> Self {
+        Self::new(Transport::Write, bytes)
+    }
+}
 
 /// HID RGB controller.
 pub(crate) trait HidController {
-    /// HID vendor ID.
-    fn vendor_id(&self) -> u16;
+    /// Human-readable device name.
+    fn name(&self) -> &str;
+
+    /// HID vendor ID, `None` for controllers outside the HID bus.
+    fn vendor_id(&self) -> Option<u16>;
+
+    /// HID product ID, `None` for controllers outside the HID bus.
+    fn product_id(&self) -> Option<u16>;
+
+    /// All HID product IDs using this controller's protocol.
+    fn product_ids(&self) -> Vec<u16> {
+        self.product_id().into_iter().collect()
+    }
+
+    /// SMBus addresses probed for controllers outside the HID bus.
+    fn smbus_addresses(&self) -> &[u8] {
+        &[]
+    }
+
+    /// Bus location of the controller, as shown to users.
+    fn location(&self) -> String {
+        match self.vendor_id().zip(self.product_id()) {
+            Some((vendor_id, product_id)) => format!("HID: {vendor_id:04x}:{product_id:04x}"),
+            None => {
+                let addresses: Vec<_> = self
+                    .smbus_addresses()
+                    .iter()
+                    .map(|address| format!("0x{address:02x}"))
+                    .collect();
+                format!("SMBus: {}", addresses.join(", "))
+            },
+        }
+    }
+
+    /// DMI board name prefix, for controllers sharing their HID IDs with
+    /// other boards.
+    fn board_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// RGB zones available on this controller.
+    fn supported_zones(&self) -> &[Zone];
+
+    /// RGB zones in their physical order on the board, used for gradients.
+    fn physical_zones(&self) -> &[Zone] {
+        self.supported_zones()
+    }
+
+    /// Color effects supported by the controller's firmware.
+    fn supported_effects(&self) -> &[Effect];
+
+    /// Open the controller's device.
+    fn open(&self, api: &HidApi, selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        let (Some(vendor_id), Some(default_product_id)) = (self.vendor_id(), self.product_id())
+        else {
+            return Err(format!("{} is not a HID device", self.name()).into());
+        };
+
+        // Use the first product ID present on the HID bus.
+        let product_id = self
+            .product_ids()
+            .into_iter()
+            .find(|product_id| {
+                api.device_list().any(|info| {
+                    info.vendor_id() == vendor_id
+                        && info.product_id() == *product_id
+                        && selector.matches(info)
+                })
+            })
+            .unwrap_or(default_product_id);
+
+        Ok(DeviceHandle::hid(selector.open(api, vendor_id, product_id)?))
+    }
+
+    /// Convert RGB config to controller-specific packets.
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>>;
+
+    /// Read the applied config back from the device.
+    ///
+    /// Returns `None` for controllers which can't report their state, or an
+    /// error describing all mismatches.
+    fn verify(&self, _device: &HidDevice, _config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+        None
+    }
+
+    /// Maximum number of LEDs of an addressable zone.
+    ///
+    /// Returns `None` for controllers without per-LED control.
+    fn max_leds(&self) -> Option<usize> {
+        None
+    }
+
+    /// Packets persisting the written configs across reboots.
+    ///
+    /// These are only sent after configs applied by the user, not for every
+    /// frame of software effects.
+    fn commit_bytes(&self) -> Vec<Packet> {
+        Vec::new()
+    }
+
+    /// Convert individual LED colors of an addressable zone to packets.
+    ///
+    /// Colors are already in the channel order of the zone's strip. Returns
+    /// `None` for controllers without per-LED control.
+    fn led_bytes(
+        &self,
+        _zone: Zone,
+        _colors: &[Rgb],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        None
+    }
+
+    /// Convert the strip configuration of all addressable headers to packets.
+    ///
+    /// Returns `None` for controllers which don't need to know their strips.
+    fn header_bytes(
+        &self,
+        _headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        None
+    }
+
+    /// Check if the controller can report its state for verification.
+    fn supports_read_back(&self) -> bool {
+        false
+    }
+
+    /// Check if the controller can set the brightness of a zone.
+    ///
+    /// Brightness is emulated by dimming the color on controllers without
+    /// brightness support.
+    fn supports_brightness(&self) -> bool {
+        true
+    }
+
+    /// Check if the controller has a white channel for RGBW strips.
+    fn supports_white(&self) -> bool {
+        false
+    }
+
+    /// Get the fade in, fade out and hold time of an effect speed preset.
+    fn speed_timings(&self, speed: Speed) -> (Duration, Duration, Duration) {
+        match speed {
+            Speed::Slow => (Duration(2000), Duration(2000), Duration(1000)),
+            Speed::Medium => (Duration(1000), Duration(1000), Duration(500)),
+            Speed::Fast => (Duration(400), Duration(400), Duration(200)),
+            Speed::Turbo => (Duration(150), Duration(150), Duration(100)),
+        }
+    }
+
+    /// Get the rendering mode of an effect, `None` if it is not available.
+    ///
+    /// All effects can be emulated on controllers supporting static colors.
+    fn effect_mode(&self, effect: Effect) -> Option<EffectMode> {
+        if self.supported_effects().contains(&effect) {
+            Some(EffectMode::Hardware)
+        } else if self.supported_effects().contains(&Effect::Static) {
+            Some(EffectMode::Emulated)
+        } else {
+            None
+        }
+    }
+
+    /// Color effects available on this controller, including emulated ones.
+    fn available_effects(&self) -> Vec<Effect> {
+        let effects = Effect::value_variants().iter().copied();
+        effects.filter(|effect| self.effect_mode(*effect).is_some()).collect()
+    }
+
+    /// Ensure the config only uses zones and effects available on this
+    /// controller.
+    fn validate(&self, config: &Config) -> Result<(), Box<dyn Error>> {
+        if !self.supported_zones().contains(&config.zone) {
+            return Err(format!("unsupported zone for {}: {:?}", self.name(), config.zone).into());
+        }
+
+        if self.effect_mode(config.effect).is_none() {
+            return Err(
+                format!("unsupported effect for {}: {:?}", self.name(), config.effect).into()
+            );
+        }
+
+        if strip::strip_type(config.device, config.zone).has_white() && !self.supports_white() {
+            return Err(format!("RGBW strips are not supported by {}", self.name()).into());
+        }
+
+        if !config.palette.is_empty() {
+            if config.effect != Effect::Cycle {
+                return Err("palettes require the cycle effect".into());
+            }
+
+            // Palettes are emulated using static colors.
+            if !self.supported_effects().contains(&Effect::Static) {
+                return Err(format!("palettes are not supported by {}", self.name()).into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Controller driving a device with different vendor and product IDs.
+pub struct IdOverride {
+    pub controller: Box<dyn HidController>,
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
+
+impl HidController for IdOverride {
+    fn name(&self) -> &str {
+        self.controller.name()
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(self.vendor_id)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(self.product_id)
+    }
+
+    fn board_name(&self) -> Option<&str> {
+        self.controller.board_name()
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        self.controller.supported_zones()
+    }
+
+    fn physical_zones(&self) -> &[Zone] {
+        self.controller.physical_zones()
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        self.controller.supported_effects()
+    }
+
+    fn open(&self, api: &HidApi, selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        Ok(DeviceHandle::hid(selector.open(api, self.vendor_id, self.product_id)?))
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        self.controller.config_bytes(config)
+    }
+
+    fn verify(&self, device: &HidDevice, config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+        self.controller.verify(device, config)
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        self.controller.max_leds()
+    }
+
+    fn commit_bytes(&self) -> Vec<Packet> {
+        self.controller.commit_bytes()
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        self.controller.led_bytes(zone, colors)
+    }
+
+    fn header_bytes(
+        &self,
+        headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        self.controller.header_bytes(headers)
+    }
+
+    fn supports_read_back(&self) -> bool {
+        self.controller.supports_read_back()
+    }
+
+    fn supports_brightness(&self) -> bool {
+        self.controller.supports_brightness()
+    }
+
+    fn supports_white(&self) -> bool {
+        self.controller.supports_white()
+    }
+
+    fn speed_timings(&self, speed: Speed) -> (Duration, Duration, Duration) {
+        self.controller.speed_timings(speed)
+    }
+
+    fn effect_mode(&self, effect: Effect) -> Option<EffectMode> {
+        self.controller.effect_mode(effect)
+    }
+}
+
+impl<T: HidController + ?Sized> HidController for &T {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        (**self).vendor_id()
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        (**self).product_id()
+    }
+
+    fn product_ids(&self) -> Vec<u16> {
+        (**self).product_ids()
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        (**self).smbus_addresses()
+    }
+
+    fn location(&self) -> String {
+        (**self).location()
+    }
+
+    fn board_name(&self) -> Option<&str> {
+        (**self).board_name()
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        (**self).supported_zones()
+    }
+
+    fn physical_zones(&self) -> &[Zone] {
+        (**self).physical_zones()
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        (**self).supported_effects()
+    }
+
+    fn open(&self, api: &HidApi, selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        (**self).open(api, selector)
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        (**self).config_bytes(config)
+    }
+
+    fn verify(&self, device: &HidDevice, config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+        (**self).verify(device, config)
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        (**self).max_leds()
+    }
+
+    fn commit_bytes(&self) -> Vec<Packet> {
+        (**self).commit_bytes()
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        (**self).led_bytes(zone, colors)
+    }
+
+    fn header_bytes(
+        &self,
+        headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        (**self).header_bytes(headers)
+    }
+
+    fn supports_read_back(&self) -> bool {
+        (**self).supports_read_back()
+    }
+
+    fn supports_brightness(&self) -> bool {
+        (**self).supports_brightness()
+    }
+
+    fn supports_white(&self) -> bool {
+        (**self).supports_white()
+    }
 
-    /// HID product ID.
-    fn product_id(&self) -> u16;
+    fn speed_timings(&self, speed: Speed) -> (Duration, Duration, Duration) {
+        (**self).speed_timings(speed)
+    }
 
-    /// Convert RGB config to controller-specific bytes.
-    fn config_bytes(&self, config: &Config) -> Result<Vec<Bytes>, Box<dyn Error>>;
+    fn effect_mode(&self, effect: Effect) -> Option<EffectMode> {
+        (**self).effect_mode(effect)
+    }
 }
@@ -0,0 +1,116 @@
+//! Corsair iCUE Elite Capellix AIO pump head control.
+//!
+//! The pump head is controlled through the `cpu` zone. It has no hardware
+//! effects, so everything but static colors is emulated in software.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::controller::{HidController, Packet};
+use crate::{strip, Config, Effect, Rgb, Zone};
+
+/// Size of a single HID report, including the report ID.
+const PACKET_SIZE: usize = 97;
+
+/// Number of LEDs on the pump head.
+const PUMP_LEDS: usize = 29;
+
+/// Endpoint for writing LED colors.
+const COLOR_ENDPOINT: u8 = 0x22;
+
+/// Data type of LED colors.
+const COLOR_DATA: u8 = 0x12;
+
+pub struct CorsairCapellix;
+
+impl HidController for CorsairCapellix {
+    fn name(&self) -> &str {
+        "Corsair iCUE Elite Capellix"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x1B1C)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x0C1C)
+    }
+
+    fn product_ids(&self) -> Vec<u16> {
+        vec![0x0C1C, 0x0C1D, 0x0C1E]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Cpu]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static]
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let color = match config.effect {
+            Effect::Off => Rgb::default(),
+            _ => strip::strip_type(config.device, config.zone).encode(config.color).0,
+        };
+
+        color_packets(config.zone, &[color; PUMP_LEDS])
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(PUMP_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(color_packets(zone, colors))
+    }
+}
+
+/// Convert the colors of the pump head LEDs to packets.
+fn color_packets(zone: Zone, colors: &[Rgb]) -> Result<Vec<Packet>, Box<dyn Error>> {
+    if zone != Zone::Cpu {
+        return Err(format!("unsupported zone: {zone:?}").into());
+    }
+
+    if colors.len() > PUMP_LEDS {
+        return Err(format!("the pump head has only {PUMP_LEDS} LEDs").into());
+    }
+
+    // Switch to software mode and open the color endpoint.
+    let wake = packet(&[0x08, 0x01, 0x03, 0x00, 0x02]);
+    let open = packet(&[0x08, 0x0d, 0x00, COLOR_ENDPOINT]);
+
+    // Missing LEDs are turned off.
+    let mut data = BytesMut::with_capacity(PUMP_LEDS * 3);
+    for i in 0..PUMP_LEDS {
+        let color = colors.get(i).copied().unwrap_or_default();
+        data.put_slice(&[color.r, color.g, color.b]);
+    }
+
+    // Data length includes the data type.
+    let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+    buf.put_slice(&[0x00, 0x08, 0x06, 0x00]);
+    buf.put_u16_le(data.len() as u16 + 4);
+    buf.put_slice(&[0x00, 0x00, COLOR_DATA, 0x00]);
+    buf.put_slice(&data);
+    buf.resize(PACKET_SIZE, 0);
+    let write = buf.freeze().into();
+
+    let close = packet(&[0x08, 0x05, 0x01, 0x00]);
+
+    Ok(vec![wake, open, write, close])
+}
+
+/// Build a packet from a command and its arguments.
+fn packet(command: &[u8]) -> Packet {
+    let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+    buf.put_u8(0x00);
+    buf.put_slice(command);
+    buf.resize(PACKET_SIZE, 0);
+    buf.freeze().into()
+}
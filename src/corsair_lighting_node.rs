@@ -0,0 +1,146 @@
+//! Corsair Lighting Node Pro and Core RGB control.
+//!
+//! The two channels are mapped to the `header0` and `header1` zones, with the
+//! Lighting Node Core only having the first one.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::controller::{HidController, Packet};
+use crate::{strip, Brightness, Config, Duration, Effect, Zone};
+
+/// Size of a single HID report, including the report ID.
+const PACKET_SIZE: usize = 65;
+
+/// Maximum number of LEDs on a channel.
+const MAX_LEDS: u8 = 204;
+
+const COMMIT: u8 = 0x33;
+const EFFECT_CONFIG: u8 = 0x35;
+const RESET: u8 = 0x37;
+const PORT_STATE: u8 = 0x38;
+const BRIGHTNESS: u8 = 0x39;
+
+/// Port state for running effects on the controller.
+const HARDWARE_MODE: u8 = 0x01;
+
+pub struct CorsairLightingNode;
+
+impl HidController for CorsairLightingNode {
+    fn name(&self) -> &str {
+        "Corsair Lighting Node"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x1B1C)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x0C0B)
+    }
+
+    fn product_ids(&self) -> Vec<u16> {
+        vec![0x0C0B, 0x0C1A]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[
+            Effect::Off,
+            Effect::Static,
+            Effect::Pulse,
+            Effect::Flash,
+            Effect::Cycle,
+            Effect::Rainbow,
+            Effect::ChaseFade,
+            Effect::Chase,
+        ]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let strip_type = strip::strip_type(config.device, config.zone);
+        let channel = channel_bytes(config.zone)?;
+
+        // The controller has no off mode, so it is emulated with a black static color.
+        let (color, color2) = match config.effect {
+            Effect::Off => Default::default(),
+            _ => (strip_type.encode(config.color).0, strip_type.encode(config.color2).0),
+        };
+        let mode = effect_bytes(config.effect);
+
+        let mut effect = BytesMut::with_capacity(PACKET_SIZE);
+        effect.put_slice(&[0x00, EFFECT_CONFIG, channel]);
+
+        // Start and count of all LEDs on the channel.
+        effect.put_slice(&[0x00, MAX_LEDS]);
+
+        // Mode, speed, forward direction and custom colors.
+        effect.put_slice(&[mode, speed_bytes(config.fade_in_time), 0x01, 0x00, 0xff]);
+
+        effect.put_slice(&[color.r, color.g, color.b]);
+        effect.put_slice(&[color2.r, color2.g, color2.b]);
+        effect.put_slice(&[0x00, 0x00, 0x00]);
+
+        Ok(vec![
+            packet(&[RESET, channel]),
+            packet(&[PORT_STATE, channel, HARDWARE_MODE]),
+            padded(effect),
+            packet(&[BRIGHTNESS, channel, brightness_bytes(config.max_brightness)]),
+            packet(&[COMMIT, 0xff]),
+        ])
+    }
+}
+
+/// Build a packet from a command and its arguments.
+fn packet(command: &[u8]) -> Packet {
+    let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+    buf.put_u8(0x00);
+    buf.put_slice(command);
+    padded(buf)
+}
+
+/// Pad a packet to the report size.
+fn padded(mut buf: BytesMut) -> Packet {
+    buf.resize(PACKET_SIZE, 0);
+    buf.freeze().into()
+}
+
+/// Convert zone to the controller's channel.
+fn channel_bytes(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Header0 => Ok(0x00),
+        Zone::Header1 => Ok(0x01),
+        zone => Err(format!("unsupported zone: {zone:?}").into()),
+    }
+}
+
+/// Convert effect type to Lighting Node format.
+fn effect_bytes(effect: Effect) -> u8 {
+    match effect {
+        Effect::Rainbow => 0x00,
+        Effect::Cycle => 0x01,
+        Effect::Pulse => 0x02,
+        Effect::ChaseFade => 0x03,
+        Effect::Off | Effect::Static => 0x04,
+        Effect::Chase => 0x07,
+        Effect::Flash => 0x08,
+    }
+}
+
+/// Convert effect duration to the controller's speed, with 0 being the fastest.
+fn speed_bytes(duration: Duration) -> u8 {
+    match duration.0 {
+        1000.. => 2,
+        400..=999 => 1,
+        _ => 0,
+    }
+}
+
+/// Convert brightness to the controller's percentage.
+fn brightness_bytes(brightness: Brightness) -> u8 {
+    (100 * brightness.0 as u16 / u8::MAX as u16) as u8
+}
@@ -0,0 +1,212 @@
+//! Corsair Vengeance RGB Pro DRAM control.
+//!
+//! Every stick has its own SMBus address, all sticks found on the adapter are
+//! controlled together through the IO zone.
+
+use std::error::Error;
+
+use bytes::Bytes;
+use hidapi::HidApi;
+
+use crate::controller::{DeviceHandle, HidController, Packet, Transport};
+use crate::smbus::{self, SmbusDevice};
+use crate::{strip, Config, Duration, Effect, HidSelector, Rgb, Zone};
+
+/// Name prefixes of the chipset's SMBus adapters.
+const ADAPTERS: &[&str] = &["SMBus"];
+
+/// SMBus addresses used by the sticks.
+const ADDRESSES: [u8; 8] = [0x58, 0x59, 0x5a, 0x5b, 0x5c, 0x5d, 0x5e, 0x5f];
+
+/// Device IDs reported by Vengeance RGB Pro sticks.
+const DEVICE_IDS: [u8; 2] = [0x1b, 0x1c];
+
+/// Number of LEDs per stick.
+const LED_COUNT: usize = 10;
+
+const REG_MODE_SELECT: u8 = 0x26;
+const REG_MODE: u8 = 0x21;
+const REG_SPEED: u8 = 0x22;
+const REG_RANDOM: u8 = 0x23;
+const REG_DIRECTION: u8 = 0x24;
+const REG_COLOR: u8 = 0x30;
+const REG_COLOR2: u8 = 0x33;
+const REG_DIRECT: u8 = 0x31;
+const REG_DEVICE_ID: u8 = 0x43;
+const REG_APPLY: u8 = 0x82;
+
+const EFFECT_MODE: u8 = 0x01;
+const DIRECT_MODE: u8 = 0x02;
+const APPLY: u8 = 0x02;
+
+pub struct CorsairVengeancePro;
+
+impl HidController for CorsairVengeancePro {
+    fn name(&self) -> &str {
+        "Corsair Vengeance RGB Pro"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        &ADDRESSES
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[
+            Effect::Off,
+            Effect::Static,
+            Effect::Pulse,
+            Effect::Cycle,
+            Effect::Rainbow,
+            Effect::ChaseFade,
+            Effect::Chase,
+        ]
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn open(&self, _api: &HidApi, _selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        for adapter in smbus::adapters(ADAPTERS) {
+            let sticks: Vec<_> = ADDRESSES
+                .iter()
+                .copied()
+                .filter(|address| match SmbusDevice::open(&adapter, &[*address]) {
+                    Ok(device) => matches!(
+                        device.read_byte_data(REG_DEVICE_ID),
+                        Ok(id) if DEVICE_IDS.contains(&id)
+                    ),
+                    Err(_) => false,
+                })
+                .collect();
+
+            if !sticks.is_empty() {
+                return Ok(DeviceHandle::Smbus(SmbusDevice::open(&adapter, &sticks)?));
+            }
+        }
+
+        Err("no Vengeance RGB Pro DRAM found, is the `i2c-dev` module loaded?".into())
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let strip_type = strip::strip_type(config.device, config.zone);
+
+        // The sticks have no off mode, so it is emulated with a black static color.
+        let (color, color2) = match config.effect {
+            Effect::Off => Default::default(),
+            _ => (strip_type.encode(config.color).0, strip_type.encode(config.color2).0),
+        };
+
+        let writes = [
+            (REG_MODE_SELECT, EFFECT_MODE),
+            (REG_MODE, effect_bytes(config.effect)?),
+            (REG_SPEED, speed_bytes(config.fade_in_time)),
+            (REG_RANDOM, 0x00),
+            (REG_DIRECTION, 0x00),
+            (REG_COLOR, color.r),
+            (REG_COLOR + 1, color.g),
+            (REG_COLOR + 2, color.b),
+            (REG_COLOR2, color2.r),
+            (REG_COLOR2 + 1, color2.g),
+            (REG_COLOR2 + 2, color2.b),
+            (REG_APPLY, APPLY),
+        ];
+
+        Ok(writes.iter().map(|(reg, value)| register_packet(*reg, *value)).collect())
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(LED_COUNT)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(direct_packets(zone, colors))
+    }
+}
+
+/// Convert individual LED colors to direct mode packets.
+///
+/// Every stick receives the same colors, followed by their checksum.
+fn direct_packets(zone: Zone, colors: &[Rgb]) -> Result<Vec<Packet>, Box<dyn Error>> {
+    if zone != Zone::Io {
+        return Err(format!("unsupported zone: {zone:?}").into());
+    }
+
+    if colors.len() > LED_COUNT {
+        return Err(format!("Vengeance RGB Pro sticks have only {LED_COUNT} LEDs").into());
+    }
+
+    // Missing LEDs are turned off.
+    let mut data: Vec<_> = colors.iter().flat_map(|color| [color.r, color.g, color.b]).collect();
+    data.resize(LED_COUNT * 3, 0);
+    data.push(crc8(&data));
+
+    let mut packets = vec![register_packet(REG_MODE_SELECT, DIRECT_MODE)];
+    packets.extend(data.iter().map(|value| register_packet(REG_DIRECT, *value)));
+    packets.push(register_packet(REG_APPLY, APPLY));
+
+    Ok(packets)
+}
+
+/// Calculate the CRC-8 checksum of direct mode colors.
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |crc, byte| {
+        (0..8).fold(crc ^ byte, |crc, _| if crc & 0x80 != 0 { crc << 1 ^ 0x07 } else { crc << 1 })
+    })
+}
+
+/// Convert a register write to an SMBus packet.
+fn register_packet(register: u8, value: u8) -> Packet {
+    Packet::new(Transport::SmbusByte, Bytes::copy_from_slice(&[register, value]))
+}
+
+/// Convert effect type to Vengeance RGB Pro format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Cycle => Ok(0x00),
+        Effect::Pulse => Ok(0x01),
+        Effect::Rainbow => Ok(0x03),
+        Effect::ChaseFade => Ok(0x04),
+        Effect::Chase => Ok(0x07),
+        Effect::Off | Effect::Static => Ok(0x10),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Convert effect duration to the sticks' speed, with 0 being the slowest.
+fn speed_bytes(duration: Duration) -> u8 {
+    match duration.0 {
+        1000.. => 0,
+        400..=999 => 1,
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direct_mode_checksum() {
+        assert_eq!(crc8(b"123456789"), 0xf4);
+
+        let packets = direct_packets(Zone::Io, &[Rgb { r: 1, g: 2, b: 3 }]).unwrap();
+        assert_eq!(packets.len(), 1 + LED_COUNT * 3 + 1 + 1);
+        assert_eq!(packets[1].bytes[..], [REG_DIRECT, 1]);
+        assert_eq!(packets[4].bytes[..], [REG_DIRECT, 0]);
+
+        assert!(direct_packets(Zone::Io, &[Rgb::default(); 11]).is_err());
+    }
+}
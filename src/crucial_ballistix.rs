@@ -0,0 +1,153 @@
+//! Crucial Ballistix RGB DRAM control.
+//!
+//! Ballistix sticks use the register access of the Aura SMBus controller, with
+//! separate register blocks for the red, green and blue channel of their eight
+//! LEDs. All sticks are controlled together through the IO zone, either with a
+//! single color or with individual LED colors.
+
+use std::error::Error;
+
+use hidapi::HidApi;
+
+use crate::asus_aura_smbus::{self, register_packets};
+use crate::controller::{DeviceHandle, HidController, Packet};
+use crate::smbus::{self, SmbusDevice};
+use crate::{strip, Config, Effect, HidSelector, Rgb, Zone};
+
+/// SMBus addresses used by the sticks.
+const ADDRESSES: [u8; 8] = [0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27];
+
+/// Number of LEDs per stick.
+const LED_COUNT: usize = 8;
+
+const REG_MICRON_CHECK: u16 = 0x1030;
+const REG_MODE: u16 = 0x820f;
+const REG_DIRECT: u16 = 0x82ee;
+const REG_APPLY: u16 = 0x82f1;
+const REG_RED: u16 = 0x8300;
+const REG_GREEN: u16 = 0x8340;
+const REG_BLUE: u16 = 0x8380;
+
+/// Apply register value to show the written configuration.
+const APPLY: u8 = 0x01;
+
+pub struct CrucialBallistix;
+
+impl HidController for CrucialBallistix {
+    fn name(&self) -> &str {
+        "Crucial Ballistix RGB"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        &ADDRESSES
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle, Effect::Rainbow]
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn open(&self, _api: &HidApi, _selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        for adapter in smbus::adapters(asus_aura_smbus::ADAPTERS) {
+            let sticks: Vec<_> = ADDRESSES
+                .iter()
+                .copied()
+                .filter(|address| match SmbusDevice::open(&adapter, &[*address]) {
+                    Ok(device) => is_micron(&device),
+                    Err(_) => false,
+                })
+                .collect();
+
+            if !sticks.is_empty() {
+                return Ok(DeviceHandle::Smbus(SmbusDevice::open(&adapter, &sticks)?));
+            }
+        }
+
+        Err("no Ballistix RGB DRAM found, is the `i2c-dev` module loaded?".into())
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+
+        // Static colors are shown in direct mode, the sticks have no off mode.
+        let mode = match config.effect {
+            Effect::Off => return color_packets(&[Rgb::default(); LED_COUNT]),
+            Effect::Static => return color_packets(&[color; LED_COUNT]),
+            effect => effect_bytes(effect)?,
+        };
+
+        let mut packets = register_packets(REG_DIRECT, &[0x00]);
+        packets.append(&mut register_packets(REG_MODE, &[mode]));
+        packets.append(&mut channel_packets(&[color; LED_COUNT]));
+        packets.append(&mut register_packets(REG_APPLY, &[APPLY]));
+
+        Ok(packets)
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(LED_COUNT)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        if zone != Zone::Io {
+            return Some(Err(format!("unsupported zone: {zone:?}").into()));
+        }
+
+        Some(color_packets(colors))
+    }
+}
+
+/// Convert individual LED colors to direct mode packets.
+fn color_packets(colors: &[Rgb]) -> Result<Vec<Packet>, Box<dyn Error>> {
+    if colors.len() > LED_COUNT {
+        return Err(format!("Ballistix RGB sticks have only {LED_COUNT} LEDs").into());
+    }
+
+    let mut packets = register_packets(REG_DIRECT, &[0x01]);
+    packets.append(&mut channel_packets(colors));
+    packets.append(&mut register_packets(REG_APPLY, &[APPLY]));
+
+    Ok(packets)
+}
+
+/// Convert LED colors to writes of the channel register blocks.
+fn channel_packets(colors: &[Rgb]) -> Vec<Packet> {
+    let channel = |f: fn(&Rgb) -> u8| colors.iter().map(f).collect::<Vec<_>>();
+
+    let mut packets = register_packets(REG_RED, &channel(|color| color.r));
+    packets.append(&mut register_packets(REG_GREEN, &channel(|color| color.g)));
+    packets.append(&mut register_packets(REG_BLUE, &channel(|color| color.b)));
+
+    packets
+}
+
+/// Check if a device is a Ballistix stick, by reading its manufacturer.
+fn is_micron(device: &SmbusDevice) -> bool {
+    matches!(asus_aura_smbus::read_register(device, REG_MICRON_CHECK), Ok(b'M'))
+}
+
+/// Convert effect type to Ballistix format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Pulse => Ok(0x02),
+        Effect::Flash => Ok(0x03),
+        Effect::Cycle => Ok(0x04),
+        Effect::Rainbow => Ok(0x05),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
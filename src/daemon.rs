@@ -0,0 +1,794 @@
+//! Long-running daemon which keeps HID devices open.
+//!
+//! Clients connect to the daemon's Unix socket, send a configuration file and
+//! close their write half. The daemon responds with one line per zone, which is
+//! either `ok` or `error: <message>`.
+//!
+//! Running as root, the daemon can authorize requests of unprivileged users
+//! using polkit.
+//!
+//! When started by systemd socket activation, the daemon uses the inherited
+//! listening socket instead of binding its own.
+//!
+//! Optionally, the daemon can also be controlled over the OpenRGB SDK protocol
+//! and a JSON HTTP API.
+//!
+//! Since some boards lose their lighting state during suspend, the daemon can
+//! also listen for logind's `PrepareForSleep` signal and reapply all configs
+//! after resume.
+//!
+//! With `--hotplug`, udev is monitored for supported devices being connected,
+//! like external ARGB controllers or boards enumerated again after a BIOS
+//! update, applying their last configs or a profile automatically.
+//!
+//! To make notifications visible in muted setups, a zone can be lit up briefly
+//! whenever a desktop notification is sent.
+
+use std::error::Error;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::fd::{FromRawFd, RawFd};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{SocketAddr, UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::{self, Stdio};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{env, fs, mem, thread};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use hidapi::HidApi;
+
+use crate::config_file::ConfigFile;
+use crate::controller::DeviceHandle;
+use crate::{
+    effect, http, openrgb, polkit, profile, state, zone_map, Config, Device, Effect, Rgb, Zone,
+};
+
+/// D-Bus match rule for logind's sleep signal.
+const RESUME_MATCH_RULE: &str =
+    "type='signal',interface='org.freedesktop.login1.Manager',member='PrepareForSleep'";
+
+/// D-Bus match rule for new desktop notifications.
+const NOTIFICATION_MATCH_RULE: &str =
+    "type='method_call',interface='org.freedesktop.Notifications',member='Notify'";
+
+/// Directory of the socket of a daemon shared by all users.
+const SYSTEM_SOCKET_DIR: &str = "/run/rgbfusion";
+
+/// First file descriptor passed by systemd socket activation.
+const SD_LISTEN_FDS_START: RawFd = 3;
+
+/// Maximum time a client may take to send its request.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// First line of requests which don't change the recorded state.
+const TRANSIENT_REQUEST: &str = "transient\n";
+
+/// First line of requests persisting a device's written configs.
+const COMMIT_REQUEST: &str = "commit\n";
+
+/// Get clap CLI parameters for the daemon subcommand.
+pub fn subcommand() -> Command {
+    Command::new("daemon")
+        .about("Keep devices open and accept configs over a socket")
+        .arg(
+            Arg::new("openrgb")
+                .help("Serve the OpenRGB SDK protocol [default: 127.0.0.1:6742]")
+                .long("openrgb")
+                .value_name("address")
+                .num_args(0..=1)
+                .default_missing_value("127.0.0.1:6742"),
+        )
+        .arg(
+            Arg::new("http")
+                .help("Serve the HTTP API [default: 127.0.0.1:8742]")
+                .long("http")
+                .value_name("address")
+                .num_args(0..=1)
+                .default_missing_value("127.0.0.1:8742"),
+        )
+        .arg(
+            Arg::new("notify-zone")
+                .help("Light up a zone when a desktop notification is received")
+                .long("notify-zone")
+                .ignore_case(true)
+                .value_parser(crate::zone_parser()),
+        )
+        .arg(
+            Arg::new("notify-color")
+                .help("Color used for notifications [0xRRGGBB]")
+                .long("notify-color")
+                .requires("notify-zone")
+                .default_value("0xffffff"),
+        )
+        .arg(
+            Arg::new("notify-duration")
+                .help("Notification highlight duration in milliseconds")
+                .long("notify-duration")
+                .requires("notify-zone")
+                .value_parser(value_parser!(u64))
+                .default_value("1000"),
+        )
+        .arg(
+            Arg::new("reapply-on-resume")
+                .help("Reapply all configs after resuming from suspend")
+                .long("reapply-on-resume")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("polkit")
+                .help(format!(
+                    "Allow all users to connect, authorizing them with polkit's {}",
+                    polkit::ACTION_ID
+                ))
+                .long("polkit")
+                .conflicts_with_all(["openrgb", "http"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("hotplug")
+                .help("Apply the last configs to supported devices when they are connected")
+                .long("hotplug")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("hotplug-profile")
+                .help("Profile applied to connected devices instead of their last configs")
+                .long("hotplug-profile")
+                .value_name("profile")
+                .requires("hotplug"),
+        )
+}
+
+/// Run the daemon until it is killed.
+pub fn daemon(matches: &ArgMatches) {
+    if let Err(err) = run(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Check if the daemon is running.
+pub fn is_running() -> bool {
+    connect().is_some()
+}
+
+/// Send a config to the daemon, without recording it as the zone's state.
+///
+/// Returns `None` if the daemon is not running.
+pub fn send(config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+    let stream = connect()?;
+    Some(request(stream, TRANSIENT_REQUEST, &config_file(config)))
+}
+
+/// Apply a config through the daemon, recording it as the zone's state.
+///
+/// Returns `None` if the daemon is not running.
+pub fn apply(config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+    let stream = connect()?;
+    Some(request(stream, "", &config_file(config)))
+}
+
+/// Persist the configs written to a device across reboots.
+///
+/// Returns `None` if the daemon is not running.
+pub fn commit(device: Device) -> Option<Result<(), Box<dyn Error>>> {
+    let stream = connect()?;
+    Some(request(stream, COMMIT_REQUEST, &ConfigFile::new(device)))
+}
+
+/// Wrap a single config in a config file.
+fn config_file(config: &Config) -> ConfigFile {
+    let mut file = ConfigFile::new(config.device);
+    file.insert(config.clone());
+    file
+}
+
+/// Submit a request over an established daemon connection.
+///
+/// The kind is the first line of the request, like [`TRANSIENT_REQUEST`].
+fn request(mut stream: UnixStream, kind: &str, file: &ConfigFile) -> Result<(), Box<dyn Error>> {
+    stream.write_all(kind.as_bytes())?;
+    stream.write_all(toml::to_string(file)?.as_bytes())?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+
+    match response.trim_end().strip_prefix("error: ") {
+        Some(err) => Err(err.into()),
+        None if response.trim_end() == "ok" => Ok(()),
+        None => Err(format!("invalid daemon response: {response:?}").into()),
+    }
+}
+
+/// Connect to the daemon's Unix socket.
+///
+/// A daemon shared by all users takes precedence over the user's own daemon.
+fn connect() -> Option<UnixStream> {
+    if let Some(path) = env::var_os("RGBFUSION_SOCKET") {
+        return UnixStream::connect(path).ok();
+    }
+
+    let system_path = Path::new(SYSTEM_SOCKET_DIR).join("rgbfusion.sock");
+    UnixStream::connect(system_path)
+        .or_else(|_| UnixStream::connect(env::temp_dir().join("rgbfusion.sock")))
+        .ok()
+}
+
+/// Bind to the daemon's socket path.
+///
+/// With polkit, the socket is accessible to all users. To prevent other users
+/// from replacing it, it is placed in a directory only writable by root.
+fn bind_listener(polkit: bool) -> Result<UnixListener, Box<dyn Error>> {
+    let path = match env::var_os("RGBFUSION_SOCKET") {
+        Some(path) => PathBuf::from(path),
+        None if polkit => {
+            let directory = env::var_os("RUNTIME_DIRECTORY")
+                .map_or_else(|| PathBuf::from(SYSTEM_SOCKET_DIR), PathBuf::from);
+            fs::create_dir_all(&directory)?;
+            fs::set_permissions(&directory, fs::Permissions::from_mode(0o755))?;
+            directory.join("rgbfusion.sock")
+        },
+        None => env::temp_dir().join("rgbfusion.sock"),
+    };
+
+    // Remove stale sockets, without taking over from a running daemon.
+    if UnixStream::connect(&path).is_ok() {
+        return Err(format!("daemon is already listening on {}", path.display()).into());
+    }
+    let _ = fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path)
+        .map_err(|err| format!("unable to bind to {}: {err}", path.display()))?;
+
+    if polkit {
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o666))?;
+    }
+
+    Ok(listener)
+}
+
+/// Get the listening socket passed by systemd socket activation.
+fn activated_listener() -> Option<UnixListener> {
+    let pid = env::var("LISTEN_PID").ok()?.parse::<u32>().ok()?;
+    let fds = env::var("LISTEN_FDS").ok()?.parse::<u32>().ok()?;
+    if pid != process::id() || fds == 0 {
+        return None;
+    }
+
+    // Don't pass the sockets on to child processes.
+    for name in ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"] {
+        env::remove_var(name);
+    }
+
+    // Only take over a listening Unix stream socket, in case the environment
+    // was inherited from an unrelated process.
+    let family = socket_option(SD_LISTEN_FDS_START, libc::SO_DOMAIN);
+    let kind = socket_option(SD_LISTEN_FDS_START, libc::SO_TYPE);
+    let listening = socket_option(SD_LISTEN_FDS_START, libc::SO_ACCEPTCONN);
+    if family != Some(libc::AF_UNIX) || kind != Some(libc::SOCK_STREAM) || listening != Some(1) {
+        eprintln!("Ignoring invalid activation socket, binding a new socket instead");
+        return None;
+    }
+
+    unsafe { libc::fcntl(SD_LISTEN_FDS_START, libc::F_SETFD, libc::FD_CLOEXEC) };
+
+    // SAFETY: The descriptor was verified to be a listening Unix stream socket.
+    Some(unsafe { UnixListener::from_raw_fd(SD_LISTEN_FDS_START) })
+}
+
+/// Get an integer socket option, returning `None` if it isn't a socket.
+fn socket_option(fd: RawFd, option: libc::c_int) -> Option<libc::c_int> {
+    let mut value: libc::c_int = 0;
+    let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+
+    // SAFETY: The buffer matches the size of integer socket options.
+    let result = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            option,
+            &mut value as *mut _ as *mut libc::c_void,
+            &mut len,
+        )
+    };
+
+    (result == 0).then_some(value)
+}
+
+/// Start listening for client connections.
+fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let polkit = matches.get_flag("polkit");
+    let listener = match activated_listener() {
+        Some(listener) => listener,
+        None => bind_listener(polkit)?,
+    };
+
+    let daemon = Arc::new(Mutex::new(Daemon::new()?));
+    spawn_effect_engine(daemon.clone());
+
+    if let Some(address) = matches.get_one::<String>("openrgb") {
+        openrgb::spawn_server(address, daemon.clone())?;
+        println!("OpenRGB SDK server listening on {address}");
+    }
+
+    if let Some(address) = matches.get_one::<String>("http") {
+        http::spawn_server(address, daemon.clone())?;
+        println!("HTTP server listening on {address}");
+    }
+
+    if matches.get_flag("reapply-on-resume") {
+        spawn_resume_monitor(daemon.clone())?;
+    }
+
+    if matches.get_flag("hotplug") {
+        spawn_hotplug_monitor(daemon.clone(), matches.clone())?;
+    }
+
+    if let Some(zone) = matches.get_one::<String>("notify-zone") {
+        let color = matches.get_one::<String>("notify-color").unwrap();
+        let color = Rgb::from_str(color).map_err(|err| format!("invalid --notify-color: {err}"))?;
+
+        let device = daemon.lock().unwrap().resolve_device(matches.get_one("device").copied())?;
+        let zone = zone_map::zone(zone, Some(device))?;
+        let config = Config { device, zone, color, effect: Effect::Static, ..Default::default() };
+        device.controller().validate(&config)?;
+
+        let duration = Duration::from_millis(*matches.get_one::<u64>("notify-duration").unwrap());
+        spawn_notification_monitor(daemon.clone(), config, duration)?;
+    }
+
+    match listener.local_addr().ok().as_ref().and_then(SocketAddr::as_pathname) {
+        Some(path) => println!("Listening on {}", path.display()),
+        None => println!("Listening on socket passed by systemd"),
+    }
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("Connection failed: {err}");
+                continue;
+            },
+        };
+
+        // Slow clients must not block others, since anyone may connect with polkit.
+        let daemon = daemon.clone();
+        thread::spawn(move || {
+            if let Err(err) = handle_client(&daemon, stream, polkit) {
+                eprintln!("Client error: {err}");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Render all emulated effects in the background.
+fn spawn_effect_engine(daemon: Arc<Mutex<Daemon>>) {
+    thread::spawn(move || loop {
+        daemon.lock().unwrap().render_emulated();
+        thread::sleep(effect::FRAME_INTERVAL);
+    });
+}
+
+/// Reapply all configs whenever the system resumes from suspend.
+fn spawn_resume_monitor(daemon: Arc<Mutex<Daemon>>) -> Result<(), Box<dyn Error>> {
+    let mut monitor = process::Command::new("dbus-monitor")
+        .args(["--system", RESUME_MATCH_RULE])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("unable to start dbus-monitor: {err}"))?;
+    let stdout = monitor.stdout.take().unwrap();
+
+    thread::spawn(move || {
+        // The signal's only argument is `true` before sleep and `false` after resume.
+        let mut sleep_signal = false;
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if line.starts_with("signal ") {
+                sleep_signal = line.contains("member=PrepareForSleep");
+            } else if sleep_signal && line.trim() == "boolean false" {
+                sleep_signal = false;
+                println!("Resumed from suspend, reapplying configs");
+                daemon.lock().unwrap().reapply();
+            }
+        }
+
+        let _ = monitor.wait();
+        eprintln!("dbus-monitor exited, configs will no longer be reapplied after resume");
+    });
+
+    Ok(())
+}
+
+/// Apply configs to supported devices whenever they are connected.
+fn spawn_hotplug_monitor(
+    daemon: Arc<Mutex<Daemon>>,
+    matches: ArgMatches,
+) -> Result<(), Box<dyn Error>> {
+    let mut monitor = process::Command::new("udevadm")
+        .args(["monitor", "--udev", "--subsystem-match=hidraw"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("unable to start udevadm: {err}"))?;
+    let stdout = monitor.stdout.take().unwrap();
+
+    // Remember the devices present at startup.
+    daemon.lock().unwrap().refresh_devices();
+
+    thread::spawn(move || {
+        // Events look like `UDEV  [1234.567] add      /devices/... (hidraw)`.
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if !matches!(line.split_whitespace().nth(2), Some("add" | "remove")) {
+                continue;
+            }
+
+            let mut daemon = daemon.lock().unwrap();
+            for device in daemon.refresh_devices() {
+                println!("{device:?} connected, applying configs");
+
+                let configs = match matches.get_one::<String>("hotplug-profile") {
+                    Some(name) => match profile::configs(name, &matches) {
+                        Ok(configs) => configs,
+                        Err(err) => {
+                            eprintln!("Unable to load profile '{name}': {err}");
+                            continue;
+                        },
+                    },
+                    None => daemon.device_state(device),
+                };
+
+                for config in configs.iter().filter(|config| config.device == device) {
+                    if let Err(err) = daemon.write(config) {
+                        eprintln!("Unable to apply {:?} to {device:?}: {err}", config.zone);
+                    }
+                }
+            }
+        }
+
+        let _ = monitor.wait();
+        eprintln!("udevadm exited, configs will no longer be applied to connected devices");
+    });
+
+    Ok(())
+}
+
+/// Highlight a zone whenever a desktop notification is sent.
+fn spawn_notification_monitor(
+    daemon: Arc<Mutex<Daemon>>,
+    config: Config,
+    duration: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let mut monitor = process::Command::new("dbus-monitor")
+        .args(["--session", NOTIFICATION_MATCH_RULE])
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("unable to start dbus-monitor: {err}"))?;
+    let stdout = monitor.stdout.take().unwrap();
+
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if !line.starts_with("method call ") || !line.contains("member=Notify") {
+                continue;
+            }
+
+            if let Err(err) = daemon.lock().unwrap().highlight(&config) {
+                eprintln!("Unable to show notification: {err}");
+                continue;
+            }
+
+            thread::sleep(duration);
+
+            // Restore the zone's state, which might have changed in the meantime.
+            let mut daemon = daemon.lock().unwrap();
+            let result = match daemon.applied(config.device, config.zone).cloned() {
+                Some(previous) => daemon.write(&previous),
+                None => daemon.write_transient(&Config { effect: Effect::Off, ..config.clone() }),
+            };
+            if let Err(err) = result {
+                eprintln!("Unable to restore {:?} after notification: {err}", config.zone);
+            }
+        }
+
+        let _ = monitor.wait();
+        eprintln!("dbus-monitor exited, notifications will no longer be shown");
+    });
+
+    Ok(())
+}
+
+/// Process a single client request.
+fn handle_client(
+    daemon: &Mutex<Daemon>,
+    mut stream: UnixStream,
+    polkit: bool,
+) -> Result<(), Box<dyn Error>> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut request = String::new();
+    stream.read_to_string(&mut request)?;
+
+    // Ignore connections probing whether the daemon is running.
+    if request.trim().is_empty() {
+        return Ok(());
+    }
+
+    if polkit {
+        if let Err(err) = polkit::authorize(&stream) {
+            stream.write_all(format!("error: {err}\n").as_bytes())?;
+            return Ok(());
+        }
+    }
+
+    let response = daemon.lock().unwrap().process(&request);
+    stream.write_all(response.as_bytes())?;
+
+    Ok(())
+}
+
+/// Daemon state.
+pub struct Daemon {
+    api: HidApi,
+    devices: Vec<(Device, DeviceHandle)>,
+    detected: Option<Vec<Device>>,
+    applied: Vec<Config>,
+    emulated: Vec<(Config, Instant)>,
+}
+
+impl Daemon {
+    fn new() -> Result<Self, Box<dyn Error>> {
+        let api = HidApi::new().map_err(|err| format!("unable to access HID: {err}"))?;
+        Ok(Self {
+            api,
+            devices: Vec::new(),
+            detected: None,
+            applied: Vec::new(),
+            emulated: Vec::new(),
+        })
+    }
+
+    /// Apply a client request, returning the response.
+    ///
+    /// Transient requests, like effect frames, are written without recording
+    /// them as the zone's state. Commit requests persist the configs written
+    /// to a device.
+    fn process(&mut self, request: &str) -> String {
+        if let Some(request) = request.strip_prefix(COMMIT_REQUEST) {
+            let file = toml::from_str::<ConfigFile>(request).map_err(Box::<dyn Error>::from);
+            let result = file
+                .and_then(|file| self.resolve_device(file.device))
+                .and_then(|device| self.commit(device));
+            return match result {
+                Ok(()) => String::from("ok\n"),
+                Err(err) => format!("error: {err}\n"),
+            };
+        }
+
+        let (request, transient) = match request.strip_prefix(TRANSIENT_REQUEST) {
+            Some(request) => (request, true),
+            None => (request, false),
+        };
+
+        let configs = match self.configs(request) {
+            Ok(configs) => configs,
+            Err(err) => return format!("error: {err}\n"),
+        };
+
+        let results = if transient {
+            configs.iter().map(|config| self.write(config)).collect()
+        } else {
+            self.apply(&configs)
+        };
+
+        results
+            .into_iter()
+            .map(|result| match result {
+                Ok(()) => String::from("ok\n"),
+                Err(err) => format!("error: {err}\n"),
+            })
+            .collect()
+    }
+
+    /// Parse the configs submitted by a client.
+    fn configs(&mut self, request: &str) -> Result<Vec<Config>, Box<dyn Error>> {
+        let file: ConfigFile = toml::from_str(request)?;
+        let device = self.resolve_device(file.device)?;
+        file.into_configs(device)
+    }
+
+    /// Get the requested device, falling back to the only detected device.
+    pub fn resolve_device(&mut self, device: Option<Device>) -> Result<Device, Box<dyn Error>> {
+        match device {
+            Some(device) => Ok(device),
+            None => match self.detected() {
+                [device] => Ok(*device),
+                [] => Err("no supported RGB device found".into()),
+                _ => Err("multiple supported RGB devices found, device must be specified".into()),
+            },
+        }
+    }
+
+    /// All successfully applied zone configs, grouped by device.
+    pub fn state(&self) -> Vec<ConfigFile> {
+        let mut files: Vec<ConfigFile> = Vec::new();
+        for config in &self.applied {
+            match files.iter_mut().find(|file| file.device == Some(config.device)) {
+                Some(file) => file.insert(config.clone()),
+                None => {
+                    let mut file = ConfigFile::new(config.device);
+                    file.insert(config.clone());
+                    files.push(file);
+                },
+            }
+        }
+        files
+    }
+
+    /// Detect devices again, returning the ones which weren't present before.
+    fn refresh_devices(&mut self) -> Vec<Device> {
+        let previous = self.detected.take().unwrap_or_default();
+        if let Err(err) = self.api.refresh_devices() {
+            eprintln!("Unable to refresh HID devices: {err}");
+        }
+        let detected = self.detected().to_vec();
+
+        // Handles of disconnected devices are no longer valid.
+        self.devices.retain(|(device, _)| detected.contains(device));
+
+        detected.into_iter().filter(|device| !previous.contains(device)).collect()
+    }
+
+    /// Get the last applied configs of a device, falling back to the recorded
+    /// state.
+    fn device_state(&self, device: Device) -> Vec<Config> {
+        let applied: Vec<_> =
+            self.applied.iter().filter(|config| config.device == device).cloned().collect();
+        if applied.is_empty() {
+            state::load(device)
+        } else {
+            applied
+        }
+    }
+
+    /// Supported devices connected to the HID bus.
+    pub fn detected(&mut self) -> &[Device] {
+        let api = &self.api;
+        self.detected.get_or_insert_with(|| Device::detect(api))
+    }
+
+    /// Write configs requested by a user.
+    ///
+    /// All successfully written configs are recorded as their zone's state at
+    /// once, so they can be restored without the daemon.
+    pub fn apply(&mut self, configs: &[Config]) -> Vec<Result<(), Box<dyn Error>>> {
+        let results: Vec<_> = configs.iter().map(|config| self.write(config)).collect();
+
+        let applied: Vec<_> = configs
+            .iter()
+            .zip(&results)
+            .filter(|(_, result)| result.is_ok())
+            .map(|(config, _)| config.clone())
+            .collect();
+        if let Err(err) = state::record(&applied) {
+            eprintln!("Unable to store applied state: {err}");
+        }
+
+        // Persist the written configs once per device, rather than with every frame.
+        let mut devices = Vec::new();
+        for config in applied.iter().filter(|config| !effect::is_emulated(config)) {
+            if !devices.contains(&config.device) {
+                devices.push(config.device);
+            }
+        }
+        for device in devices {
+            if let Err(err) = self.commit(device) {
+                eprintln!("Unable to persist config of {device:?}: {err}");
+            }
+        }
+
+        results
+    }
+
+    /// Persist the configs written to a device across reboots.
+    fn commit(&mut self, device: Device) -> Result<(), Box<dyn Error>> {
+        if !self.devices.iter().any(|(open, _)| *open == device) {
+            let handle = crate::open_device(&self.api, device)?;
+            self.devices.push((device, handle));
+        }
+
+        let (_, handle) = self.devices.iter().find(|(open, _)| *open == device).unwrap();
+        crate::commit_device(handle, device)
+    }
+
+    /// Write a config, reusing the open device handle if possible.
+    ///
+    /// Emulated effects are rendered by the daemon's effect engine.
+    pub fn write(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        self.stop_emulation(config);
+
+        if effect::is_emulated(config) {
+            config.device.controller().validate(config)?;
+            self.emulated.push((config.clone(), Instant::now()));
+        } else {
+            self.write_transient(config)?;
+        }
+
+        self.record(config);
+
+        Ok(())
+    }
+
+    /// Temporarily override a zone, without changing its state.
+    fn highlight(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        self.stop_emulation(config);
+        self.write_transient(config)
+    }
+
+    /// Stop the emulated effect of the config's zone.
+    fn stop_emulation(&mut self, config: &Config) {
+        self.emulated.retain(|(emulated, _)| {
+            emulated.device != config.device || emulated.zone != config.zone
+        });
+    }
+
+    /// Write the current frame of all emulated effects.
+    fn render_emulated(&mut self) {
+        let frames: Vec<_> = self
+            .emulated
+            .iter()
+            .map(|(config, start)| effect::frame(config, start.elapsed()))
+            .collect();
+
+        for frame in frames {
+            if let Err(err) = self.write_transient(&frame) {
+                eprintln!("Stopping emulated effect for {:?}: {err}", frame.zone);
+                self.stop_emulation(&frame);
+            }
+        }
+    }
+
+    /// Write a config without remembering it as the zone's state.
+    fn write_transient(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        if let Some((_, device)) = self.devices.iter().find(|(device, _)| *device == config.device)
+        {
+            if crate::write_device(device, config).is_ok() {
+                return Ok(());
+            }
+
+            // Reopen the device in case the handle became invalid.
+            self.devices.retain(|(device, _)| *device != config.device);
+        }
+
+        let device = crate::open_device(&self.api, config.device)?;
+        crate::write_device(&device, config)?;
+        self.devices.push((config.device, device));
+
+        Ok(())
+    }
+
+    /// Get the last config applied to a zone.
+    fn applied(&self, device: Device, zone: Zone) -> Option<&Config> {
+        self.applied.iter().find(|config| config.device == device && config.zone == zone)
+    }
+
+    /// Write all previously applied configs again.
+    pub fn reapply(&mut self) {
+        // Device handles might not survive suspend, so always reopen them.
+        self.devices.clear();
+
+        for config in self.applied.clone() {
+            if let Err(err) = self.write(&config) {
+                eprintln!("Unable to reapply {:?}: {err}", config.zone);
+            }
+        }
+    }
+
+    /// Remember a successfully applied config.
+    fn record(&mut self, config: &Config) {
+        self.applied
+            .retain(|applied| applied.device != config.device || applied.zone != config.zone);
+        self.applied.push(config.clone());
+    }
+}
@@ -0,0 +1,103 @@
+//! Deepcool ARGB hub control.
+//!
+//! The hubs shipped with Deepcool AIOs control the pump block through the `io`
+//! zone and the connected fans and strips through the `header0` zone.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::controller::{HidController, Packet};
+use crate::{strip, Brightness, Config, Duration, Effect, Zone};
+
+/// Size of a single HID report, including the report ID.
+const PACKET_SIZE: usize = 64;
+
+/// Report ID of all lighting commands.
+const REPORT_ID: u8 = 0x10;
+
+/// Command for setting the effect of a channel.
+const SET_EFFECT: u8 = 0x02;
+
+/// Slowest speed setting of the hub.
+const MAX_SPEED: u16 = 5;
+
+pub struct DeepcoolHub;
+
+impl HidController for DeepcoolHub {
+    fn name(&self) -> &str {
+        "Deepcool ARGB Hub"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x3633)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x0002)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Header0]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Cycle, Effect::Rainbow]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+        let speed = speed_bytes(config.fade_in_time);
+
+        // The hub has no off mode, so it is emulated with zero brightness.
+        let (mode, brightness) = match config.effect {
+            Effect::Off => (effect_bytes(Effect::Static)?, 0),
+            effect => (effect_bytes(effect)?, brightness_bytes(config.max_brightness)),
+        };
+
+        let payload =
+            [channel_bytes(config.zone)?, mode, speed, brightness, color.r, color.g, color.b];
+
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+        buf.put_slice(&[REPORT_ID, 0x68, 0x01, SET_EFFECT, payload.len() as u8]);
+        buf.put_slice(&payload);
+
+        // Checksum of everything following the frame header, then the frame end.
+        let checksum = buf[2..].iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        buf.put_slice(&[checksum, 0x16]);
+
+        buf.resize(PACKET_SIZE, 0);
+
+        Ok(vec![buf.freeze().into()])
+    }
+}
+
+/// Convert zone to the hub's channel.
+fn channel_bytes(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Io => Ok(0x00),
+        Zone::Header0 => Ok(0x01),
+        zone => Err(format!("unsupported zone: {zone:?}").into()),
+    }
+}
+
+/// Convert effect type to Deepcool format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Static => Ok(0x01),
+        Effect::Pulse => Ok(0x02),
+        Effect::Cycle => Ok(0x03),
+        Effect::Rainbow => Ok(0x04),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Convert effect duration to the hub's speed, with 0 being the fastest.
+fn speed_bytes(duration: Duration) -> u8 {
+    (duration.0 / 400).min(MAX_SPEED) as u8
+}
+
+/// Convert brightness to the hub's percentage.
+fn brightness_bytes(brightness: Brightness) -> u8 {
+    (100 * brightness.0 as u16 / u8::MAX as u16) as u8
+}
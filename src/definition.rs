@@ -0,0 +1,501 @@
+//! Declarative controller definitions.
+//!
+//! Controllers which only differ from existing ones in a few constants can be
+//! described in TOML files placed in `~/.config/rgbfusion/controllers/`:
+//!
+//! ```toml
+//! name = "Gigabyte B550 Aorus Pro"
+//! vendor_id = 0x048d
+//! product_id = 0x5702
+//! duration_step = 250
+//! max_brightness = 0x5a
+//!
+//! packets = [
+//!     "cc {zone} 00*8 {effect} {max_brightness} {min_brightness} {b} {g} {r} 00*5",
+//!     { transport = "feature-report", bytes = "cc 28 ff 00*20" },
+//! ]
+//!
+//! [zones]
+//! io = "2001"
+//! cpu = "2102"
+//!
+//! [effects]
+//! off = "00"
+//! static = "01"
+//! ```
+//!
+//! Packet templates are whitespace-separated hex bytes, with `<hex>*<count>`
+//! repeating bytes and `{placeholder}` tokens being replaced by the config.
+//! Packets are sent as interrupt writes unless another transport is specified.
+//!
+//! Controllers reporting their state can specify a `state` feature report
+//! template, which is read back to verify the applied config.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use bytes::{BufMut, Bytes};
+use clap::ValueEnum;
+use hidapi::HidDevice;
+use serde::Deserialize;
+
+use crate::controller::{HidController, Packet, Transport};
+use crate::{strip, Brightness, Config, Duration, Effect, Zone};
+
+/// Controller definitions loaded from the configuration directory.
+static DEFINITIONS: OnceLock<Vec<ControllerDefinition>> = OnceLock::new();
+
+/// Get all controller definitions from the configuration directory.
+pub fn definitions() -> &'static [ControllerDefinition] {
+    DEFINITIONS.get_or_init(|| {
+        let dir = match crate::config_dir() {
+            Some(config_dir) => config_dir.join("controllers"),
+            None => return Vec::new(),
+        };
+
+        let mut paths: Vec<_> = match fs::read_dir(&dir) {
+            Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+            Err(_) => return Vec::new(),
+        };
+        paths.sort();
+
+        paths
+            .iter()
+            .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+            .filter_map(|path| match ControllerDefinition::load(path) {
+                Ok(definition) => Some(definition),
+                Err(err) => {
+                    eprintln!("Skipping controller definition {}: {err}", path.display());
+                    None
+                },
+            })
+            .collect()
+    })
+}
+
+/// Controller definition file format.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DefinitionFile {
+    name: String,
+    vendor_id: u16,
+    product_id: u16,
+    #[serde(default = "default_duration_step")]
+    duration_step: u16,
+    #[serde(default = "default_max_brightness")]
+    max_brightness: u8,
+    zones: BTreeMap<String, String>,
+    effects: BTreeMap<String, String>,
+    packets: Vec<PacketTemplate>,
+    state: Option<String>,
+}
+
+/// Packet template with an optional transport.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PacketTemplate {
+    Bytes(String),
+    Transport { transport: Transport, bytes: String },
+}
+
+fn default_duration_step() -> u16 {
+    1
+}
+
+fn default_max_brightness() -> u8 {
+    u8::MAX
+}
+
+/// Controller loaded from a definition file.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ControllerDefinition {
+    /// Identifier used for selecting this controller on the CLI.
+    pub id: String,
+
+    name: String,
+    vendor_id: u16,
+    product_id: u16,
+    duration_step: u16,
+    max_brightness: u8,
+    zones: Vec<(Zone, Bytes)>,
+    supported_zones: Vec<Zone>,
+    effects: Vec<(Effect, Bytes)>,
+    supported_effects: Vec<Effect>,
+    packets: Vec<(Transport, Vec<Token>)>,
+    state: Option<Vec<Token>>,
+}
+
+impl ControllerDefinition {
+    /// Load a controller definition from a file.
+    fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let id = match path.file_stem() {
+            Some(stem) => stem.to_string_lossy().to_lowercase(),
+            None => return Err("missing file name".into()),
+        };
+
+        let content = fs::read_to_string(path)?;
+        Self::from_toml(id, &content)
+    }
+
+    /// Parse a controller definition from its TOML representation.
+    fn from_toml(id: String, content: &str) -> Result<Self, Box<dyn Error>> {
+        let file: DefinitionFile = toml::from_str(content)?;
+
+        let zones = parse_codes::<Zone>(&file.zones)?;
+        let effects = parse_codes::<Effect>(&file.effects)?;
+
+        let packets = file
+            .packets
+            .iter()
+            .map(|packet| {
+                let (transport, bytes) = match packet {
+                    PacketTemplate::Bytes(bytes) => (Transport::default(), bytes),
+                    PacketTemplate::Transport { transport, bytes } => (*transport, bytes),
+                };
+                let tokens = bytes.split_whitespace().map(Token::from_str);
+                Ok((transport, tokens.collect::<Result<_, _>>()?))
+            })
+            .collect::<Result<_, String>>()?;
+
+        let state = match file.state {
+            Some(state) if state.trim().is_empty() => return Err("empty state template".into()),
+            Some(state) => {
+                Some(state.split_whitespace().map(Token::from_str).collect::<Result<_, _>>()?)
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            supported_zones: zones.iter().map(|(zone, _)| *zone).collect(),
+            supported_effects: effects.iter().map(|(effect, _)| *effect).collect(),
+            max_brightness: file.max_brightness,
+            duration_step: file.duration_step.max(1),
+            product_id: file.product_id,
+            vendor_id: file.vendor_id,
+            name: file.name,
+            packets,
+            effects,
+            state,
+            zones,
+            id,
+        })
+    }
+
+    /// Iterate over the tokens of all packet templates.
+    fn tokens(&self) -> impl Iterator<Item = &Token> {
+        self.packets.iter().flat_map(|(_, tokens)| tokens)
+    }
+
+    /// Encode each token of a packet template.
+    fn encode(&self, template: &[Token], config: &Config) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+        let zone = lookup_code(&self.zones, config.zone)?;
+        let effect = lookup_code(&self.effects, config.effect)?;
+
+        let strip_type = strip::strip_type(config.device, config.zone);
+        let (color, white) = strip_type.encode(config.color);
+        let (color2, white2) = strip_type.encode(config.color2);
+
+        let mut encoded = Vec::new();
+        for token in template {
+            let mut buf = Vec::new();
+
+            match token {
+                Token::Bytes(bytes) => buf.put_slice(bytes),
+                Token::Zone => buf.put_slice(zone),
+                Token::Effect => buf.put_slice(effect),
+                Token::Red => buf.put_u8(color.r),
+                Token::Green => buf.put_u8(color.g),
+                Token::Blue => buf.put_u8(color.b),
+                Token::White => buf.put_u8(white),
+                Token::Red2 => buf.put_u8(color2.r),
+                Token::Green2 => buf.put_u8(color2.g),
+                Token::Blue2 => buf.put_u8(color2.b),
+                Token::White2 => buf.put_u8(white2),
+                Token::MaxBrightness => buf.put_u8(self.brightness_byte(config.max_brightness)),
+                Token::MinBrightness => buf.put_u8(self.brightness_byte(config.min_brightness)),
+                Token::FadeInTime => buf.put_slice(&self.duration_bytes(config.fade_in_time)),
+                Token::FadeOutTime => buf.put_slice(&self.duration_bytes(config.fade_out_time)),
+                Token::HoldTime => buf.put_slice(&self.duration_bytes(config.hold_time)),
+            }
+
+            encoded.push(buf);
+        }
+
+        Ok(encoded)
+    }
+
+    /// Read the state feature report and compare it to the expected config.
+    fn verify_state(
+        &self,
+        device: &HidDevice,
+        config: &Config,
+        template: &[Token],
+    ) -> Result<(), Box<dyn Error>> {
+        let expected = self.encode(template, config)?;
+
+        // Request the report using the template's report ID.
+        let mut report = expected.concat();
+        report[1..].fill(0);
+        let len = device.get_feature_report(&mut report)?;
+        report.truncate(len);
+
+        // Compare all placeholders, ignoring the constant bytes.
+        let mut mismatches = Vec::new();
+        let mut offset = 0;
+        for (token, bytes) in template.iter().zip(&expected) {
+            let actual = report.get(offset..offset + bytes.len());
+            offset += bytes.len();
+
+            if matches!(token, Token::Bytes(_)) || actual == Some(bytes.as_slice()) {
+                continue;
+            }
+
+            match actual {
+                Some(actual) => mismatches.push(format!(
+                    "{token:?} is {}, expected {}",
+                    hex(actual),
+                    hex(bytes)
+                )),
+                None => mismatches.push(format!("{token:?} is missing")),
+            }
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches.join(", ").into())
+        }
+    }
+
+    /// Convert duration to the definition's time unit.
+    fn duration_bytes(&self, duration: Duration) -> [u8; 2] {
+        (duration.0 / self.duration_step).to_be_bytes()
+    }
+
+    /// Convert brightness to the definition's brightness range.
+    fn brightness_byte(&self, brightness: Brightness) -> u8 {
+        (self.max_brightness as u16 * brightness.0 as u16 / u8::MAX as u16) as u8
+    }
+}
+
+impl HidController for ControllerDefinition {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(self.vendor_id)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(self.product_id)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &self.supported_zones
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &self.supported_effects
+    }
+
+    fn supports_brightness(&self) -> bool {
+        self.tokens().any(|token| *token == Token::MaxBrightness)
+    }
+
+    fn supports_white(&self) -> bool {
+        self.tokens().any(|token| *token == Token::White)
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let mut packets = Vec::new();
+        for (transport, template) in &self.packets {
+            let encoded = self.encode(template, config)?;
+            packets.push(Packet::new(*transport, encoded.concat().into()));
+        }
+
+        Ok(packets)
+    }
+
+    fn verify(&self, device: &HidDevice, config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+        let template = self.state.as_ref()?;
+        Some(self.verify_state(device, config, template))
+    }
+
+    fn supports_read_back(&self) -> bool {
+        self.state.is_some()
+    }
+}
+
+/// Element of a packet template.
+#[derive(PartialEq, Eq, Debug)]
+enum Token {
+    Bytes(Bytes),
+    Zone,
+    Effect,
+    Red,
+    Green,
+    Blue,
+    White,
+    Red2,
+    Green2,
+    Blue2,
+    White2,
+    MaxBrightness,
+    MinBrightness,
+    FadeInTime,
+    FadeOutTime,
+    HoldTime,
+}
+
+impl FromStr for Token {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let placeholder = match s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(placeholder) => placeholder,
+            None => {
+                // Handle repetitions like `00*8`.
+                let (hex, count) = match s.split_once('*') {
+                    Some((hex, count)) => {
+                        let count = usize::from_str(count)
+                            .map_err(|_| format!("invalid repetition count: {s}"))?;
+                        (hex, count)
+                    },
+                    None => (s, 1),
+                };
+
+                return Ok(Token::Bytes(parse_hex(hex)?.repeat(count).into()));
+            },
+        };
+
+        match placeholder {
+            "zone" => Ok(Token::Zone),
+            "effect" => Ok(Token::Effect),
+            "r" => Ok(Token::Red),
+            "g" => Ok(Token::Green),
+            "b" => Ok(Token::Blue),
+            "w" => Ok(Token::White),
+            "r2" => Ok(Token::Red2),
+            "g2" => Ok(Token::Green2),
+            "b2" => Ok(Token::Blue2),
+            "w2" => Ok(Token::White2),
+            "max_brightness" => Ok(Token::MaxBrightness),
+            "min_brightness" => Ok(Token::MinBrightness),
+            "fade_in_time" => Ok(Token::FadeInTime),
+            "fade_out_time" => Ok(Token::FadeOutTime),
+            "hold_time" => Ok(Token::HoldTime),
+            _ => Err(format!("unknown placeholder: {s}")),
+        }
+    }
+}
+
+/// Parse zone or effect codes, sorted by their CLI order.
+fn parse_codes<T>(codes: &BTreeMap<String, String>) -> Result<Vec<(T, Bytes)>, String>
+where
+    T: ValueEnum + PartialEq + Copy,
+{
+    let mut parsed = Vec::new();
+    for (name, code) in codes {
+        let variant = T::from_str(name, true)?;
+        parsed.push((variant, Bytes::from(parse_hex(code)?)));
+    }
+
+    let order = |variant: &T| T::value_variants().iter().position(|v| v == variant);
+    parsed.sort_by_key(|(variant, _)| order(variant));
+
+    Ok(parsed)
+}
+
+/// Find the controller code for a zone or effect.
+fn lookup_code<T>(codes: &[(T, Bytes)], variant: T) -> Result<&Bytes, String>
+where
+    T: PartialEq + std::fmt::Debug,
+{
+    match codes.iter().find(|(v, _)| *v == variant) {
+        Some((_, code)) => Ok(code),
+        None => Err(format!("unsupported value: {variant:?}")),
+    }
+}
+
+/// Format bytes as hex string.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Parse a string of hex bytes without separators.
+pub fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.is_empty() || !hex.len().is_multiple_of(2) {
+        return Err(format!("invalid hex bytes: {hex}"));
+    }
+
+    // Slicing non-ASCII strings by byte offsets could split a character.
+    if !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(format!("invalid hex: {hex}"));
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("invalid hex: {hex}")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rgb, RgbDevice};
+
+    #[test]
+    fn packet_template() {
+        let definition = ControllerDefinition::from_toml(
+            String::from("test"),
+            r#"
+                name = "Test"
+                vendor_id = 0x1234
+                product_id = 0x5678
+                duration_step = 250
+                max_brightness = 0x5a
+
+                packets = [
+                    "cc {zone} 00*2 {effect} {max_brightness} {r} {g} {b} {hold_time}",
+                    { transport = "feature-report", bytes = "cc 28 ff" },
+                ]
+
+                [zones]
+                io = "2001"
+
+                [effects]
+                static = "01"
+            "#,
+        )
+        .unwrap();
+
+        let config = Config {
+            device: RgbDevice::Trx40.into(),
+            color: Rgb { r: 1, g: 2, b: 3 },
+            hold_time: Duration(1000),
+            ..Default::default()
+        };
+
+        let packets = definition.config_bytes(&config).unwrap();
+        assert_eq!(packets, vec![
+            Packet::from(Bytes::from_static(&[
+                0xcc, 0x20, 0x01, 0x00, 0x00, 0x01, 0x5a, 0x01, 0x02, 0x03, 0x00, 0x04
+            ])),
+            Packet::new(Transport::FeatureReport, Bytes::from_static(&[0xcc, 0x28, 0xff])),
+        ]);
+    }
+
+    #[test]
+    fn hex_parsing() {
+        assert_eq!(parse_hex("0x12ab"), Ok(vec![0x12, 0xab]));
+        assert!(parse_hex("123").is_err());
+        assert!(parse_hex("zz").is_err());
+        assert!(parse_hex("aé").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//! Packet dumps for inspecting configs without touching the HID bus.
+
+use std::fmt::Write;
+
+use crate::controller::Packet;
+use crate::{effect, Config};
+
+/// Bytes per hex dump line.
+const LINE_LENGTH: usize = 16;
+
+/// Print the packets of all configs instead of writing them.
+pub fn print(configs: &[Config]) {
+    for config in configs {
+        println!(
+            "{:?} zone {:?} with effect {:?} and color {}:",
+            config.device, config.zone, config.effect, config.color
+        );
+
+        if effect::is_emulated(config) {
+            println!("  Emulated in software using static colors.");
+            continue;
+        }
+
+        let packets = match crate::config_packets(config) {
+            Ok(packets) => packets,
+            Err(err) => {
+                eprintln!("\x1b[31mError:\x1b[0m {err}");
+                continue;
+            },
+        };
+
+        print_packets(&packets);
+    }
+
+    // Configs are persisted once per device after all of them were written.
+    let mut devices = Vec::new();
+    for config in configs.iter().filter(|config| !effect::is_emulated(config)) {
+        if !devices.contains(&config.device) {
+            devices.push(config.device);
+        }
+    }
+    for device in devices {
+        let packets = device.controller().commit_bytes();
+        if !packets.is_empty() {
+            println!("{device:?} persisting configs:");
+            print_packets(&packets);
+        }
+    }
+}
+
+/// Print numbered hex dumps of packets.
+fn print_packets(packets: &[Packet]) {
+    for (i, packet) in packets.iter().enumerate() {
+        println!("  Packet {} ({:?}, {} bytes):", i + 1, packet.transport, packet.bytes.len());
+        print!("{}", hex_dump(&packet.bytes));
+    }
+}
+
+/// Format bytes with their offset and ASCII representation.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    let mut dump = String::new();
+
+    for (i, line) in bytes.chunks(LINE_LENGTH).enumerate() {
+        let _ = write!(dump, "    {:04x} ", i * LINE_LENGTH);
+
+        for j in 0..LINE_LENGTH {
+            // Separate the two halves of each line.
+            if j == LINE_LENGTH / 2 {
+                dump.push(' ');
+            }
+
+            match line.get(j) {
+                Some(byte) => {
+                    let _ = write!(dump, " {byte:02x}");
+                },
+                None => dump.push_str("   "),
+            }
+        }
+
+        let ascii: String = line
+            .iter()
+            .map(|byte| if byte.is_ascii_graphic() { *byte as char } else { '.' })
+            .collect();
+        let _ = writeln!(dump, "  |{ascii}|");
+    }
+
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_dump_lines() {
+        let bytes: Vec<u8> = (0x40..0x52).collect();
+
+        assert_eq!(
+            hex_dump(&bytes),
+            "    0000  40 41 42 43 44 45 46 47  48 49 4a 4b 4c 4d 4e 4f  |@ABCDEFGHIJKLMNO|\n    \
+             0010  50 51                                             |PQ|\n"
+        );
+    }
+}
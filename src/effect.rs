@@ -0,0 +1,179 @@
+//! Software effect engine.
+//!
+//! Effects which are not supported by a controller's firmware are emulated by
+//! repeatedly writing static colors. Since a zone only has a single color,
+//! `rainbow` is rendered like `cycle`, `chase-fade` like `pulse` and `chase`
+//! like `flash`.
+//!
+//! Cycling through a user-defined palette is always emulated, since controller
+//! firmware only cycles through the full spectrum.
+
+use std::error::Error;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use hidapi::HidApi;
+
+use crate::controller::EffectMode;
+use crate::{Brightness, Config, Effect, Rgb};
+
+/// Time between two frames of an emulated effect.
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Check if a config's effect is emulated in software.
+pub fn is_emulated(config: &Config) -> bool {
+    (config.effect == Effect::Cycle && !config.palette.is_empty())
+        || config.device.controller().effect_mode(config.effect) == Some(EffectMode::Emulated)
+}
+
+/// Render emulated effects until the process is killed.
+pub fn run(configs: &[Config]) -> Result<(), Box<dyn Error>> {
+    let api = HidApi::new().map_err(|err| format!("unable to access HID: {err}"))?;
+
+    let mut devices = Vec::new();
+    for config in configs {
+        if !devices.iter().any(|(device, _)| *device == config.device) {
+            devices.push((config.device, crate::open_device(&api, config.device)?));
+        }
+    }
+
+    let zones: Vec<_> = configs.iter().map(|config| format!("{:?}", config.zone)).collect();
+    println!("Emulating effects for {}, press Ctrl+C to stop.", zones.join(", "));
+
+    let start = Instant::now();
+    loop {
+        for config in configs {
+            let (_, device) = devices.iter().find(|(device, _)| *device == config.device).unwrap();
+            crate::write_device(device, &frame(config, start.elapsed()))?;
+        }
+
+        thread::sleep(FRAME_INTERVAL);
+    }
+}
+
+/// Get the static config displaying an emulated effect at a point in time.
+pub fn frame(config: &Config, elapsed: Duration) -> Config {
+    let mut frame = Config { effect: Effect::Static, ..config.clone() };
+
+    let fade_in = config.fade_in_time.0 as f32;
+    let hold = config.hold_time.0 as f32;
+    let fade_out = config.fade_out_time.0 as f32;
+    let period = [config.fade_in_time, config.hold_time, config.fade_out_time];
+    let period = period.iter().map(|duration| duration.0 as u128).sum::<u128>().max(1);
+    let time = (elapsed.as_millis() % period) as f32;
+
+    match config.effect {
+        // Fade in, hold and fade out between min and max brightness.
+        Effect::Pulse | Effect::ChaseFade => {
+            let level = if time < fade_in {
+                time / fade_in
+            } else if time < fade_in + hold {
+                1.
+            } else {
+                1. - (time - fade_in - hold) / fade_out
+            };
+            frame.max_brightness = brightness(config, level);
+        },
+        // Only light up during the hold time.
+        Effect::Flash | Effect::Chase => {
+            let on = time >= fade_in && time < fade_in + hold;
+            frame.max_brightness = brightness(config, if on { 1. } else { 0. });
+        },
+        // Hold each palette color, then fade to the next one.
+        Effect::Cycle if !config.palette.is_empty() => {
+            frame.color = palette_color(config, elapsed);
+        },
+        // Rotate through all hues, spending one period on each sixth of the wheel.
+        Effect::Cycle | Effect::Rainbow => {
+            let wheel_period = period * 6;
+            frame.color =
+                hue_color((elapsed.as_millis() % wheel_period) as f32 / wheel_period as f32);
+        },
+        Effect::Off | Effect::Static => frame.effect = config.effect,
+    }
+
+    frame
+}
+
+/// Get the color of a palette cycle at a point in time.
+fn palette_color(config: &Config, elapsed: Duration) -> Rgb {
+    let palette = &config.palette;
+    let fade = config.fade_in_time.0 as u128;
+    let step = (config.hold_time.0 as u128 + fade).max(1);
+
+    let time = elapsed.as_millis() % (step * palette.len() as u128);
+    let index = (time / step) as usize;
+    let time = time % step;
+
+    let color = palette[index];
+    match time.checked_sub(config.hold_time.0 as u128) {
+        Some(fading) if fade > 0 => {
+            let next = palette[(index + 1) % palette.len()];
+            color.interpolate(next, fading as f32 / fade as f32)
+        },
+        _ => color,
+    }
+}
+
+/// Interpolate between a config's min and max brightness.
+fn brightness(config: &Config, level: f32) -> Brightness {
+    let min = config.min_brightness.0 as f32;
+    let max = config.max_brightness.0 as f32;
+    Brightness((min + (max - min) * level.clamp(0., 1.)).round() as u8)
+}
+
+/// Get the fully saturated color at a position in the range `0..1` of the hue
+/// wheel.
+fn hue_color(position: f32) -> Rgb {
+    Rgb::from_hsv(position * 360., 1., 1.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RgbDevice;
+
+    #[test]
+    fn emulated_pulse() {
+        let config = Config {
+            device: RgbDevice::Trx40.into(),
+            effect: Effect::ChaseFade,
+            max_brightness: Brightness(200),
+            min_brightness: Brightness(0),
+            fade_in_time: crate::Duration(100),
+            hold_time: crate::Duration(100),
+            fade_out_time: crate::Duration(100),
+            ..Default::default()
+        };
+
+        let brightness = |ms| frame(&config, Duration::from_millis(ms)).max_brightness.0;
+        assert_eq!(brightness(0), 0);
+        assert_eq!(brightness(50), 100);
+        assert_eq!(brightness(150), 200);
+        assert_eq!(brightness(275), 50);
+        assert_eq!(brightness(300), 0);
+        assert_eq!(frame(&config, Duration::ZERO).effect, Effect::Static);
+    }
+
+    #[test]
+    fn palette_cycle() {
+        let red = Rgb { r: 255, g: 0, b: 0 };
+        let blue = Rgb { r: 0, g: 0, b: 255 };
+        let config = Config {
+            device: RgbDevice::Trx40.into(),
+            effect: Effect::Cycle,
+            palette: vec![red, blue],
+            hold_time: crate::Duration(100),
+            fade_in_time: crate::Duration(100),
+            ..Default::default()
+        };
+        assert!(is_emulated(&config));
+
+        let color = |ms| frame(&config, Duration::from_millis(ms)).color;
+        assert_eq!(color(50), red);
+        assert_eq!(color(150), Rgb { r: 128, g: 0, b: 128 });
+        assert_eq!(color(250), blue);
+        assert_eq!(color(350), Rgb { r: 128, g: 0, b: 128 });
+        assert_eq!(color(400), red);
+    }
+}
@@ -0,0 +1,145 @@
+//! EVGA FTW3 and XC graphics card RGB control.
+//!
+//! The card's lighting controller is connected to one of the NVIDIA i2c
+//! adapters. The light bar, logo and backplate are controlled through the
+//! `io`, `header0` and `header1` zones, with XC cards only having the first
+//! two.
+
+use std::error::Error;
+
+use bytes::Bytes;
+use hidapi::HidApi;
+
+use crate::controller::{DeviceHandle, HidController, Packet, Transport};
+use crate::smbus::{self, SmbusDevice};
+use crate::{strip, Brightness, Config, Duration, Effect, HidSelector, Zone};
+
+/// Name prefixes of NVIDIA graphics card i2c adapters.
+const ADAPTERS: &[&str] = &["NVIDIA i2c adapter"];
+
+/// i2c address of the lighting controller.
+const ADDRESS: u8 = 0x2d;
+
+/// PCI subsystem vendor ID of EVGA, reported by the controller.
+const VENDOR_ID: [u8; 2] = [0x38, 0x42];
+
+const REG_VENDOR_ID: u8 = 0x02;
+const REG_APPLY: u8 = 0x0e;
+
+/// Offsets of the registers in the block of every zone.
+const REG_MODE: u8 = 0x00;
+const REG_RED: u8 = 0x01;
+const REG_GREEN: u8 = 0x02;
+const REG_BLUE: u8 = 0x03;
+const REG_BRIGHTNESS: u8 = 0x04;
+const REG_SPEED: u8 = 0x05;
+
+/// Slowest speed setting of the card.
+const MAX_SPEED: u16 = 10;
+
+pub struct EvgaGpu;
+
+impl HidController for EvgaGpu {
+    fn name(&self) -> &str {
+        "EVGA GPU"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        &[ADDRESS]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle, Effect::Rainbow]
+    }
+
+    fn open(&self, _api: &HidApi, _selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        for adapter in smbus::adapters(ADAPTERS) {
+            let device = match SmbusDevice::open(&adapter, &[ADDRESS]) {
+                Ok(device) => device,
+                Err(_) => continue,
+            };
+
+            // Ignore other devices on the card using the same address.
+            let vendor_id =
+                [REG_VENDOR_ID, REG_VENDOR_ID + 1].map(|reg| device.read_byte_data(reg));
+            if let [Ok(high), Ok(low)] = vendor_id {
+                if [high, low] == VENDOR_ID {
+                    return Ok(DeviceHandle::Smbus(device));
+                }
+            }
+        }
+
+        Err("no EVGA GPU lighting controller found, is the `i2c-dev` module loaded?".into())
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+        let base = zone_bytes(config.zone)?;
+
+        let writes = [
+            (REG_MODE, effect_bytes(config.effect)?),
+            (REG_RED, color.r),
+            (REG_GREEN, color.g),
+            (REG_BLUE, color.b),
+            (REG_BRIGHTNESS, brightness_bytes(config.max_brightness)),
+            (REG_SPEED, speed_bytes(config.fade_in_time)),
+        ];
+        let mut packets: Vec<_> =
+            writes.iter().map(|(reg, value)| register_packet(base + reg, *value)).collect();
+
+        // Apply the submitted configuration.
+        packets.push(register_packet(REG_APPLY, 0x01));
+
+        Ok(packets)
+    }
+}
+
+/// Convert a register write to an SMBus packet.
+fn register_packet(register: u8, value: u8) -> Packet {
+    Packet::new(Transport::SmbusByte, Bytes::copy_from_slice(&[register, value]))
+}
+
+/// Convert zone to the first register of its block.
+fn zone_bytes(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Io => Ok(0x60),
+        Zone::Header0 => Ok(0x70),
+        Zone::Header1 => Ok(0x80),
+        zone => Err(format!("unsupported zone: {zone:?}").into()),
+    }
+}
+
+/// Convert effect type to EVGA format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Off => Ok(0x00),
+        Effect::Static => Ok(0x01),
+        Effect::Pulse => Ok(0x02),
+        Effect::Flash => Ok(0x03),
+        Effect::Cycle => Ok(0x04),
+        Effect::Rainbow => Ok(0x05),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Convert effect duration to the card's speed, with 0 being the fastest.
+fn speed_bytes(duration: Duration) -> u8 {
+    (duration.0 / 200).min(MAX_SPEED) as u8
+}
+
+/// Convert brightness to the card's percentage.
+fn brightness_bytes(brightness: Brightness) -> u8 {
+    (100 * brightness.0 as u16 / u8::MAX as u16) as u8
+}
@@ -0,0 +1,84 @@
+//! Per-device gamma correction.
+//!
+//! LEDs often render mid-tones brighter than a monitor would. Gamma curves
+//! applied before encoding colors can be configured per device in
+//! `~/.config/rgbfusion/gamma.toml`:
+//!
+//! ```toml
+//! trx40 = 2.2
+//! ```
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::{Config, Device, Rgb};
+
+/// Gamma values loaded from the configuration directory.
+static GAMMA: OnceLock<Vec<(Device, f32)>> = OnceLock::new();
+
+/// Get the configured gamma values of all devices.
+fn gamma_values() -> &'static [(Device, f32)] {
+    GAMMA.get_or_init(|| match load() {
+        Ok(values) => values,
+        Err(err) => {
+            eprintln!("Ignoring gamma configuration: {err}");
+            Vec::new()
+        },
+    })
+}
+
+/// Load the gamma configuration file.
+fn load() -> Result<Vec<(Device, f32)>, Box<dyn Error>> {
+    let path = match crate::config_dir() {
+        Some(config_dir) => config_dir.join("gamma.toml"),
+        None => return Ok(Vec::new()),
+    };
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let values: BTreeMap<String, f32> = toml::from_str(&content)?;
+    values
+        .into_iter()
+        .map(|(name, gamma)| {
+            let device = name.parse::<Device>()?;
+            if gamma <= 0. {
+                return Err(format!("invalid gamma for {device:?}: {gamma}").into());
+            }
+            Ok((device, gamma))
+        })
+        .collect()
+}
+
+/// Apply the device's gamma curve to all colors of a config.
+pub fn correct(config: &Config) -> Config {
+    let gamma = gamma_values().iter().find(|(device, _)| *device == config.device);
+    match gamma {
+        Some((_, gamma)) => apply(config, *gamma),
+        None => config.clone(),
+    }
+}
+
+/// Apply a gamma curve to all colors of a config.
+fn apply(config: &Config, gamma: f32) -> Config {
+    let channel = |value: u8| ((value as f32 / 255.).powf(gamma) * 255.).round() as u8;
+    config.map_colors(|color| Rgb { r: channel(color.r), g: channel(color.g), b: channel(color.b) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamma_curve() {
+        let config = Config { color: Rgb { r: 255, g: 128, b: 0 }, ..Default::default() };
+
+        let corrected = apply(&config, 2.2);
+        assert_eq!(corrected.color, Rgb { r: 255, g: 56, b: 0 });
+        assert_eq!(apply(&config, 1.).color, config.color);
+    }
+}
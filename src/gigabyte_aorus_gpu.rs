@@ -0,0 +1,78 @@
+//! Gigabyte Aorus graphics card RGB Fusion control.
+//!
+//! Aorus cards expose their lighting as a separate HID endpoint. The whole card
+//! is controlled as a single zone.
+
+use std::error::Error;
+
+use bytes::Bytes;
+
+use crate::controller::{HidController, Packet};
+use crate::{strip, Brightness, Config, Duration, Effect, Zone};
+
+/// Slowest speed setting of the card.
+const MAX_SPEED: u16 = 9;
+
+pub struct GigabyteAorusGpu;
+
+impl HidController for GigabyteAorusGpu {
+    fn name(&self) -> &str {
+        "Gigabyte Aorus GPU"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x1044)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x7a39)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+        let (mode, brightness) = match config.effect {
+            // The card has no off mode, so it is emulated with zero brightness.
+            Effect::Off => (1, 0),
+            effect => (effect_bytes(effect)?, brightness_bytes(config.max_brightness)),
+        };
+        let speed = speed_bytes(config.fade_in_time);
+
+        // Set effect mode, report ID 0 is used since the card has no report IDs.
+        let mode_bytes = Bytes::copy_from_slice(&[0x00, 0x88, mode, speed, brightness, 0, 0, 0, 0]);
+
+        // Set effect color.
+        let color_bytes =
+            Bytes::copy_from_slice(&[0x00, 0x40, color.r, color.g, color.b, 0, 0, 0, 0]);
+
+        Ok(vec![mode_bytes.into(), color_bytes.into()])
+    }
+}
+
+/// Convert effect type to Aorus GPU format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Static => Ok(1),
+        Effect::Pulse => Ok(2),
+        Effect::Flash => Ok(3),
+        Effect::Cycle => Ok(4),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Convert brightness to the card's percentage.
+fn brightness_bytes(brightness: Brightness) -> u8 {
+    (100 * brightness.0 as u16 / u8::MAX as u16) as u8
+}
+
+/// Convert effect duration to the card's speed, with 0 being the fastest.
+fn speed_bytes(duration: Duration) -> u8 {
+    (duration.0 / 250).min(MAX_SPEED) as u8
+}
@@ -0,0 +1,74 @@
+//! Gigabyte B550 Aorus Pro RGB Fusion control.
+//!
+//! The board uses an IT5702 controller with the IT8297 protocol, but has no
+//! CPU zone and connects its other zones to different LEDs.
+
+use std::error::Error;
+
+use crate::controller::{HidController, Packet};
+use crate::gigabyte_it8297::{self, GigabyteIt8297};
+use crate::strip::HeaderConfig;
+use crate::{Config, Duration, Effect, Rgb, Speed, Zone};
+
+pub struct GigabyteB550AorusPro;
+
+impl HidController for GigabyteB550AorusPro {
+    fn name(&self) -> &str {
+        "Gigabyte B550 Aorus Pro"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x048d)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x5702)
+    }
+
+    fn board_name(&self) -> Option<&str> {
+        Some("B550 AORUS PRO")
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Chipset, Zone::Audio, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        gigabyte_it8297::led_packets(config, led_index(config.zone)?)
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(gigabyte_it8297::MAX_DIGITAL_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(gigabyte_it8297::digital_led_packets(zone, colors))
+    }
+
+    fn header_bytes(
+        &self,
+        headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(gigabyte_it8297::header_packets(headers))
+    }
+
+    fn speed_timings(&self, speed: Speed) -> (Duration, Duration, Duration) {
+        GigabyteIt8297.speed_timings(speed)
+    }
+}
+
+/// Get the LED index of a zone.
+fn led_index(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Io => Ok(0),
+        Zone::Chipset => Ok(1),
+        Zone::Audio => Ok(2),
+        Zone::Header0 => Ok(5),
+        Zone::Header1 => Ok(6),
+        zone => Err(format!("unsupported zone: {zone:?}").into()),
+    }
+}
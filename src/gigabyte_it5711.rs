@@ -0,0 +1,103 @@
+//! Gigabyte RGB Fusion 2 IT5711 controller.
+//!
+//! Z690 and Z790 boards use the IT5711, which addresses zones with a 32-bit
+//! bitmask and expects every report as a separate 64 byte feature report. The
+//! effect parameters and D_LED headers are unchanged from the IT8297.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::controller::{HidController, Packet, Transport};
+use crate::gigabyte_it8297::{self, GigabyteIt8297, PACKET_SIZE};
+use crate::strip::HeaderConfig;
+use crate::{strip, Config, Duration, Effect, Rgb, Speed, Zone};
+
+pub struct GigabyteIt5711;
+
+impl HidController for GigabyteIt5711 {
+    fn name(&self) -> &str {
+        "Gigabyte RGB Fusion 2 (IT5711)"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x048d)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x5711)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Cpu, Zone::Audio, Zone::Chipset, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let led = gigabyte_it8297::led_index(config.zone);
+        let strip_type = strip::strip_type(config.device, config.zone);
+        let (color, _) = strip_type.encode(config.color);
+        let (color2, _) = strip_type.encode(config.color2);
+
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+
+        // Report ID.
+        buf.put_u8(0xcc);
+
+        // RGB Zone address and bitmask.
+        buf.put_u8(0x20 + led);
+        buf.put_u32_le(1 << led);
+
+        // Padding.
+        buf.put_slice(&[0; 5]);
+
+        // Effect.
+        buf.put_u8(gigabyte_it8297::effect_bytes(config.effect)?);
+
+        // Max and min Brightness.
+        buf.put_slice(&gigabyte_it8297::brightness_bytes(config.max_brightness));
+        buf.put_slice(&gigabyte_it8297::brightness_bytes(config.min_brightness));
+
+        // Primary and secondary color data.
+        buf.put_slice(&[color.b, color.g, color.r, 0]);
+        buf.put_slice(&[color2.b, color2.g, color2.r, 0]);
+
+        // Color effect timings.
+        buf.put_slice(&gigabyte_it8297::duration_bytes(config.fade_in_time));
+        buf.put_slice(&gigabyte_it8297::duration_bytes(config.fade_out_time));
+        buf.put_slice(&gigabyte_it8297::duration_bytes(config.hold_time));
+
+        buf.resize(PACKET_SIZE, 0);
+        let config_packet = Packet::new(Transport::FeatureReport, buf.freeze());
+
+        // Packet to apply the submitted configuration.
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+        buf.put_slice(&[0xcc, 0x28, 0xff, 0x07]);
+        buf.resize(PACKET_SIZE, 0);
+        let apply_packet = Packet::new(Transport::FeatureReport, buf.freeze());
+
+        Ok(vec![config_packet, apply_packet])
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        GigabyteIt8297.max_leds()
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        GigabyteIt8297.led_bytes(zone, colors)
+    }
+
+    fn header_bytes(
+        &self,
+        headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        GigabyteIt8297.header_bytes(headers)
+    }
+
+    fn speed_timings(&self, speed: Speed) -> (Duration, Duration, Duration) {
+        GigabyteIt8297.speed_timings(speed)
+    }
+}
@@ -0,0 +1,322 @@
+//! Generic Gigabyte RGB Fusion 2 USB controller.
+//!
+//! Most Gigabyte boards with RGB Fusion 2 use an ITE IT8297 or IT5702
+//! controller, which share the same protocol across their product IDs.
+
+use std::error::Error;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::controller::{HidController, Packet};
+use crate::strip::{HeaderConfig, StripType};
+use crate::{strip, Brightness, Config, Duration, Effect, Rgb, Speed, Zone};
+
+/// Size of a single HID report.
+pub const PACKET_SIZE: usize = 64;
+
+/// Colors fitting into a single D_LED packet after its 5 byte header.
+const DIGITAL_LEDS_PER_PACKET: usize = 19;
+
+/// Maximum number of LEDs on a single D_LED header.
+pub const MAX_DIGITAL_LEDS: usize = 1024;
+
+pub struct GigabyteIt8297;
+
+impl HidController for GigabyteIt8297 {
+    fn name(&self) -> &str {
+        "Gigabyte RGB Fusion 2 (IT8297)"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x048d)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x8297)
+    }
+
+    fn product_ids(&self) -> Vec<u16> {
+        vec![0x8297, 0x5702]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Cpu, Zone::Audio, Zone::Chipset, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        led_packets(config, led_index(config.zone))
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(MAX_DIGITAL_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(digital_led_packets(zone, colors))
+    }
+
+    fn header_bytes(
+        &self,
+        headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(header_packets(headers))
+    }
+
+    fn speed_timings(&self, speed: Speed) -> (Duration, Duration, Duration) {
+        // Timings are limited to quarter seconds.
+        match speed {
+            Speed::Slow => (Duration(2000), Duration(2000), Duration(1000)),
+            Speed::Medium => (Duration(1000), Duration(1000), Duration(500)),
+            Speed::Fast => (Duration(500), Duration(500), Duration(250)),
+            Speed::Turbo => (Duration(250), Duration(250), Duration(250)),
+        }
+    }
+}
+
+/// Convert a config to the packets for an LED index of the controller.
+///
+/// Boards differ in which zone is connected to which LED index.
+pub fn led_packets(config: &Config, led: u8) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let strip_type = strip::strip_type(config.device, config.zone);
+    let (color, _) = strip_type.encode(config.color);
+    let (color2, _) = strip_type.encode(config.color2);
+
+    let mut buf = BytesMut::new();
+
+    // Report ID.
+    buf.put_u8(0xcc);
+
+    // RGB Zone.
+    buf.put_u16(zone_bytes(led));
+
+    // Padding.
+    buf.put_slice(&[0; 8]);
+
+    // Effect.
+    buf.put_u8(effect_bytes(config.effect)?);
+
+    // Max Brightness.
+    buf.put_slice(&brightness_bytes(config.max_brightness));
+
+    // Min Brightness.
+    buf.put_slice(&brightness_bytes(config.min_brightness));
+
+    // Primary color Data.
+    buf.put_u8(color.b);
+    buf.put_u8(color.g);
+    buf.put_u8(color.r);
+
+    // Padding.
+    buf.put_u8(0);
+
+    // Secondary color Data.
+    buf.put_u8(color2.b);
+    buf.put_u8(color2.g);
+    buf.put_u8(color2.r);
+
+    // Padding.
+    buf.put_u8(0);
+
+    // Color effect timings.
+    buf.put_slice(&duration_bytes(config.fade_in_time));
+    buf.put_slice(&duration_bytes(config.fade_out_time));
+    buf.put_slice(&duration_bytes(config.hold_time));
+
+    // Padding for minimum packet size.
+    buf.put_slice(&[0; 3]);
+
+    // Packet to apply the submitted configuration.
+    buf.put_u8(0xcc);
+    buf.put_u8(0x28);
+    buf.put_u8(0xff);
+    buf.put_slice(&[0; 20]);
+
+    Ok(vec![buf.freeze().into()])
+}
+
+/// Convert individual LED colors to packets for a D_LED header.
+///
+/// Switching a header to digital mode disables its built-in effects, the
+/// colors are then streamed in chunks of 19 LEDs per 64 byte packet.
+pub fn digital_led_packets(zone: Zone, colors: &[Rgb]) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let header = match zone {
+        Zone::Header0 => 0,
+        Zone::Header1 => 1,
+        zone => return Err(format!("zone {zone:?} is not an addressable D_LED header").into()),
+    };
+
+    if colors.len() > MAX_DIGITAL_LEDS {
+        return Err(format!("D_LED headers support at most {MAX_DIGITAL_LEDS} LEDs").into());
+    }
+
+    let mut packets = Vec::new();
+
+    // Enable digital mode for the header.
+    let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+    buf.put_u8(0xcc);
+    buf.put_u8(0x32);
+    buf.put_u8(1 << header);
+    buf.resize(PACKET_SIZE, 0);
+    packets.push(buf.freeze().into());
+
+    for (i, chunk) in colors.chunks(DIGITAL_LEDS_PER_PACKET).enumerate() {
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+
+        // Report ID and header.
+        buf.put_u8(0xcc);
+        buf.put_u8(0x58 + header);
+
+        // Byte offset and length of the color data.
+        buf.put_u16_le((i * DIGITAL_LEDS_PER_PACKET * 3) as u16);
+        buf.put_u8((chunk.len() * 3) as u8);
+
+        for color in chunk {
+            buf.put_slice(&[color.r, color.g, color.b]);
+        }
+
+        buf.resize(PACKET_SIZE, 0);
+        packets.push(buf.freeze().into());
+    }
+
+    Ok(packets)
+}
+
+/// Convert the strip configuration of both D_LED headers to packets.
+///
+/// Missing headers are reset to their defaults, since the controller expects
+/// the LED counts and channel orders of all headers at once.
+pub fn header_packets(headers: &[(Zone, HeaderConfig)]) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let header = |zone| {
+        let config = headers.iter().find(|(header, _)| *header == zone);
+        config.map_or_else(HeaderConfig::default, |(_, config)| *config)
+    };
+    let (header0, header1) = (header(Zone::Header0), header(Zone::Header1));
+
+    // LED count of both headers.
+    let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+    buf.put_u8(0xcc);
+    buf.put_u8(0x34);
+    buf.put_u8(led_count_bytes(header0.led_count)? | led_count_bytes(header1.led_count)? << 4);
+    buf.resize(PACKET_SIZE, 0);
+    let led_count = buf.freeze().into();
+
+    // Channel order of both headers, followed by the onboard LEDs. Header
+    // colors are already in the order of their strip.
+    let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+    buf.put_u8(0xcc);
+    buf.put_u8(0x33);
+    buf.put_slice(&calibration_bytes(StripType::Rgb)?);
+    buf.put_slice(&calibration_bytes(StripType::Rgb)?);
+    buf.put_slice(&calibration_bytes(StripType::Bgr)?);
+    buf.put_slice(&calibration_bytes(StripType::Bgr)?);
+    buf.resize(PACKET_SIZE, 0);
+    let calibration = buf.freeze().into();
+
+    Ok(vec![led_count, calibration])
+}
+
+/// Convert an LED count to the smallest D_LED size covering it.
+fn led_count_bytes(count: u16) -> Result<u8, Box<dyn Error>> {
+    match count {
+        0..=32 => Ok(0),
+        33..=64 => Ok(1),
+        65..=256 => Ok(2),
+        257..=512 => Ok(3),
+        513..=1024 => Ok(4),
+        count => {
+            Err(format!("D_LED headers support at most {MAX_DIGITAL_LEDS} LEDs, got {count}")
+                .into())
+        },
+    }
+}
+
+/// Convert a strip type to the channel offsets of red, green and blue.
+fn calibration_bytes(strip_type: StripType) -> Result<[u8; 4], Box<dyn Error>> {
+    match strip_type {
+        StripType::Rgb => Ok([0, 1, 2, 0]),
+        StripType::Rbg => Ok([0, 2, 1, 0]),
+        StripType::Grb => Ok([1, 0, 2, 0]),
+        StripType::Gbr => Ok([2, 0, 1, 0]),
+        StripType::Brg => Ok([1, 2, 0, 0]),
+        StripType::Bgr => Ok([2, 1, 0, 0]),
+        strip_type => Err(format!("unsupported strip type: {strip_type:?}").into()),
+    }
+}
+
+/// Convert duration to RGB Fusion format.
+pub fn duration_bytes(duration: Duration) -> Bytes {
+    let mut bytes = BytesMut::with_capacity(2);
+
+    // Convert from milliseconds to quarter seconds.
+    bytes.put_u16(duration.0 / 250);
+
+    bytes.freeze()
+}
+
+/// Convert brightness to RGB Fusion format.
+pub fn brightness_bytes(brightness: Brightness) -> Bytes {
+    // Convert format from 0..=255 to the protocol's range 0..=90.
+    let byte = (0x5a * brightness.0 as u16 / u8::MAX as u16) as u8;
+    Bytes::copy_from_slice(&[byte])
+}
+
+/// Convert effect type to RGB Fusion format.
+pub fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Off => Ok(0),
+        Effect::Static => Ok(1),
+        Effect::Pulse => Ok(2),
+        Effect::Flash => Ok(3),
+        Effect::Cycle => Ok(4),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Get the LED index of a zone on most boards.
+pub fn led_index(zone: Zone) -> u8 {
+    match zone {
+        Zone::Io => 0,
+        Zone::Cpu => 1,
+        Zone::Audio => 3,
+        Zone::Chipset => 4,
+        Zone::Header0 => 5,
+        Zone::Header1 => 6,
+    }
+}
+
+/// Convert an LED index to its RGB Fusion zone address and bitmask.
+fn zone_bytes(led: u8) -> u16 {
+    (0x20 + led as u16) << 8 | 1 << led
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digital_led_chunks() {
+        let colors = vec![Rgb { r: 1, g: 2, b: 3 }; 20];
+        let packets = digital_led_packets(Zone::Header1, &colors).unwrap();
+
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[0].bytes[..3], [0xcc, 0x32, 0x02]);
+        assert_eq!(packets[1].bytes[..8], [0xcc, 0x59, 0, 0, 57, 1, 2, 3]);
+        assert_eq!(packets[2].bytes[..8], [0xcc, 0x59, 57, 0, 3, 1, 2, 3]);
+        assert!(packets.iter().all(|packet| packet.bytes.len() == PACKET_SIZE));
+
+        assert!(digital_led_packets(Zone::Cpu, &colors).is_err());
+    }
+
+    #[test]
+    fn header_configuration() {
+        let header1 = HeaderConfig { led_count: 60 };
+        let packets = header_packets(&[(Zone::Header1, header1)]).unwrap();
+
+        assert_eq!(packets[0].bytes[..3], [0xcc, 0x34, 0x10]);
+        assert_eq!(packets[1].bytes[..10], [0xcc, 0x33, 0, 1, 2, 0, 0, 1, 2, 0]);
+    }
+}
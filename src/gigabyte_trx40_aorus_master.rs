@@ -1,113 +1,67 @@
 //! Gigabyte TRX40 Aorus Master RGB Fusion control.
+//!
+//! The board uses the generic IT8297 protocol, but its zone layout is known.
 
 use std::error::Error;
 
-use bytes::{BufMut, Bytes, BytesMut};
-
-use crate::controller::HidController;
-use crate::{Brightness, Config, Duration, Effect, Zone};
+use crate::controller::{HidController, Packet};
+use crate::gigabyte_it8297::GigabyteIt8297;
+use crate::strip::HeaderConfig;
+use crate::{Config, Duration, Effect, Rgb, Speed, Zone};
 
 pub struct GigabyteTrx40AorusMaster;
 
 impl HidController for GigabyteTrx40AorusMaster {
-    fn vendor_id(&self) -> u16 {
-        0x048d
+    fn name(&self) -> &str {
+        "Gigabyte TRX40 Aorus Master"
     }
 
-    fn product_id(&self) -> u16 {
-        0x8297
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x048d)
     }
 
-    fn config_bytes(&self, config: &Config) -> Result<Vec<Bytes>, Box<dyn Error>> {
-        let mut buf = BytesMut::new();
-
-        // Report ID.
-        buf.put_u8(0xcc);
-
-        // RGB Zone.
-        buf.put_u16(zone_bytes(config.zone));
-
-        // Padding.
-        buf.put_slice(&[0; 8]);
-
-        // Effect.
-        buf.put_u8(effect_bytes(config.effect)?);
-
-        // Max Brightness.
-        buf.put_slice(&brightness_bytes(config.max_brightness));
-
-        // Min Brightness.
-        buf.put_slice(&brightness_bytes(config.min_brightness));
-
-        // Primary color Data.
-        buf.put_u8(config.color.b);
-        buf.put_u8(config.color.g);
-        buf.put_u8(config.color.r);
-
-        // Padding.
-        buf.put_u8(0);
-
-        // Secondary color Data.
-        buf.put_slice(&[0; 3]);
-
-        // Padding.
-        buf.put_u8(0);
-
-        // Color effect timings.
-        buf.put_slice(&duration_bytes(config.fade_in_time));
-        buf.put_slice(&duration_bytes(config.fade_out_time));
-        buf.put_slice(&duration_bytes(config.hold_time));
+    fn product_id(&self) -> Option<u16> {
+        Some(0x8297)
+    }
 
-        // Padding for minimum packet size.
-        buf.put_slice(&[0; 3]);
+    fn board_name(&self) -> Option<&str> {
+        Some("TRX40 AORUS MASTER")
+    }
 
-        // Packet to apply the submitted configuration.
-        buf.put_u8(0xcc);
-        buf.put_u8(0x28);
-        buf.put_u8(0xff);
-        buf.put_slice(&[0; 20]);
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Cpu, Zone::Audio, Zone::Chipset, Zone::Header0, Zone::Header1]
+    }
 
-        Ok(vec![buf.freeze()])
+    fn physical_zones(&self) -> &[Zone] {
+        // Onboard zones from the top of the board to the bottom, followed by the
+        // external headers.
+        &[Zone::Io, Zone::Cpu, Zone::Chipset, Zone::Audio, Zone::Header0, Zone::Header1]
     }
-}
 
-/// Convert duration to RGB Fusion format.
-fn duration_bytes(duration: Duration) -> Bytes {
-    let mut bytes = BytesMut::with_capacity(2);
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle]
+    }
 
-    // Convert from milliseconds to quarter seconds.
-    bytes.put_u16(duration.0 / 250);
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        GigabyteIt8297.config_bytes(config)
+    }
 
-    bytes.freeze()
-}
+    fn max_leds(&self) -> Option<usize> {
+        GigabyteIt8297.max_leds()
+    }
 
-/// Convert brightness to RGB Fusion format.
-fn brightness_bytes(brightness: Brightness) -> Bytes {
-    // Convert format from 0..=255 to the protocol's range 0..=90.
-    let byte = (0x5a * brightness.0 as u16 / u8::max_value() as u16) as u8;
-    Bytes::copy_from_slice(&[byte])
-}
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        GigabyteIt8297.led_bytes(zone, colors)
+    }
 
-/// Convert effect type to RGB Fusion format.
-fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
-    match effect {
-        Effect::Off => Ok(0),
-        Effect::Static => Ok(1),
-        Effect::Pulse => Ok(2),
-        Effect::Flash => Ok(3),
-        Effect::Cycle => Ok(4),
-        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    fn header_bytes(
+        &self,
+        headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        GigabyteIt8297.header_bytes(headers)
     }
-}
 
-/// Convert zone to RGB Fusion format.
-fn zone_bytes(zone: Zone) -> u16 {
-    match zone {
-        Zone::Io => 0x2001,
-        Zone::Cpu => 0x2102,
-        Zone::Audio => 0x2308,
-        Zone::Chipset => 0x2410,
-        Zone::Header0 => 0x2520,
-        Zone::Header1 => 0x2640,
+    fn speed_timings(&self, speed: Speed) -> (Duration, Duration, Duration) {
+        GigabyteIt8297.speed_timings(speed)
     }
 }
@@ -0,0 +1,62 @@
+//! Gigabyte X570 Aorus Elite RGB Fusion control.
+//!
+//! The board uses an IT5702 controller with the common IT8297 LED layout, but
+//! only has a single ARGB header and no CPU zone.
+
+use std::error::Error;
+
+use crate::controller::{HidController, Packet};
+use crate::gigabyte_it8297::{self, GigabyteIt8297};
+use crate::strip::HeaderConfig;
+use crate::{Config, Duration, Effect, Rgb, Speed, Zone};
+
+pub struct GigabyteX570AorusElite;
+
+impl HidController for GigabyteX570AorusElite {
+    fn name(&self) -> &str {
+        "Gigabyte X570 Aorus Elite"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x048d)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x5702)
+    }
+
+    fn board_name(&self) -> Option<&str> {
+        Some("X570 AORUS ELITE")
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Audio, Zone::Chipset, Zone::Header0]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        gigabyte_it8297::led_packets(config, gigabyte_it8297::led_index(config.zone))
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(gigabyte_it8297::MAX_DIGITAL_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(gigabyte_it8297::digital_led_packets(zone, colors))
+    }
+
+    fn header_bytes(
+        &self,
+        headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(gigabyte_it8297::header_packets(headers))
+    }
+
+    fn speed_timings(&self, speed: Speed) -> (Duration, Duration, Duration) {
+        GigabyteIt8297.speed_timings(speed)
+    }
+}
@@ -0,0 +1,74 @@
+//! Gigabyte Z390 Aorus series RGB Fusion control.
+//!
+//! These boards use the IT8297 protocol, with the chipset connected to a
+//! different LED than on other boards.
+
+use std::error::Error;
+
+use crate::controller::{HidController, Packet};
+use crate::gigabyte_it8297::{self, GigabyteIt8297};
+use crate::strip::HeaderConfig;
+use crate::{Config, Duration, Effect, Rgb, Speed, Zone};
+
+pub struct GigabyteZ390Aorus;
+
+impl HidController for GigabyteZ390Aorus {
+    fn name(&self) -> &str {
+        "Gigabyte Z390 Aorus"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x048d)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x8297)
+    }
+
+    fn board_name(&self) -> Option<&str> {
+        Some("Z390 AORUS")
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Cpu, Zone::Audio, Zone::Chipset, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        gigabyte_it8297::led_packets(config, led_index(config.zone))
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(gigabyte_it8297::MAX_DIGITAL_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(gigabyte_it8297::digital_led_packets(zone, colors))
+    }
+
+    fn header_bytes(
+        &self,
+        headers: &[(Zone, HeaderConfig)],
+    ) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(gigabyte_it8297::header_packets(headers))
+    }
+
+    fn speed_timings(&self, speed: Speed) -> (Duration, Duration, Duration) {
+        GigabyteIt8297.speed_timings(speed)
+    }
+}
+
+/// Get the LED index of a zone.
+fn led_index(zone: Zone) -> u8 {
+    match zone {
+        Zone::Io => 0,
+        Zone::Cpu => 1,
+        Zone::Chipset => 2,
+        Zone::Audio => 3,
+        Zone::Header0 => 5,
+        Zone::Header1 => 6,
+    }
+}
@@ -0,0 +1,52 @@
+//! G.Skill Trident Z RGB DRAM control.
+//!
+//! Trident Z RGB and Trident Z Neo sticks use the same ENE controller as Aura
+//! DRAM, including its address assignment.
+
+use std::error::Error;
+
+use hidapi::HidApi;
+
+use crate::asus_aura_dram::{self, AsusAuraDram};
+use crate::controller::{DeviceHandle, HidController, Packet};
+use crate::{Config, Effect, HidSelector, Zone};
+
+pub struct GskillTridentZ;
+
+impl HidController for GskillTridentZ {
+    fn name(&self) -> &str {
+        "G.Skill Trident Z RGB"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        AsusAuraDram.smbus_addresses()
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        AsusAuraDram.supported_zones()
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        AsusAuraDram.supported_effects()
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn open(&self, _api: &HidApi, _selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        asus_aura_dram::open_sticks("Trident Z RGB DRAM")
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        AsusAuraDram.config_bytes(config)
+    }
+}
@@ -0,0 +1,239 @@
+//! HTTP REST API for the daemon.
+//!
+//! Endpoints:
+//!
+//!  - `GET /state` returns all applied zone configs, grouped by device
+//!  - `PUT /zones/{zone}[?device=<device>]` applies a JSON zone config
+//!
+//! Zone configs use the same fields as the TOML configuration files:
+//!
+//! ```json
+//! { "effect": "pulse", "color": "0xff0000", "hold_time": 500 }
+//! ```
+
+use std::error::Error;
+use std::io::{BufRead, BufReader, Read, Take, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{slice, thread};
+
+use serde_json::json;
+
+use crate::config_file::ConfigFile;
+use crate::daemon::Daemon;
+use crate::{zone_map, Config, Device};
+
+/// Maximum time a client may take to send its request.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum accepted request body size.
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Maximum accepted size of the request line and headers.
+const MAX_HEADER_SIZE: u64 = 8 * 1024;
+
+/// Start the HTTP server on a background thread.
+pub fn spawn_server(address: &str, daemon: Arc<Mutex<Daemon>>) -> Result<(), Box<dyn Error>> {
+    let listener =
+        TcpListener::bind(address).map_err(|err| format!("unable to bind to {address}: {err}"))?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    eprintln!("HTTP connection failed: {err}");
+                    continue;
+                },
+            };
+
+            let daemon = daemon.clone();
+            thread::spawn(move || {
+                if let Err(err) = handle_client(&daemon, stream) {
+                    eprintln!("HTTP client error: {err}");
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+/// Process a single HTTP request.
+fn handle_client(daemon: &Mutex<Daemon>, stream: TcpStream) -> Result<(), Box<dyn Error>> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let response = match Request::read(&mut reader) {
+        Ok(request) => request.respond(daemon),
+        Err(err) => Response::error("400 Bad Request", err),
+    };
+
+    response.write(&stream)
+}
+
+/// Parsed HTTP request.
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    body: String,
+}
+
+impl Request {
+    /// Read a request from the client.
+    fn read(reader: &mut impl BufRead) -> Result<Self, Box<dyn Error>> {
+        let mut head = reader.by_ref().take(MAX_HEADER_SIZE);
+        let mut line = String::new();
+        read_head_line(&mut head, &mut line)?;
+
+        let mut request_line = line.split_whitespace();
+        let (method, target) = match (request_line.next(), request_line.next()) {
+            (Some(method), Some(target)) => (method.to_owned(), target),
+            _ => return Err(format!("invalid request line: {line:?}").into()),
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let (path, query) = (path.to_owned(), query.to_owned());
+
+        // Only the body length is relevant, all other headers are ignored.
+        let mut content_length = 0;
+        loop {
+            line.clear();
+            if read_head_line(&mut head, &mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = usize::from_str(value.trim())
+                        .map_err(|_| format!("invalid content length: {}", value.trim()))?;
+                }
+            }
+        }
+
+        if content_length > MAX_BODY_SIZE {
+            return Err(format!("request body exceeds {MAX_BODY_SIZE} bytes").into());
+        }
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        let body = String::from_utf8(body)?;
+
+        Ok(Self { method, path, query, body })
+    }
+
+    /// Handle the request.
+    fn respond(&self, daemon: &Mutex<Daemon>) -> Response {
+        let zone = self.path.strip_prefix("/zones/");
+        match (self.method.as_str(), self.path.as_str(), zone) {
+            ("GET", "/state", _) => {
+                let state = daemon.lock().unwrap().state();
+                match serde_json::to_string(&state) {
+                    Ok(body) => Response::ok(body),
+                    Err(err) => Response::error("500 Internal Server Error", err.into()),
+                }
+            },
+            ("PUT", _, Some(zone)) => match self.config(daemon, zone) {
+                Ok(config) => {
+                    match daemon.lock().unwrap().apply(slice::from_ref(&config)).remove(0) {
+                        Ok(()) => match serde_json::to_string(&config) {
+                            Ok(body) => Response::ok(body),
+                            Err(err) => Response::error("500 Internal Server Error", err.into()),
+                        },
+                        Err(err) => Response::error("500 Internal Server Error", err),
+                    }
+                },
+                Err(err) => Response::error("400 Bad Request", err),
+            },
+            (_, "/state", _) | (_, _, Some(_)) => {
+                Response::error("405 Method Not Allowed", "method not allowed".into())
+            },
+            _ => Response::error("404 Not Found", "not found".into()),
+        }
+    }
+
+    /// Parse the zone config of a `PUT /zones/{zone}` request.
+    fn config(&self, daemon: &Mutex<Daemon>, zone: &str) -> Result<Config, Box<dyn Error>> {
+        let mut config: Config = match self.body.trim() {
+            "" => Config::default(),
+            body => serde_json::from_str(body)?,
+        };
+
+        let device = self
+            .query
+            .split('&')
+            .find_map(|param| param.strip_prefix("device="))
+            .map(Device::from_str)
+            .transpose()?;
+        let device = daemon.lock().unwrap().resolve_device(device)?;
+        config.zone = zone_map::zone(&percent_decode(zone)?, Some(device))?;
+
+        let mut file = ConfigFile::new(device);
+        file.insert(config);
+        Ok(file.into_configs(device)?.remove(0))
+    }
+}
+
+/// Read a line of the request line or headers, failing once they exceed
+/// [`MAX_HEADER_SIZE`].
+fn read_head_line(
+    head: &mut Take<impl BufRead>,
+    line: &mut String,
+) -> Result<usize, Box<dyn Error>> {
+    let len = head.read_line(line)?;
+    if head.limit() == 0 && !line.ends_with('\n') {
+        return Err(format!("request headers exceed {MAX_HEADER_SIZE} bytes").into());
+    }
+    Ok(len)
+}
+
+/// Decode percent-encoded characters of a URL path segment.
+fn percent_decode(segment: &str) -> Result<String, Box<dyn Error>> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match (byte, tail) {
+            (b'%', [high, low, tail @ ..]) => {
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&[*high, *low])?, 16)?);
+                rest = tail;
+            },
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            },
+        }
+    }
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// HTTP response with a JSON body.
+struct Response {
+    status: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self { status: "200 OK", body }
+    }
+
+    fn error(status: &'static str, err: Box<dyn Error>) -> Self {
+        Self { status, body: json!({ "error": err.to_string() }).to_string() }
+    }
+
+    /// Send the response to the client.
+    fn write(&self, mut stream: &TcpStream) -> Result<(), Box<dyn Error>> {
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: \
+             close\r\n\r\n{}",
+            self.status,
+            self.body.len(),
+            self.body
+        )?;
+
+        Ok(())
+    }
+}
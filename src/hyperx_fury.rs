@@ -0,0 +1,148 @@
+//! HyperX and Kingston Fury RGB DRAM control.
+//!
+//! Every stick is controlled separately, with the DIMMs at the first to fourth
+//! address mapped to the `header0`, `header1`, `io` and `cpu` zones.
+
+use std::error::Error;
+
+use bytes::Bytes;
+use hidapi::HidApi;
+
+use crate::controller::{DeviceHandle, HidController, Packet, Transport};
+use crate::smbus::{self, SmbusDevice};
+use crate::{strip, Brightness, Config, Duration, Effect, HidSelector, Zone};
+
+/// Name prefixes of the chipset's SMBus adapters.
+const ADAPTERS: &[&str] = &["SMBus"];
+
+/// SMBus addresses used by the sticks, in zone order.
+const ADDRESSES: [u8; 4] = [0x58, 0x59, 0x5a, 0x5b];
+
+/// Model identifier reported by Fury sticks.
+const MODEL_ID: u8 = 0x46;
+
+const REG_MODEL: u8 = 0x21;
+const REG_APPLY: u8 = 0x08;
+const REG_MODE: u8 = 0x09;
+const REG_SPEED: u8 = 0x0c;
+const REG_BRIGHTNESS: u8 = 0x20;
+const REG_COLOR: u8 = 0x31;
+
+/// Start a configuration transaction.
+const BEGIN: u8 = 0x53;
+
+/// Apply the configuration transaction.
+const END: u8 = 0x44;
+
+/// Slowest speed setting of the sticks.
+const MAX_SPEED: u16 = 4;
+
+pub struct HyperxFury;
+
+impl HidController for HyperxFury {
+    fn name(&self) -> &str {
+        "HyperX Fury RGB"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        &ADDRESSES
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Header0, Zone::Header1, Zone::Io, Zone::Cpu]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Flash, Effect::Cycle, Effect::Rainbow]
+    }
+
+    fn open(&self, _api: &HidApi, _selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        for adapter in smbus::adapters(ADAPTERS) {
+            let sticks: Vec<_> = ADDRESSES
+                .iter()
+                .copied()
+                .filter(|address| match SmbusDevice::open(&adapter, &[*address]) {
+                    Ok(device) => matches!(device.read_byte_data(REG_MODEL), Ok(MODEL_ID)),
+                    Err(_) => false,
+                })
+                .collect();
+
+            if !sticks.is_empty() {
+                return Ok(DeviceHandle::Smbus(SmbusDevice::open(&adapter, &sticks)?));
+            }
+        }
+
+        Err("no Fury RGB DRAM found, is the `i2c-dev` module loaded?".into())
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+        let address = address_bytes(config.zone)?;
+
+        // The sticks have no off mode, so it is emulated with zero brightness.
+        let (mode, brightness) = match config.effect {
+            Effect::Off => (effect_bytes(Effect::Static)?, 0),
+            effect => (effect_bytes(effect)?, brightness_bytes(config.max_brightness)),
+        };
+
+        let writes = [
+            (REG_APPLY, BEGIN),
+            (REG_MODE, mode),
+            (REG_SPEED, speed_bytes(config.fade_in_time)),
+            (REG_BRIGHTNESS, brightness),
+            (REG_COLOR, color.r),
+            (REG_COLOR + 1, color.g),
+            (REG_COLOR + 2, color.b),
+            (REG_APPLY, END),
+        ];
+
+        Ok(writes
+            .iter()
+            .map(|(reg, value)| {
+                let bytes = Bytes::copy_from_slice(&[address, *reg, *value]);
+                Packet::new(Transport::SmbusAddressedByte, bytes)
+            })
+            .collect())
+    }
+}
+
+/// Convert zone to the address of its stick.
+fn address_bytes(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Header0 => Ok(ADDRESSES[0]),
+        Zone::Header1 => Ok(ADDRESSES[1]),
+        Zone::Io => Ok(ADDRESSES[2]),
+        Zone::Cpu => Ok(ADDRESSES[3]),
+        zone => Err(format!("unsupported zone: {zone:?}").into()),
+    }
+}
+
+/// Convert effect type to Fury format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Static => Ok(0x00),
+        Effect::Rainbow => Ok(0x01),
+        Effect::Cycle => Ok(0x02),
+        Effect::Pulse => Ok(0x03),
+        Effect::Flash => Ok(0x04),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Convert effect duration to the sticks' speed, with 0 being the fastest.
+fn speed_bytes(duration: Duration) -> u8 {
+    (duration.0 / 500).min(MAX_SPEED) as u8
+}
+
+/// Convert brightness to the sticks' percentage.
+fn brightness_bytes(brightness: Brightness) -> u8 {
+    (100 * brightness.0 as u16 / u8::MAX as u16) as u8
+}
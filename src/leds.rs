@@ -0,0 +1,237 @@
+//! Addressable LED headers.
+//!
+//! The strips connected to each header are stored in
+//! `~/.config/rgbfusion/headers.toml`, since controllers expect the
+//! configuration of all their headers at once:
+//!
+//! ```toml
+//! [trx40.header0]
+//! led_count = 60
+//! ```
+//!
+//! Strip types are stored in `strips.toml` instead, see [`strip`].
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::builder::EnumValueParser;
+use clap::{value_parser, Arg, ArgMatches, Command, ValueEnum};
+use hidapi::HidApi;
+
+use crate::controller::Packet;
+use crate::strip::{HeaderConfig, StripType};
+use crate::{dry_run, output, strip, transcript, zone_map, Device, Palette, Zone};
+
+/// Header configs of every device.
+type Headers = BTreeMap<String, BTreeMap<String, HeaderConfig>>;
+
+/// Get clap CLI parameters for the leds subcommand.
+pub fn subcommand() -> Command {
+    Command::new("leds")
+        .about("Set the color of every LED on an addressable header")
+        .arg(
+            Arg::new("colors")
+                .help("Colors of the LEDs, starting at the controller [COLOR,COLOR,...]")
+                .required(true)
+                .value_parser(value_parser!(Palette)),
+        )
+        .arg(
+            Arg::new("count")
+                .help("Number of LEDs, repeating the colors to fill the strip")
+                .long("count")
+                .value_parser(value_parser!(usize)),
+        )
+}
+
+/// Get clap CLI parameters for the header subcommand.
+pub fn header_subcommand() -> Command {
+    Command::new("header")
+        .about("Configure the LED strip connected to an addressable header")
+        .arg(
+            Arg::new("led-count")
+                .help("Number of LEDs on the strip")
+                .long("led-count")
+                .value_parser(value_parser!(u16).range(1..)),
+        )
+        .arg(
+            Arg::new("strip-type")
+                .help("Channel order of the strip, stored in strips.toml")
+                .long("strip-type")
+                .ignore_case(true)
+                .value_parser(EnumValueParser::<StripType>::new()),
+        )
+}
+
+/// Run the leds subcommand.
+pub fn leds(matches: &ArgMatches) {
+    if let Err(err) = write(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Write the LED colors to the selected header.
+fn write(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let device = crate::required_device(matches)?;
+    let controller = device.controller();
+
+    let zone = match zones(matches, device)?.as_slice() {
+        [zone] => *zone,
+        _ => return Err("per-LED colors require exactly one `--zone`".into()),
+    };
+
+    let colors = &matches.get_one::<Palette>("colors").unwrap().0;
+    let count = matches.get_one::<usize>("count").copied().unwrap_or(colors.len());
+    check_led_count(device, count)?;
+    let strip_type = strip::strip_type(device, zone);
+    let colors: Vec<_> =
+        colors.iter().cycle().take(count).map(|color| strip_type.encode(*color).0).collect();
+
+    let packets = match controller.led_bytes(zone, &colors) {
+        Some(packets) => packets?,
+        None => return Err(format!("{} doesn't support per-LED colors", controller.name()).into()),
+    };
+
+    if crate::hid_options().dry_run {
+        println!("{device:?} zone {zone:?} with {count} LEDs:");
+        print_packets(&packets);
+        return Ok(());
+    }
+
+    send(device, &packets)?;
+    output::message(&format!("\x1b[32mSuccessfully applied {count} LED colors.\x1b[0m"));
+
+    Ok(())
+}
+
+/// Run the header subcommand.
+pub fn header(matches: &ArgMatches) {
+    if let Err(err) = configure(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Update the strip configuration of the selected headers.
+fn configure(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let device = crate::required_device(matches)?;
+    let controller = device.controller();
+
+    let zones = zones(matches, device)?;
+    if zones.is_empty() {
+        return Err("no header selected, use `--zone`".into());
+    }
+
+    let led_count = matches.get_one::<u16>("led-count").copied();
+    if let Some(led_count) = led_count {
+        check_led_count(device, led_count as usize)?;
+    }
+
+    let mut headers = load()?;
+    let device_headers = headers.entry(format!("{device:?}")).or_default();
+    for zone in &zones {
+        let key = zone.to_possible_value().unwrap().get_name().to_owned();
+        let header = device_headers.entry(key).or_default();
+        if let Some(led_count) = led_count {
+            header.led_count = led_count;
+        }
+    }
+
+    let configs: Vec<_> = device_headers
+        .iter()
+        .map(|(zone, config)| Ok((Zone::from_str(zone, true)?, *config)))
+        .collect::<Result<_, String>>()?;
+    let packets = match controller.header_bytes(&configs) {
+        Some(packets) => packets?,
+        None => {
+            return Err(format!("{} doesn't support header configuration", controller.name()).into())
+        },
+    };
+
+    if crate::hid_options().dry_run {
+        println!("{device:?} header configuration:");
+        print_packets(&packets);
+        return Ok(());
+    }
+
+    send(device, &packets)?;
+    save(&headers)?;
+
+    if let Some(strip_type) = matches.get_one::<StripType>("strip-type") {
+        for zone in zones {
+            strip::save(device, zone, *strip_type)?;
+        }
+    }
+
+    output::message("\x1b[32mSuccessfully configured headers.\x1b[0m");
+
+    Ok(())
+}
+
+/// Get the selected zones, ensuring the device supports them.
+fn zones(matches: &ArgMatches, device: Device) -> Result<Vec<Zone>, Box<dyn Error>> {
+    let controller = device.controller();
+    let names = matches.get_many::<String>("zone").into_iter().flatten();
+    let zones =
+        names.map(|name| zone_map::zone(name, Some(device))).collect::<Result<Vec<_>, _>>()?;
+
+    match zones.iter().find(|zone| !controller.supported_zones().contains(zone)) {
+        Some(zone) => Err(format!("{} has no zone {zone:?}", controller.name()).into()),
+        None => Ok(zones),
+    }
+}
+
+/// Ensure a device's addressable zones support an LED count.
+fn check_led_count(device: Device, count: usize) -> Result<(), Box<dyn Error>> {
+    let controller = device.controller();
+    match controller.max_leds() {
+        Some(max_leds) if count > max_leds => {
+            Err(format!("{} supports at most {max_leds} LEDs per zone", controller.name()).into())
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Write packets to a device.
+fn send(device: Device, packets: &[Packet]) -> Result<(), Box<dyn Error>> {
+    let api = HidApi::new().map_err(|err| format!("unable to access HID: {err}"))?;
+    let hid_device = crate::open_device(&api, device)?;
+    for packet in packets {
+        crate::send_packet(&hid_device, packet)
+            .map_err(|err| format!("unable to write packet: {err}"))?;
+        transcript::record(device, packet)?;
+    }
+
+    Ok(())
+}
+
+/// Print packets instead of writing them.
+fn print_packets(packets: &[Packet]) {
+    for (i, packet) in packets.iter().enumerate() {
+        println!("  Packet {} ({:?}, {} bytes):", i + 1, packet.transport, packet.bytes.len());
+        print!("{}", dry_run::hex_dump(&packet.bytes));
+    }
+}
+
+/// Get the path of the header configuration.
+fn path() -> Option<PathBuf> {
+    Some(crate::config_dir()?.join("headers.toml"))
+}
+
+/// Load the header configs of all devices.
+fn load() -> Result<Headers, Box<dyn Error>> {
+    let path = path().ok_or("unable to locate config directory")?;
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok(toml::from_str(&content)
+            .map_err(|err| format!("invalid header configuration {}: {err}", path.display()))?),
+        Err(_) => Ok(BTreeMap::new()),
+    }
+}
+
+/// Store the header configs of all devices.
+fn save(headers: &Headers) -> Result<(), Box<dyn Error>> {
+    let path = path().ok_or("unable to locate config directory")?;
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, toml::to_string(headers)?)
+        .map_err(|err| format!("unable to write {}: {err}", path.display()).into())
+}
@@ -3,23 +3,139 @@
 //! The Gigabyte RGB Fusion 2 HID protocol information is documentad at
 //! https://gitlab.com/CalcProgrammer1/OpenRGB/-/wikis/Gigabyte-RGB-Fusion-2.0.
 
+use std::any::Any;
 use std::error::Error;
+use std::ffi::CString;
 use std::fmt::{self, Debug, Display, Formatter};
 use std::io::{self, Write};
 use std::num::ParseIntError;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::str::FromStr;
-
-use clap::builder::EnumValueParser;
-use clap::{crate_description, crate_name, crate_version, Arg, ArgMatches, Command, ValueEnum};
-use hidapi::HidApi;
-
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::{env, fs, thread};
+
+use clap::builder::{EnumValueParser, PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::{
+    crate_description, crate_name, crate_version, value_parser, Arg, ArgAction, ArgMatches,
+    Command, ValueEnum,
+};
+use hidapi::{DeviceInfo, HidApi, HidDevice};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
+
+use crate::amd_wraith_prism::AmdWraithPrism;
+use crate::asrock_polychrome::AsrockPolychrome;
+use crate::asus_aura_dram::AsusAuraDram;
+use crate::asus_aura_gpu::AsusAuraGpu;
+use crate::asus_aura_smbus::AsusAuraSmbus;
+use crate::asus_crosshair_x670e_extreme::AsusRogCrosshairX670EExtreme;
+use crate::asus_crosshair_x670e_hero::AsusRogCrosshairX670EHero;
+use crate::asus_strix_x670e_e::AsusRogStrixX670EE;
 use crate::asus_strix_x670e_f::AsusRogStrixX670EF;
-use crate::controller::HidController;
+use crate::asus_strix_z790::AsusRogStrixZ790;
+use crate::config_file::DeviceConfigs;
+use crate::controller::{DeviceHandle, HidController, IdOverride, Packet};
+use crate::corsair_capellix::CorsairCapellix;
+use crate::corsair_lighting_node::CorsairLightingNode;
+use crate::corsair_vengeance_pro::CorsairVengeancePro;
+use crate::crucial_ballistix::CrucialBallistix;
+use crate::deepcool_hub::DeepcoolHub;
+use crate::definition::ControllerDefinition;
+use crate::evga_gpu::EvgaGpu;
+use crate::gigabyte_aorus_gpu::GigabyteAorusGpu;
+use crate::gigabyte_b550_aorus_pro::GigabyteB550AorusPro;
+use crate::gigabyte_it5711::GigabyteIt5711;
+use crate::gigabyte_it8297::GigabyteIt8297;
 use crate::gigabyte_trx40_aorus_master::GigabyteTrx40AorusMaster;
-
+use crate::gigabyte_x570_aorus_elite::GigabyteX570AorusElite;
+use crate::gigabyte_z390_aorus::GigabyteZ390Aorus;
+use crate::gigabyte_z490_vision_d::GigabyteZ490VisionD;
+use crate::gskill_trident_z::GskillTridentZ;
+use crate::hyperx_fury::HyperxFury;
+use crate::msi_mystic_light::MsiMysticLight;
+use crate::named_colors::NAMED_COLORS;
+use crate::nzxt_hue2::NzxtHue2;
+use crate::nzxt_smart_device_v2::NzxtSmartDeviceV2;
+use crate::razer_argb::RazerArgb;
+use crate::sapphire_nitro_glow::SapphireNitroGlow;
+use crate::thermaltake_riing::ThermaltakeRiing;
+
+mod amd_wraith_prism;
+mod animation;
+mod asrock_polychrome;
+mod asus_aura_dram;
+mod asus_aura_gpu;
+mod asus_aura_smbus;
+mod asus_crosshair_x670e_extreme;
+mod asus_crosshair_x670e_hero;
+mod asus_strix_x670e_e;
 mod asus_strix_x670e_f;
+mod asus_strix_z790;
+#[cfg(feature = "audio")]
+mod audio;
+mod calibration;
+mod check;
+mod condition;
+mod config_file;
 mod controller;
+mod corsair_capellix;
+mod corsair_lighting_node;
+mod corsair_vengeance_pro;
+mod crucial_ballistix;
+mod daemon;
+mod deepcool_hub;
+mod definition;
+mod dry_run;
+mod effect;
+mod evga_gpu;
+mod gamma;
+mod gigabyte_aorus_gpu;
+mod gigabyte_b550_aorus_pro;
+mod gigabyte_it5711;
+mod gigabyte_it8297;
 mod gigabyte_trx40_aorus_master;
+mod gigabyte_x570_aorus_elite;
+mod gigabyte_z390_aorus;
+mod gigabyte_z490_vision_d;
+mod gskill_trident_z;
+mod http;
+mod hyperx_fury;
+mod leds;
+mod mqtt;
+mod msi_mystic_light;
+mod named_colors;
+mod nzxt_hue2;
+mod nzxt_smart_device_v2;
+mod openrgb;
+mod output;
+mod permissions;
+mod polkit;
+mod profile;
+mod raw;
+mod razer_argb;
+mod reactive;
+mod sapphire_nitro_glow;
+mod schedule;
+mod screen;
+mod service;
+mod smbus;
+mod state;
+mod status;
+mod strip;
+mod theme;
+mod thermaltake_riing;
+mod transcript;
+mod transition;
+mod tui;
+mod watch;
+mod zone_defaults;
+mod zone_map;
+
+/// Suggested fix for HID devices which can't be opened.
+const PERMISSIONS_HINT: &str = "see `rgbfusion setup-permissions` for running without root";
 
 /// Colors used to test the available zones.
 const TESTCOLORS: [Rgb; 6] = [
@@ -32,7 +148,8 @@ const TESTCOLORS: [Rgb; 6] = [
 ];
 
 /// RGB zone.
-#[derive(ValueEnum, Default, Debug, Copy, Clone)]
+#[derive(ValueEnum, Serialize, Deserialize, Default, PartialEq, Eq, Debug, Copy, Clone)]
+#[serde(rename_all = "kebab-case")]
 enum Zone {
     #[default]
     Io,
@@ -44,7 +161,8 @@ enum Zone {
 }
 
 /// Color effect.
-#[derive(ValueEnum, Default, PartialEq, Eq, Debug, Copy, Clone)]
+#[derive(ValueEnum, Serialize, Deserialize, Default, PartialEq, Eq, Debug, Copy, Clone)]
+#[serde(rename_all = "kebab-case")]
 enum Effect {
     Off,
     #[default]
@@ -57,12 +175,53 @@ enum Effect {
     Chase,
 }
 
+/// Effect speed preset.
+#[derive(ValueEnum, PartialEq, Eq, Debug, Copy, Clone)]
+enum Speed {
+    Slow,
+    Medium,
+    Fast,
+    Turbo,
+}
+
 /// Supported RGB controllers.
 #[derive(ValueEnum, Default, PartialEq, Eq, Debug, Copy, Clone)]
 enum RgbDevice {
     #[default]
     X670EF,
+    X670EE,
+    X670EHero,
+    X670EExtreme,
+    Z790,
+    AuraSmbus,
+    AuraDram,
+    AuraGpu,
+    VengeancePro,
+    TridentZ,
+    Fury,
+    Ballistix,
+    EvgaGpu,
+    NitroGlow,
     Trx40,
+    Z390,
+    B550,
+    X570,
+    Z490,
+    /// Z690 and Z790 boards with the IT5711 controller.
+    It5711,
+    AorusGpu,
+    WraithPrism,
+    MysticLight,
+    Polychrome,
+    RazerArgb,
+    LightingNode,
+    Hue2,
+    SmartDeviceV2,
+    Riing,
+    Deepcool,
+    Capellix,
+    /// Generic controller for boards without a dedicated implementation.
+    It8297,
 }
 
 impl RgbDevice {
@@ -71,39 +230,416 @@ impl RgbDevice {
         match self {
             Self::Trx40 => Box::new(GigabyteTrx40AorusMaster),
             Self::X670EF => Box::new(AsusRogStrixX670EF),
+            Self::X670EE => Box::new(AsusRogStrixX670EE),
+            Self::X670EHero => Box::new(AsusRogCrosshairX670EHero),
+            Self::X670EExtreme => Box::new(AsusRogCrosshairX670EExtreme),
+            Self::Z790 => Box::new(AsusRogStrixZ790),
+            Self::AuraSmbus => Box::new(AsusAuraSmbus),
+            Self::AuraDram => Box::new(AsusAuraDram),
+            Self::AuraGpu => Box::new(AsusAuraGpu),
+            Self::VengeancePro => Box::new(CorsairVengeancePro),
+            Self::TridentZ => Box::new(GskillTridentZ),
+            Self::Fury => Box::new(HyperxFury),
+            Self::Ballistix => Box::new(CrucialBallistix),
+            Self::EvgaGpu => Box::new(EvgaGpu),
+            Self::NitroGlow => Box::new(SapphireNitroGlow),
+            Self::Z390 => Box::new(GigabyteZ390Aorus),
+            Self::B550 => Box::new(GigabyteB550AorusPro),
+            Self::X570 => Box::new(GigabyteX570AorusElite),
+            Self::Z490 => Box::new(GigabyteZ490VisionD),
+            Self::It5711 => Box::new(GigabyteIt5711),
+            Self::AorusGpu => Box::new(GigabyteAorusGpu),
+            Self::WraithPrism => Box::new(AmdWraithPrism),
+            Self::MysticLight => Box::new(MsiMysticLight),
+            Self::Polychrome => Box::new(AsrockPolychrome),
+            Self::RazerArgb => Box::new(RazerArgb),
+            Self::LightingNode => Box::new(CorsairLightingNode),
+            Self::Hue2 => Box::new(NzxtHue2),
+            Self::SmartDeviceV2 => Box::new(NzxtSmartDeviceV2),
+            Self::Riing => Box::new(ThermaltakeRiing),
+            Self::Deepcool => Box::new(DeepcoolHub),
+            Self::Capellix => Box::new(CorsairCapellix),
+            Self::It8297 => Box::new(GigabyteIt8297),
         }
     }
 }
 
+/// RGB device selected by the user.
+#[derive(PartialEq, Eq, Copy, Clone)]
+enum Device {
+    /// Controller built into rgbfusion.
+    Builtin(RgbDevice),
+    /// Controller loaded from a definition file.
+    Custom(&'static ControllerDefinition),
+}
+
+impl Device {
+    /// Get all builtin and user-defined devices.
+    fn all() -> Vec<Self> {
+        let builtin = RgbDevice::value_variants().iter().map(|device| Self::Builtin(*device));
+        let custom = definition::definitions().iter().map(Self::Custom);
+        builtin.chain(custom).collect()
+    }
+
+    /// Get RGB controller for a device.
+    fn controller(&self) -> Box<dyn HidController> {
+        let controller = match self {
+            Self::Builtin(device) => device.controller(),
+            Self::Custom(definition) => Box::new(*definition),
+        };
+
+        // Only the selected device uses the overridden IDs, so detection of
+        // other devices is unaffected.
+        let options = hid_options();
+        let overridden = options.vendor_id.is_some() || options.product_id.is_some();
+        if !overridden || options.overridden_device != Some(*self) {
+            return controller;
+        }
+
+        // SMBus controllers have no HID IDs to override.
+        let vendor_id = options.vendor_id.or(controller.vendor_id());
+        let product_id = options.product_id.or(controller.product_id());
+        match (vendor_id, product_id) {
+            (Some(vendor_id), Some(product_id)) => {
+                Box::new(IdOverride { controller, vendor_id, product_id })
+            },
+            _ => controller,
+        }
+    }
+
+    /// Value used for selecting the device on the CLI.
+    fn possible_value(&self) -> PossibleValue {
+        match self {
+            Self::Builtin(device) => device.to_possible_value().unwrap(),
+            Self::Custom(definition) => PossibleValue::new(definition.id.as_str()),
+        }
+    }
+
+    /// Find all supported devices connected to the HID bus.
+    fn detect(api: &HidApi) -> Vec<Self> {
+        let mut devices = Vec::new();
+        for info in api.device_list().filter(|info| hid_options().selector.matches(info)) {
+            if let Some(device) = Self::from_info(info).filter(|device| !devices.contains(device)) {
+                devices.push(device);
+            }
+        }
+        devices
+    }
+
+    /// Find the supported device for a HID bus entry.
+    ///
+    /// Controllers matching the DMI board name take precedence over generic
+    /// controllers sharing their IDs.
+    fn from_info(info: &DeviceInfo) -> Option<Self> {
+        let devices = Self::all().into_iter().filter(|device| device.matches(info));
+        devices.min_by_key(|device| device.controller().board_name().is_none())
+    }
+
+    /// Check if a HID bus entry belongs to this device.
+    fn matches(&self, info: &DeviceInfo) -> bool {
+        let controller = self.controller();
+        let board_matches = match (controller.board_name(), board_name()) {
+            (Some(expected), Some(board_name)) => board_name.starts_with(expected),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+
+        board_matches
+            && controller.vendor_id() == Some(info.vendor_id())
+            && controller.product_ids().contains(&info.product_id())
+    }
+
+    /// Check if a HID bus entry shares its vendor with a supported device.
+    fn similar(info: &DeviceInfo) -> bool {
+        Self::all().iter().any(|device| device.controller().vendor_id() == Some(info.vendor_id()))
+    }
+}
+
+impl Default for Device {
+    fn default() -> Self {
+        Self::Builtin(RgbDevice::default())
+    }
+}
+
+impl From<RgbDevice> for Device {
+    fn from(device: RgbDevice) -> Self {
+        Self::Builtin(device)
+    }
+}
+
+impl FromStr for Device {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .into_iter()
+            .find(|device| device.possible_value().matches(s, true))
+            .ok_or_else(|| format!("unknown device: {s}"))
+    }
+}
+
+impl Serialize for Device {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.possible_value().get_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Device {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Device::from_str(&name).map_err(D::Error::custom)
+    }
+}
+
+impl Debug for Device {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.possible_value().get_name())
+    }
+}
+
+/// HID bus entry selected by the user.
+///
+/// This allows choosing between multiple identical controllers connected at
+/// the same time.
+#[derive(Default, PartialEq, Eq, Debug, Clone)]
+enum HidSelector {
+    /// First entry matching the controller's vendor and product ID.
+    #[default]
+    Any,
+    /// Entry with a specific HID path.
+    Path(CString),
+    /// Entry with a specific serial number.
+    Serial(String),
+}
+
+impl HidSelector {
+    /// Read the HID selector from the CLI.
+    fn from_cli(matches: &ArgMatches) -> Result<Self, Box<dyn Error>> {
+        if let Some(path) = matches.get_one::<String>("hid-path") {
+            let path = CString::new(path.as_str()).map_err(|_| "invalid HID path")?;
+            return Ok(Self::Path(path));
+        }
+
+        match matches.get_one::<String>("serial") {
+            Some(serial) => Ok(Self::Serial(serial.clone())),
+            None => Ok(Self::Any),
+        }
+    }
+
+    /// Open the selected HID device with a vendor and product ID.
+    fn open(
+        &self,
+        api: &HidApi,
+        vendor_id: u16,
+        product_id: u16,
+    ) -> Result<HidDevice, Box<dyn Error>> {
+        let device = match self {
+            Self::Any => api.open(vendor_id, product_id)?,
+            Self::Serial(serial) => api.open_serial(vendor_id, product_id, serial)?,
+            Self::Path(path) => {
+                // Avoid writing to unrelated devices.
+                let is_controller = api.device_list().any(|info| {
+                    self.matches(info)
+                        && info.vendor_id() == vendor_id
+                        && info.product_id() == product_id
+                });
+                if !is_controller {
+                    let ids = format!("0x{vendor_id:04x}/0x{product_id:04x}");
+                    return Err(format!("no device {ids} at {path:?}").into());
+                }
+
+                api.open_path(path)?
+            },
+        };
+
+        // Never block on reads, so only the kernel's transfer timeouts apply.
+        device.set_blocking_mode(false)?;
+
+        Ok(device)
+    }
+
+    /// Check if a HID bus entry is selected.
+    fn matches(&self, info: &DeviceInfo) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Path(path) => info.path() == path.as_c_str(),
+            Self::Serial(serial) => info.serial_number() == Some(serial.as_str()),
+        }
+    }
+}
+
+/// HID access options passed on the CLI.
+#[derive(Default, Debug)]
+struct HidOptions {
+    selector: HidSelector,
+
+    /// Vendor ID override for untested boards.
+    vendor_id: Option<u16>,
+
+    /// Product ID override for untested boards.
+    product_id: Option<u16>,
+
+    /// Device using the overridden IDs.
+    overridden_device: Option<Device>,
+
+    /// Number of times a failed packet write is retried.
+    retries: u32,
+
+    /// Delay before the first retry, doubled after every attempt.
+    retry_delay: std::time::Duration,
+
+    /// Print packets instead of writing them.
+    dry_run: bool,
+
+    /// Transcript file recording all written packets.
+    record: Option<PathBuf>,
+
+    /// Maximum duration of a single HID operation.
+    timeout: Option<std::time::Duration>,
+}
+
+impl HidOptions {
+    /// Read the HID options from the CLI.
+    fn from_cli(matches: &ArgMatches) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            selector: HidSelector::from_cli(matches)?,
+            vendor_id: matches.get_one::<u16>("vid").copied(),
+            product_id: matches.get_one::<u16>("pid").copied(),
+            overridden_device: matches.get_one::<Device>("device").copied(),
+            retries: matches.get_one::<u32>("retries").copied().unwrap_or_default(),
+            retry_delay: matches
+                .get_one::<u64>("retry-delay")
+                .map(|ms| std::time::Duration::from_millis(*ms))
+                .unwrap_or_default(),
+            dry_run: matches.get_flag("dry-run"),
+            record: matches.get_one::<PathBuf>("record").cloned(),
+            timeout: matches
+                .get_one::<u64>("timeout")
+                .filter(|ms| **ms > 0)
+                .map(|ms| std::time::Duration::from_millis(*ms)),
+        })
+    }
+}
+
+/// HID options passed on the CLI.
+static HID_OPTIONS: OnceLock<HidOptions> = OnceLock::new();
+
+/// Get the HID options passed on the CLI.
+fn hid_options() -> &'static HidOptions {
+    HID_OPTIONS.get_or_init(HidOptions::default)
+}
+
 /// RGB color.
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, PartialEq, Eq, Debug, Copy, Clone)]
 struct Rgb {
     r: u8,
     g: u8,
     b: u8,
 }
 
-impl FromStr for Rgb {
-    type Err = ();
+impl Rgb {
+    /// Dim the color to a brightness.
+    fn scale(self, brightness: Brightness) -> Rgb {
+        let channel = |value: u8| (value as u16 * brightness.0 as u16 / u8::MAX as u16) as u8;
+        Rgb { r: channel(self.r), g: channel(self.g), b: channel(self.b) }
+    }
 
-    fn from_str(s: &str) -> Result<Rgb, ()> {
-        let chars = if s.starts_with("0x") && s.len() == 8 {
-            &s[2..]
-        } else {
-            return Err(());
+    /// Linear interpolation towards another color.
+    fn interpolate(self, to: Rgb, position: f32) -> Rgb {
+        let channel =
+            |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * position).round() as u8;
+        Rgb { r: channel(self.r, to.r), g: channel(self.g, to.g), b: channel(self.b, to.b) }
+    }
+}
+
+impl Rgb {
+    /// Create a color from its `0xRRGGBB` integer representation.
+    const fn from_hex(color: u32) -> Rgb {
+        Rgb { r: (color >> 16) as u8, g: (color >> 8) as u8, b: color as u8 }
+    }
+
+    /// Convert from HSV, with the hue in degrees and the other components in
+    /// the range `0..=1`.
+    fn from_hsv(hue: f32, saturation: f32, value: f32) -> Rgb {
+        let chroma = value * saturation;
+        Self::from_hue(hue, chroma, value - chroma)
+    }
+
+    /// Convert from HSL, with the hue in degrees and the other components in
+    /// the range `0..=1`.
+    fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Rgb {
+        let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+        Self::from_hue(hue, chroma, lightness - chroma / 2.)
+    }
+
+    /// Create a color from its hue, chroma and the minimum channel value.
+    fn from_hue(hue: f32, chroma: f32, min: f32) -> Rgb {
+        let hue = hue.rem_euclid(360.) / 60.;
+        let x = chroma * (1. - (hue % 2. - 1.).abs());
+
+        let (r, g, b) = match hue as u8 {
+            0 => (chroma, x, 0.),
+            1 => (x, chroma, 0.),
+            2 => (0., chroma, x),
+            3 => (0., x, chroma),
+            4 => (x, 0., chroma),
+            _ => (chroma, 0., x),
         };
 
-        match u32::from_str_radix(chars, 16) {
-            Ok(mut color) => {
-                let b = (color & 0xff) as u8;
-                color >>= 8;
-                let g = (color & 0xff) as u8;
-                color >>= 8;
-                let r = color as u8;
-                Ok(Rgb { r, g, b })
+        let channel = |value: f32| ((value + min) * 255.).round() as u8;
+        Rgb { r: channel(r), g: channel(g), b: channel(b) }
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Rgb, String> {
+        // Resolve user-defined colors.
+        if let Some(name) = s.strip_prefix('@') {
+            return theme::color(name);
+        }
+
+        let prefixed = s.strip_prefix("0x").or_else(|| s.strip_prefix('#'));
+        let chars = prefixed.unwrap_or(s);
+        let is_hex = chars.chars().all(|c| c.is_ascii_hexdigit());
+
+        // Parse `0xRRGGBB`, `#RRGGBB` and `RRGGBB`, with optional `RGB` shorthand.
+        match chars.len() {
+            6 if is_hex => return Ok(Rgb::from_hex(u32::from_str_radix(chars, 16).unwrap())),
+            3 if is_hex => {
+                let expanded: String = chars.chars().flat_map(|c| [c, c]).collect();
+                return Ok(Rgb::from_hex(u32::from_str_radix(&expanded, 16).unwrap()));
             },
-            Err(_) => Err(()),
+            _ if prefixed.is_some() => {
+                return Err(format!("color '{s}' does not match format 0xRRGGBB or #RGB"));
+            },
+            _ => (),
         }
+
+        // Match CSS color names, ignoring case and word separators.
+        let name: String = s
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .flat_map(char::to_lowercase)
+            .collect();
+        match NAMED_COLORS.binary_search_by_key(&name.as_str(), |(name, _)| name) {
+            Ok(index) => Ok(Rgb::from_hex(NAMED_COLORS[index].1)),
+            Err(_) => Err(format!("unknown color '{s}', expected 0xRRGGBB or a color name")),
+        }
+    }
+}
+
+impl Serialize for Rgb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let color = String::deserialize(deserializer)?;
+        Rgb::from_str(&color).map_err(D::Error::custom)
     }
 }
 
@@ -113,21 +649,123 @@ impl Display for Rgb {
     }
 }
 
+/// Color specified in the HSV color space.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+struct Hsv(Rgb);
+
+impl FromStr for Hsv {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hue, saturation, value) = hue_components(s, "H,S,V")?;
+        Ok(Self(Rgb::from_hsv(hue, saturation, value)))
+    }
+}
+
+/// Color specified in the HSL color space.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+struct Hsl(Rgb);
+
+impl FromStr for Hsl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hue, saturation, lightness) = hue_components(s, "H,S,L")?;
+        Ok(Self(Rgb::from_hsl(hue, saturation, lightness)))
+    }
+}
+
+/// Parse the hue in degrees and two percentages of an HSV or HSL color.
+fn hue_components(s: &str, format: &str) -> Result<(f32, f32, f32), String> {
+    let error = || format!("'{s}' does not match format {format} [0..=360,0..=100,0..=100]");
+
+    let components: Vec<_> = s.split(',').map(|value| f32::from_str(value.trim())).collect();
+    let percent = |value: f32| (0.0..=100.).contains(&value).then_some(value / 100.);
+    match components[..] {
+        [Ok(hue), Ok(saturation), Ok(other)] if (0.0..=360.).contains(&hue) => {
+            Ok((hue, percent(saturation).ok_or_else(error)?, percent(other).ok_or_else(error)?))
+        },
+        _ => Err(error()),
+    }
+}
+
+/// Color ramp spread across multiple zones.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+struct Gradient {
+    from: Rgb,
+    to: Rgb,
+}
+
+impl FromStr for Gradient {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s
+            .split_once("..")
+            .ok_or_else(|| format!("gradient '{s}' does not match format COLOR..COLOR"))?;
+        Ok(Self { from: Rgb::from_str(from)?, to: Rgb::from_str(to)? })
+    }
+}
+
+/// Colors cycled through in software.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct Palette(Vec<Rgb>);
+
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut colors = Vec::new();
+        for color in s.split(',').map(str::trim) {
+            // Expand user-defined palettes.
+            match color.strip_prefix('@') {
+                Some(name) => colors.extend_from_slice(theme::colors(name)?),
+                None => colors.push(Rgb::from_str(color)?),
+            }
+        }
+        Ok(Self(colors))
+    }
+}
+
+impl Display for Palette {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let colors: Vec<_> = self.0.iter().map(Rgb::to_string).collect();
+        f.write_str(&colors.join(","))
+    }
+}
+
 /// LED brightness.
-#[derive(Default, PartialEq, Eq, Copy, Clone)]
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug, Copy, Clone)]
 struct Brightness(u8);
 
 impl Brightness {
     const fn max_value() -> Self {
-        Self(u8::max_value())
+        Self(u8::MAX)
+    }
+
+    /// Linear interpolation towards another brightness.
+    fn interpolate(self, to: Brightness, position: f32) -> Brightness {
+        Brightness((self.0 as f32 + (to.0 as f32 - self.0 as f32) * position).round() as u8)
     }
 }
 
 impl FromStr for Brightness {
-    type Err = ParseIntError;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Brightness(u8::from_str(s)?))
+        // Convert percentages to the range 0..=255.
+        if let Some(percent) = s.strip_suffix('%') {
+            return match f32::from_str(percent) {
+                Ok(percent) if (0.0..=100.).contains(&percent) => {
+                    Ok(Brightness((percent * 2.55).round() as u8))
+                },
+                _ => Err(format!("brightness '{s}' is not in the range 0%..=100%")),
+            };
+        }
+
+        u8::from_str(s)
+            .map(Brightness)
+            .map_err(|_| format!("brightness '{s}' is not in the range 0..=255"))
     }
 }
 
@@ -138,7 +776,7 @@ impl Display for Brightness {
 }
 
 /// Duration in milliseconds.
-#[derive(PartialEq, Eq, Copy, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
 struct Duration(u16);
 
 impl Default for Duration {
@@ -162,47 +800,201 @@ impl Display for Duration {
 }
 
 /// New color config.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
 struct Config {
-    device: RgbDevice,
+    #[serde(skip)]
+    device: Device,
     zone: Zone,
     effect: Effect,
     max_brightness: Brightness,
     min_brightness: Brightness,
     color: Rgb,
+    color2: Rgb,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    palette: Vec<Rgb>,
     fade_in_time: Duration,
     fade_out_time: Duration,
     hold_time: Duration,
+    #[serde(skip)]
     interactive: bool,
 }
 
 impl Config {
-    fn from_cli(matches: &ArgMatches) -> Self {
-        let mut config = Config::default();
+    /// Create one config for every zone passed on the CLI.
+    fn from_cli(matches: &ArgMatches) -> Result<Vec<Self>, Box<dyn Error>> {
+        let device = required_device(matches)?;
+        ZoneArgs::split(matches).iter().map(|args| Self::from_zone_args(args, device)).collect()
+    }
+
+    fn from_zone_args(args: &ZoneArgs, device: Device) -> Result<Self, Box<dyn Error>> {
+        // Only offer zones and effects supported by the device.
+        let controller = device.controller();
+        let zone = required_zone(args, device)?;
+        let effect = *required_enum(args, "effect", &controller.available_effects());
+
+        // Determine if some parameters were read from STDIN.
+        let color_given = ["color", "hsv", "hsl", "palette"].iter().any(|name| args.contains(name));
+        let interactive = !args.contains("zone")
+            || !args.contains("effect")
+            || (!color_given && effect != Effect::Off);
+
+        let mut config = Config { device, zone, effect, interactive, ..Default::default() };
+        zone_defaults::apply(&mut config);
+        config.replace_from_cli(args);
+        controller.validate(&config)?;
+
+        // Palettes replace the primary color.
+        if config.effect != Effect::Off && config.palette.is_empty() {
+            config.color = required_color(args);
+        }
+
+        Ok(config)
+    }
+
+    /// Create configs applying the CLI options to every supported zone.
+    fn sync_from_cli(matches: &ArgMatches) -> Result<Vec<Self>, Box<dyn Error>> {
+        let device = required_device(matches)?;
 
-        // Determine if some parameters need to be read from STDIN.
-        config.interactive = !matches.contains_id("zone")
-            || !matches.contains_id("color")
-            || !matches.contains_id("effect");
+        let args = ZoneArgs::new(matches);
+        let controller = device.controller();
+        let effect = *required_enum(&args, "effect", &controller.available_effects());
 
-        config.device = *required_enum::<RgbDevice>(matches, "device");
-        config.zone = *required_enum::<Zone>(matches, "zone");
-        config.effect = *required_enum::<Effect>(matches, "effect");
+        let mut color = None;
+        let mut configs = Vec::new();
+        for zone in controller.supported_zones() {
+            let mut config = Config { device, zone: *zone, effect, ..Default::default() };
+            zone_defaults::apply(&mut config);
+            config.replace_from_cli(&args);
 
-        if config.effect != Effect::Off {
-            config.color = required_color(matches);
+            // Palettes replace the primary color, which is only read once.
+            if config.effect != Effect::Off && config.palette.is_empty() {
+                config.color = *color.get_or_insert_with(|| required_color(&args));
+            }
+
+            controller.validate(&config)?;
+            configs.push(config);
         }
 
-        config.interactive = !matches.contains_id("zone")
-            || !matches.contains_id("effect")
-            || (!matches.contains_id("color") && config.effect != Effect::Off);
+        Ok(configs)
+    }
+
+    /// Create configs spreading a gradient across all zones in their physical
+    /// order.
+    fn gradient_from_cli(
+        matches: &ArgMatches,
+        gradient: Gradient,
+    ) -> Result<Vec<Self>, Box<dyn Error>> {
+        let device = required_device(matches)?;
+
+        let args = ZoneArgs::new(matches);
+        let controller = device.controller();
+        let effect = *required_enum(&args, "effect", &controller.available_effects());
+        let interactive = !args.contains("effect");
+
+        let zones = controller.physical_zones();
+        let last = zones.len().saturating_sub(1).max(1) as f32;
+
+        let mut configs = Vec::new();
+        for (i, zone) in zones.iter().enumerate() {
+            let color = gradient.from.interpolate(gradient.to, i as f32 / last);
+            let mut config =
+                Config { device, zone: *zone, effect, color, interactive, ..Default::default() };
+            zone_defaults::apply(&mut config);
+            config.replace_from_cli(&args);
+            controller.validate(&config)?;
+            configs.push(config);
+        }
 
-        replace_from_str(&mut config.max_brightness, matches, "max-brightness");
-        replace_from_str(&mut config.min_brightness, matches, "min-brightness");
-        replace_from_str(&mut config.fade_in_time, matches, "fade-in-time");
-        replace_from_str(&mut config.fade_out_time, matches, "fade-out-time");
-        replace_from_str(&mut config.hold_time, matches, "hold-time");
+        Ok(configs)
+    }
 
-        config
+    /// Transform all colors of the config.
+    fn map_colors(&self, f: impl Fn(Rgb) -> Rgb) -> Self {
+        Self {
+            color: f(self.color),
+            color2: f(self.color2),
+            palette: self.palette.iter().copied().map(&f).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Replace the brightness and timing parameters with their CLI values.
+    fn replace_from_cli(&mut self, args: &ZoneArgs) {
+        replace_from_str(&mut self.color2, args, "color2");
+        if let Some(palette) = args.get::<Palette>("palette") {
+            self.palette = palette.0.clone();
+        }
+        replace_from_str(&mut self.max_brightness, args, "max-brightness");
+        replace_from_str(&mut self.min_brightness, args, "min-brightness");
+
+        // Explicit timings take precedence over the speed preset.
+        if let Some(speed) = args.get::<Speed>("speed") {
+            (self.fade_in_time, self.fade_out_time, self.hold_time) =
+                self.device.controller().speed_timings(*speed);
+        }
+        replace_from_str(&mut self.fade_in_time, args, "fade-in-time");
+        replace_from_str(&mut self.fade_out_time, args, "fade-out-time");
+        replace_from_str(&mut self.hold_time, args, "hold-time");
+    }
+}
+
+/// CLI options of a single zone.
+///
+/// The `--zone` option can be repeated to configure multiple zones at once,
+/// with every option applying to the zone preceding it. Options in front of the
+/// first zone are shared by all zones.
+struct ZoneArgs<'a> {
+    matches: &'a ArgMatches,
+    /// Index range of the options belonging to this zone.
+    range: Range<usize>,
+    /// Index of the first zone.
+    first_zone: usize,
+}
+
+impl<'a> ZoneArgs<'a> {
+    /// Use all CLI options for a single zone.
+    fn new(matches: &'a ArgMatches) -> Self {
+        Self { matches, range: 0..usize::MAX, first_zone: 0 }
+    }
+
+    /// Split the CLI options into one group per zone.
+    fn split(matches: &'a ArgMatches) -> Vec<Self> {
+        let indices: Vec<_> = matches.indices_of("zone").into_iter().flatten().collect();
+        if indices.len() <= 1 {
+            return vec![Self::new(matches)];
+        }
+
+        let ends = indices[1..].iter().copied().chain([usize::MAX]);
+        indices
+            .iter()
+            .zip(ends)
+            .map(|(start, end)| Self { matches, range: *start..end, first_zone: indices[0] })
+            .collect()
+    }
+
+    /// Get the last value of an option applying to this zone.
+    fn get<T: Any + Clone + Send + Sync>(&self, name: &str) -> Option<&'a T> {
+        let values = self.matches.get_many::<T>(name)?;
+        let indices = self.matches.indices_of(name)?;
+
+        let mut shared = None;
+        let mut own = None;
+        for (value, index) in values.zip(indices) {
+            if self.range.contains(&index) {
+                own = Some(value);
+            } else if index < self.first_zone {
+                shared = Some(value);
+            }
+        }
+
+        own.or(shared)
+    }
+
+    /// Check if an option applying to this zone is present.
+    fn contains(&self, name: &str) -> bool {
+        let mut indices = self.matches.indices_of(name).into_iter().flatten();
+        indices.any(|index| self.range.contains(&index) || index < self.first_zone)
     }
 }
 
@@ -217,6 +1009,8 @@ impl Default for Config {
             hold_time: Default::default(),
             device: Default::default(),
             effect: Default::default(),
+            color2: Default::default(),
+            palette: Default::default(),
             color: Default::default(),
             zone: Default::default(),
         }
@@ -244,7 +1038,11 @@ impl Display for Config {
             return Ok(());
         }
 
-        write!(f, " \\\n  --color {}", self.color)?;
+        if self.palette.is_empty() {
+            write!(f, " \\\n  --color {}", self.color)?;
+        } else {
+            write!(f, " \\\n  --palette {}", Palette(self.palette.clone()))?;
+        }
 
         if self.max_brightness != Brightness::max_value() {
             write!(f, " \\\n  --max-brightness {}", self.max_brightness)?;
@@ -255,6 +1053,10 @@ impl Display for Config {
             return Ok(());
         }
 
+        if self.color2 != Rgb::default() {
+            write!(f, " \\\n  --color2 {}", self.color2)?;
+        }
+
         if self.min_brightness != Brightness::default() {
             write!(f, " \\\n  --min-brightness {}", self.min_brightness)?;
         }
@@ -277,197 +1079,1100 @@ impl Display for Config {
 
 fn main() {
     let cli = cli();
-    match cli.subcommand_matches("zonetest") {
-        Some(_) => zonetest(&cli),
-        None => rgbfusion(&cli),
+
+    // Global options are only propagated to the innermost subcommand.
+    let mut global = &cli;
+    while let Some((_, subcommand)) = global.subcommand() {
+        global = subcommand;
+    }
+
+    let options = match HidOptions::from_cli(global) {
+        Ok(options) => HID_OPTIONS.get_or_init(|| options),
+        Err(err) => return eprintln!("\x1b[31mError:\x1b[0m {err}"),
+    };
+    output::init(*global.get_one::<output::Format>("output").unwrap());
+
+    let overridden = options.vendor_id.is_some() || options.product_id.is_some();
+    match global.get_one::<Device>("device") {
+        Some(device) if overridden => {
+            let controller = device.controller();
+            let (Some(vendor_id), Some(product_id)) =
+                (controller.vendor_id(), controller.product_id())
+            else {
+                return eprintln!(
+                    "\x1b[31mError:\x1b[0m `--vid` and `--pid` are not supported for SMBus devices"
+                );
+            };
+            eprintln!(
+                "\x1b[33mWarning:\x1b[0m using the {} protocol for untested device \
+                 0x{vendor_id:04x}/0x{product_id:04x}",
+                controller.name(),
+            );
+        },
+        // Raw packets can be sent to arbitrary devices.
+        None if overridden && cli.subcommand_name() != Some("raw") => {
+            return eprintln!("\x1b[31mError:\x1b[0m `--vid` and `--pid` require `--device`");
+        },
+        _ => (),
     }
+
+    match cli.subcommand_name() {
+        Some("zonetest") => zonetest(&cli),
+        Some("list-devices") => list_devices(),
+        Some("daemon") => daemon::daemon(cli.subcommand_matches("daemon").unwrap()),
+        Some("mqtt") => mqtt::mqtt(cli.subcommand_matches("mqtt").unwrap()),
+        Some("profile") => profile::profile(cli.subcommand_matches("profile").unwrap()),
+        Some("animate") => animation::animate(cli.subcommand_matches("animate").unwrap()),
+        Some("schedule") => schedule::schedule(cli.subcommand_matches("schedule").unwrap()),
+        Some("watch") => watch::watch(cli.subcommand_matches("watch").unwrap()),
+        Some("check") => check::check(cli.subcommand_matches("check").unwrap()),
+        Some("sync") => sync(cli.subcommand_matches("sync").unwrap()),
+        Some("off") => off(cli.subcommand_matches("off").unwrap()),
+        Some("undo") => undo(cli.subcommand_matches("undo").unwrap()),
+        Some("reapply") => reapply(cli.subcommand_matches("reapply").unwrap()),
+        Some("status") => status::status(cli.subcommand_matches("status").unwrap()),
+        Some("tui") => tui::tui(cli.subcommand_matches("tui").unwrap()),
+        Some("leds") => leds::leds(cli.subcommand_matches("leds").unwrap()),
+        Some("header") => leds::header(cli.subcommand_matches("header").unwrap()),
+        Some("raw") => raw::raw(cli.subcommand_matches("raw").unwrap()),
+        Some("setup-permissions") => {
+            permissions::setup_permissions(cli.subcommand_matches("setup-permissions").unwrap())
+        },
+        Some("replay") => transcript::replay(cli.subcommand_matches("replay").unwrap()),
+        Some("install-service") => {
+            service::install_service(cli.subcommand_matches("install-service").unwrap())
+        },
+        _ => rgbfusion(&cli),
+    }
+}
+
+/// Print all supported devices connected to the HID bus.
+fn list_devices() {
+    let api = match HidApi::new() {
+        Ok(api) => api,
+        Err(err) => {
+            eprintln!("\x1b[31mError:\x1b[0m unable to access HID: {err}");
+            return;
+        },
+    };
+
+    let mut found = false;
+    for info in api.device_list() {
+        let device = Device::from_info(info);
+        if output::json() && (device.is_some() || Device::similar(info)) {
+            output::print(&json!({
+                "name": match device {
+                    Some(device) => Some(device.controller().name().to_owned()),
+                    None => info.product_string().map(str::to_owned),
+                },
+                "device": device,
+                "tested": device.is_some(),
+                "vendor_id": info.vendor_id(),
+                "product_id": info.product_id(),
+                "path": info.path().to_string_lossy(),
+                "serial": info.serial_number(),
+            }));
+            continue;
+        }
+
+        let name = match device {
+            Some(device) => format!("{} ({device:?})", device.controller().name()),
+            None if Device::similar(info) => {
+                let product = info.product_string().unwrap_or("Unknown product");
+                format!("{product} \x1b[33m(unknown, untested)\x1b[0m")
+            },
+            None => continue,
+        };
+
+        println!("{name}");
+        println!("  VID/PID: 0x{:04x}/0x{:04x}", info.vendor_id(), info.product_id());
+        println!("  Path:    {}", info.path().to_string_lossy());
+        println!("  Serial:  {}", info.serial_number().unwrap_or("-"));
+
+        found = true;
+    }
+
+    if !found && !output::json() {
+        println!("No supported RGB devices found.");
+    }
+}
+
+/// Apply the same config to every zone of the device.
+fn sync(matches: &ArgMatches) {
+    let configs = match Config::sync_from_cli(matches) {
+        Ok(configs) => configs,
+        Err(err) => return eprintln!("\x1b[31mError:\x1b[0m {err}"),
+    };
+
+    apply_configs(&configs, transition_time(matches), matches.get_flag("verify"));
+    forward_openrgb(matches, &configs);
+}
+
+/// Turn off every zone of the selected or all detected devices.
+fn off(matches: &ArgMatches) {
+    let devices = match matches.get_one::<Device>("device") {
+        Some(device) => vec![*device],
+        None => match HidApi::new() {
+            Ok(api) => Device::detect(&api),
+            Err(err) => return eprintln!("\x1b[31mError:\x1b[0m unable to access HID: {err}"),
+        },
+    };
+
+    if devices.is_empty() {
+        eprintln!(
+            "\x1b[31mError:\x1b[0m no supported RGB device found, use `--device` to select one"
+        );
+    }
+
+    for device in devices {
+        let configs: Vec<_> = device
+            .controller()
+            .supported_zones()
+            .iter()
+            .map(|zone| Config { device, zone: *zone, effect: Effect::Off, ..Default::default() })
+            .collect();
+        apply_configs(&configs, transition_time(matches), matches.get_flag("verify"));
+    }
+}
+
+/// Roll back to the configuration before the last change.
+fn undo(matches: &ArgMatches) {
+    let configs = match state::previous() {
+        Ok(configs) => configs,
+        Err(err) => return eprintln!("\x1b[31mError:\x1b[0m {err}"),
+    };
+
+    // Restore the state first, so applying it isn't recorded as new change.
+    if !hid_options().dry_run {
+        if let Err(err) = state::undo() {
+            return eprintln!("\x1b[31mError:\x1b[0m {err}");
+        }
+    }
+
+    apply_configs(&configs, transition_time(matches), matches.get_flag("verify"));
+}
+
+/// Send the last applied configs to their device again.
+fn reapply(matches: &ArgMatches) {
+    let configs = match (state::last(), matches.get_one::<Device>("device")) {
+        (Some((device, _)), Some(selected)) if device != *selected => {
+            return eprintln!("\x1b[31mError:\x1b[0m last configuration belongs to {device:?}");
+        },
+        (Some((_, configs)), _) if !configs.is_empty() => configs,
+        _ => return eprintln!("\x1b[31mError:\x1b[0m no configuration has been applied yet"),
+    };
+
+    apply_configs(&configs, transition_time(matches), matches.get_flag("verify"));
 }
 
 /// Mark all zones in a unique color.
 fn zonetest(matches: &ArgMatches) {
-    println!("Are you sure you want to test the available RGB zones?");
-    println!("\x1b[31mThis will reset your RGB Fusion configuration\x1b[0m.");
-    print!(" [y/N] > ");
-    let _ = io::stdout().flush();
+    let device = match required_device(matches) {
+        Ok(device) => device,
+        Err(err) => {
+            eprintln!("\x1b[31mError:\x1b[0m {err}");
+            return;
+        },
+    };
+
+    // Zonetest doesn't update the state, so it still holds the previous config.
+    let previous = state::load(device);
+
+    output::message("Are you sure you want to test the available RGB zones?");
+    if previous.is_empty() {
+        output::message("\x1b[31mThis will reset your RGB Fusion configuration\x1b[0m.");
+    } else {
+        output::message("Your last applied configuration will be restored afterwards.");
+    }
+    output::prompt(" [y/N] > ");
 
     // Abort unless the user agrees to reset their config.
     if stdin_nextline().to_lowercase() != "y" {
-        println!("Bailing out.");
+        output::message("Bailing out.");
         return;
     }
 
-    let device = required_enum::<RgbDevice>(matches, "device");
+    output::message("\nTesting available RGB zones...\n");
 
-    println!("\nTesting available RGB zones...\n");
-
-    for (i, zone) in Zone::value_variants().iter().enumerate() {
+    let mut tested = Vec::new();
+    for (i, zone) in device.controller().supported_zones().iter().enumerate() {
         let color = TESTCOLORS[i];
 
-        println!("Color for zone {:?}: {}", zone, color);
+        let config = Config { color, device, zone: *zone, ..Default::default() };
+        let result = write_config(&config);
+
+        if output::json() {
+            let error = result.as_ref().err().map(|err| err.to_string());
+            output::print(&json!({ "zone": zone, "color": color, "error": error }));
+        } else {
+            println!("Color for zone {:?}: {}", zone, color);
+            if let Err(err) = &result {
+                eprintln!("Skipping zone: {err}");
+            }
+        }
+
+        if result.is_ok() {
+            tested.push(*zone);
+        }
+    }
+
+    label_zones(device, &tested);
+
+    if previous.is_empty() {
+        return;
+    }
+
+    output::message("\nRestore the previous configuration?");
+    output::prompt(" [Y/n] > ");
+
+    if stdin_nextline().to_lowercase() != "n" {
+        apply_configs(&previous, None, false);
+    }
+}
+
+/// Ask the user to label the zones lit up by zonetest.
+fn label_zones(device: Device, zones: &[Zone]) {
+    if zones.is_empty() {
+        return;
+    }
+
+    output::message("\nDescribe what lit up for each zone to use it as label, or leave it empty.");
 
-        let config = Config { color, device: *device, zone: *zone, ..Default::default() };
+    let mut labels = zone_map::labels(device);
+    let previous = labels.clone();
+    for zone in zones {
+        output::prompt(&format!(" Zone {:?} > ", zone));
 
-        if let Err(err) = write_config(&config) {
-            eprintln!("Skipping zone: {err}");
+        let label = stdin_nextline();
+        if label.is_empty() {
+            continue;
+        } else if Zone::from_str(&label, true).is_ok() {
+            eprintln!("\x1b[33mWarning:\x1b[0m ignoring label '{label}' matching a zone name");
+            continue;
         }
+
+        labels.retain(|_, labeled| labeled != zone);
+        labels.insert(label, *zone);
+    }
+
+    if labels == previous {
+        return;
+    }
+
+    match zone_map::save(device, labels) {
+        Ok(path) => {
+            output::message(&format!("\x1b[32mSaved zone labels to {}.\x1b[0m", path.display()))
+        },
+        Err(err) => eprintln!("\x1b[31mError:\x1b[0m unable to save zone labels: {err}"),
     }
 }
 
 /// Update RGB Fusion 2 configuration.
 fn rgbfusion(matches: &ArgMatches) {
-    let config = Config::from_cli(matches);
+    if let Some(mode) = matches.get_one::<reactive::Mode>("mode") {
+        reactive::run(matches, *mode);
+        return;
+    }
+
+    let configs =
+        match (matches.get_one::<PathBuf>("config"), matches.get_one::<Gradient>("gradient")) {
+            (Some(path), _) => DeviceConfigs::load(path)
+                .and_then(|file| file.configs(matches))
+                .map(|configs| configs.into_iter().flatten().collect()),
+            (None, Some(gradient)) => Config::gradient_from_cli(matches, *gradient),
+            (None, None) => Config::from_cli(matches),
+        };
+
+    let configs = match configs {
+        Ok(configs) => configs,
+        Err(err) => {
+            eprintln!("\x1b[31mError:\x1b[0m {err}");
+            return;
+        },
+    };
 
     // Print CLI example to skip manual configuration.
-    if config.interactive {
-        println!("\x1b[32mConfiguration successful.\x1b[0m\n");
-        println!("To reapply this config, you can run the following command:\n\n{}\n", config);
+    for config in configs.iter().filter(|config| config.interactive) {
+        output::message("\x1b[32mConfiguration successful.\x1b[0m\n");
+        output::message(&format!(
+            "To reapply this config, you can run the following command:\n\n{}\n",
+            config
+        ));
     }
 
-    match write_config(&config) {
-        Ok(()) => println!("\x1b[32mSuccessfully applied changes.\x1b[0m"),
-        Err(err) => eprintln!("\x1b[31mError:\x1b[0m {err:?}"),
+    apply_configs(&configs, transition_time(matches), matches.get_flag("verify"));
+    forward_openrgb(matches, &configs);
+}
+
+/// Get the transition time from the CLI.
+fn transition_time(matches: &ArgMatches) -> Option<std::time::Duration> {
+    matches.get_one::<u64>("transition").map(|ms| std::time::Duration::from_millis(*ms))
+}
+
+/// Forward the configs to an OpenRGB server, if requested.
+///
+/// OpenRGB devices don't share rgbfusion's zones, so all configs must use the
+/// same color and effect.
+fn forward_openrgb(matches: &ArgMatches, configs: &[Config]) {
+    let (address, config) = match (matches.get_one::<String>("openrgb-forward"), configs.first()) {
+        (Some(address), Some(config)) => (address, config),
+        _ => return,
+    };
+
+    let differs = |other: &Config| other.effect != config.effect || other.color != config.color;
+    if configs.iter().any(differs) {
+        return eprintln!(
+            "\x1b[31mError:\x1b[0m unable to forward to OpenRGB: zones with different colors or \
+             effects can't be forwarded"
+        );
+    }
+
+    match openrgb::forward(address, config) {
+        Ok(names) => {
+            for name in names {
+                println!("\x1b[32mForwarded to OpenRGB device {name}.\x1b[0m");
+            }
+        },
+        Err(err) => eprintln!("\x1b[31mError:\x1b[0m unable to forward to OpenRGB: {err}"),
+    }
+}
+
+/// Write configs to the HID bus, reporting the result of each.
+///
+/// Without a running daemon, emulated effects are rendered until the process is
+/// killed.
+///
+/// Returns `false` if any config could not be written.
+fn apply_configs(
+    configs: &[Config],
+    transition: Option<std::time::Duration>,
+    verify: bool,
+) -> bool {
+    if hid_options().dry_run {
+        dry_run::print(configs);
+        return true;
+    }
+
+    let written = write_configs(&mut reactive::Writer::default(), configs, transition, verify);
+
+    if !written.emulated.is_empty() {
+        if let Err(err) = effect::run(&written.emulated) {
+            eprintln!("\x1b[31mError:\x1b[0m {err}");
+        }
+    }
+
+    !written.failed
+}
+
+/// Outcome of writing configs to the HID bus.
+struct Written {
+    /// Configs emulated in software, which were not written.
+    emulated: Vec<Config>,
+    /// Whether writing any config failed.
+    failed: bool,
+}
+
+/// Write configs to the HID bus, reporting the result of each.
+///
+/// The device is only opened once for all configs. With a transition time, the
+/// zones fade from their last applied state first. Without a running daemon,
+/// emulated effects are skipped and returned instead.
+fn write_configs(
+    writer: &mut reactive::Writer,
+    configs: &[Config],
+    transition: Option<std::time::Duration>,
+    verify: bool,
+) -> Written {
+    let daemon_running = daemon::is_running();
+
+    if let Some(duration) = transition {
+        if let Err(err) = transition::fade(writer, configs, duration) {
+            eprintln!("\x1b[31mError:\x1b[0m transition failed: {err}");
+        }
+    }
+
+    let mut applied = Vec::new();
+    let mut emulated = Vec::new();
+    for config in configs {
+        if !daemon_running && effect::is_emulated(config) {
+            if output::json() {
+                output::print(&output::ConfigResult::emulated(config));
+            }
+            emulated.push(config.clone());
+            continue;
+        }
+
+        let result = writer.write(config);
+        let verification = match result {
+            Ok(()) if verify => writer.verify(config),
+            _ => None,
+        };
+
+        if output::json() {
+            output::print(&output::ConfigResult::new(config, &result, &verification));
+        } else {
+            match &result {
+                Ok(()) => {
+                    println!("\x1b[32mSuccessfully applied changes.\x1b[0m");
+                    if verify {
+                        report_verification(config, verification);
+                    }
+                },
+                Err(err) => eprintln!("\x1b[31mError:\x1b[0m {err:?}"),
+            }
+        }
+
+        if result.is_ok() {
+            applied.push(config.clone());
+        }
+    }
+
+    // Persist the written configs once per device, rather than with every write.
+    let mut devices = Vec::new();
+    for config in &applied {
+        if !devices.contains(&config.device) {
+            devices.push(config.device);
+        }
+    }
+    for device in devices {
+        if let Err(err) = writer.commit(device) {
+            eprintln!("\x1b[31mError:\x1b[0m {err}");
+        }
+    }
+
+    let failed = applied.len() + emulated.len() < configs.len();
+
+    applied.extend_from_slice(&emulated);
+    if let Err(err) = state::record(&applied) {
+        eprintln!("\x1b[31mError:\x1b[0m unable to store applied state: {err}");
+    }
+
+    Written { emulated, failed }
+}
+
+/// Report whether the device has applied a written config.
+fn report_verification(config: &Config, verification: Option<Result<(), Box<dyn Error>>>) {
+    match verification {
+        Some(Ok(())) => println!("\x1b[32mVerified applied changes.\x1b[0m"),
+        Some(Err(err)) => eprintln!("\x1b[31mError:\x1b[0m verification failed: {err}"),
+        None => eprintln!(
+            "\x1b[33mWarning:\x1b[0m {} can't read back the applied changes",
+            config.device.controller().name()
+        ),
+    }
+}
+
+/// Write a config to the HID bus, using the daemon if it is running.
+fn apply_config(config: &Config) -> Result<(), Box<dyn Error>> {
+    match daemon::apply(config) {
+        Some(result) => result,
+        None => write_config(config),
     }
 }
 
 /// Write a config to the HID bus.
 fn write_config(config: &Config) -> Result<(), Box<dyn Error>> {
-    let controller = config.device.controller();
+    config.device.controller().validate(config)?;
 
     let api = HidApi::new().expect("unable to access HID");
-    let device = match api.open(controller.vendor_id(), controller.product_id()) {
-        Ok(device) => device,
-        Err(err) => {
-            return Err(format!("unable to open device: {} (root permissions required)", err).into())
-        },
-    };
+    let device = open_device(&api, config.device)?;
 
-    // Get all byte packets required to apply a configuration.
-    let bytes = controller.config_bytes(&config)?;
+    write_device(&device, config)?;
+    commit_device(&device, config.device)
+}
 
-    for packet in bytes {
-        if let Err(err) = device.write(&packet) {
+/// Open the device of an RGB controller.
+fn open_device(api: &HidApi, device: Device) -> Result<DeviceHandle, Box<dyn Error>> {
+    let controller = device.controller();
+    match controller.open(api, &hid_options().selector) {
+        Ok(device) => Ok(device),
+        Err(err) => Err(format!("unable to open device: {} ({PERMISSIONS_HINT})", err).into()),
+    }
+}
+
+/// Write a config to an open device.
+fn write_device(device: &DeviceHandle, config: &Config) -> Result<(), Box<dyn Error>> {
+    for packet in config_packets(config)? {
+        if let Err(err) = send_packet(device, &packet) {
             return Err(format!("unable to write new config: {}", err).into());
         }
+
+        transcript::record(config.device, &packet)?;
+    }
+
+    Ok(())
+}
+
+/// Persist the configs written to an open device across reboots.
+fn commit_device(device: &DeviceHandle, kind: Device) -> Result<(), Box<dyn Error>> {
+    for packet in kind.controller().commit_bytes() {
+        if let Err(err) = send_packet(device, &packet) {
+            return Err(format!("unable to persist config: {}", err).into());
+        }
+
+        transcript::record(kind, &packet)?;
     }
 
     Ok(())
 }
 
+/// Get all packets required to apply a config.
+fn config_packets(config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+    let controller = config.device.controller();
+    controller.validate(config)?;
+
+    if effect::is_emulated(config) {
+        return Err(format!(
+            "{:?} is emulated in software for {}, which requires `rgbfusion daemon`",
+            config.effect,
+            controller.name()
+        )
+        .into());
+    }
+
+    controller.config_bytes(&corrected_config(config))
+}
+
+/// Send a packet to a device, retrying with exponential backoff.
+fn send_packet(device: &DeviceHandle, packet: &Packet) -> Result<(), Box<dyn Error>> {
+    let options = hid_options();
+    let mut delay = options.retry_delay;
+
+    let send = || send_with_timeout(device, packet);
+
+    let mut result = send();
+    for _ in 0..options.retries {
+        if result.is_ok() {
+            break;
+        }
+
+        thread::sleep(delay);
+        delay *= 2;
+
+        result = send();
+    }
+
+    result
+}
+
+/// Run a HID operation on a worker thread, failing if it doesn't finish in
+/// time.
+///
+/// A timed out operation keeps blocking its thread while holding the device's
+/// lock, so later operations on the same device time out as well.
+fn with_timeout<T, E>(
+    operation: &str,
+    f: impl FnOnce() -> Result<T, E> + Send + 'static,
+) -> Result<T, Box<dyn Error>>
+where
+    T: Send + 'static,
+    E: Into<Box<dyn Error>>,
+{
+    let timeout = match hid_options().timeout {
+        Some(timeout) => timeout,
+        None => return f().map_err(Into::into),
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(f().map_err(|err| err.into().to_string()));
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result.map_err(Into::into),
+        Err(RecvTimeoutError::Timeout) => {
+            Err(format!("{operation} timed out after {}ms", timeout.as_millis()).into())
+        },
+        Err(RecvTimeoutError::Disconnected) => Err(format!("{operation} failed").into()),
+    }
+}
+
+/// Send a packet to a device, failing if it doesn't finish in time.
+fn send_with_timeout(device: &DeviceHandle, packet: &Packet) -> Result<(), Box<dyn Error>> {
+    match device {
+        DeviceHandle::Hid(hid) => {
+            let (hid, packet) = (hid.clone(), packet.clone());
+            with_timeout("writing packet", move || packet.send(&DeviceHandle::Hid(hid)))
+        },
+        // SMBus transfers are bounded by the adapter's own timeout.
+        DeviceHandle::Smbus(_) => packet.send(device),
+    }
+}
+
+/// Read the applied config back from an open device.
+///
+/// Returns `None` if the controller can't report its state.
+fn verify_device(device: &DeviceHandle, config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+    let device = match device {
+        DeviceHandle::Hid(device) => device.clone(),
+        DeviceHandle::Smbus(_) => return None,
+    };
+
+    let config = corrected_config(config);
+    let verified = with_timeout("reading device state", move || {
+        config.device.controller().verify(&device.lock().unwrap(), &config).transpose()
+    });
+    verified.transpose()
+}
+
+/// Apply color corrections to a config before it is sent to its device.
+fn corrected_config(config: &Config) -> Config {
+    let mut config = calibration::correct(&gamma::correct(config));
+
+    // Emulate brightness for controllers without a brightness setting.
+    if !config.device.controller().supports_brightness() {
+        config = config.map_colors(|color| color.scale(config.max_brightness));
+    }
+
+    config
+}
+
 /// Get clap CLI parameters.
 fn cli() -> ArgMatches {
+    command().get_matches()
+}
+
+/// Build the clap CLI.
+fn command() -> Command {
     Command::new(crate_name!())
         .version(crate_version!())
         .author("Christian Duerr <contact@christianduerr.com>")
         .about(crate_description!())
         .subcommand(Command::new("zonetest").about("Test available RGB zones"))
+        .subcommand(Command::new("list-devices").about("List RGB devices connected to the HID bus"))
+        .subcommand(status::subcommand())
+        .subcommand(Command::new("sync").about("Apply the same configuration to all zones"))
+        .subcommand(
+            Command::new("off").about("Turn off all zones of the selected or detected devices"),
+        )
+        .subcommand(Command::new("undo").about("Restore the configuration before the last change"))
+        .subcommand(
+            Command::new("reapply")
+                .about("Apply the last applied configuration of all zones again"),
+        )
+        .subcommand(tui::subcommand())
+        .subcommand(leds::subcommand())
+        .subcommand(leds::header_subcommand())
+        .subcommand(profile::subcommand())
+        .subcommand(daemon::subcommand())
+        .subcommand(mqtt::subcommand())
+        .subcommand(service::subcommand())
+        .subcommand(animation::subcommand())
+        .subcommand(schedule::subcommand())
+        .subcommand(watch::subcommand())
+        .subcommand(check::subcommand())
+        .subcommand(raw::subcommand())
+        .subcommand(permissions::subcommand())
+        .subcommand(transcript::subcommand())
+        .arg(
+            Arg::new("config")
+                .help("Apply zone configurations from a TOML file")
+                .long("config")
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with_all([
+                    "color",
+                    "hsv",
+                    "hsl",
+                    "color2",
+                    "effect",
+                    "palette",
+                    "fade-in-time",
+                    "fade-out-time",
+                    "hold-time",
+                    "max-brightness",
+                    "min-brightness",
+                    "speed",
+                    "zone",
+                ]),
+        )
+        .arg(
+            Arg::new("gradient")
+                .help("Spread a color gradient across all zones [COLOR..COLOR]")
+                .long("gradient")
+                .value_parser(value_parser!(Gradient))
+                .conflicts_with_all(["config", "color", "hsv", "hsl", "zone"]),
+        )
+        .arg(
+            Arg::new("transition")
+                .help("Fade from the previous colors over a time in milliseconds")
+                .long("transition")
+                .global(true)
+                .value_parser(value_parser!(u64).range(1..)),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .help("Print the packets of all changes instead of applying them")
+                .long("dry-run")
+                .global(true)
+                .conflicts_with_all(["transition", "verify"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("output")
+                .help("Format of command results")
+                .long("output")
+                .global(true)
+                .default_value("text")
+                .value_parser(EnumValueParser::<output::Format>::new()),
+        )
+        .arg(
+            Arg::new("record")
+                .help("Append all packets written to devices to a transcript file")
+                .long("record")
+                .global(true)
+                .conflicts_with("dry-run")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("verify")
+                .help("Read the applied changes back from devices supporting it")
+                .long("verify")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("openrgb-forward")
+                .help(
+                    "Also apply the color and effect to an OpenRGB server [default: \
+                     127.0.0.1:6742]",
+                )
+                .long("openrgb-forward")
+                .value_name("address")
+                .global(true)
+                .num_args(0..=1)
+                .default_missing_value("127.0.0.1:6742"),
+        )
         .arg(
             Arg::new("device")
-                .help("RGB device")
+                .help("RGB device, detected automatically if omitted")
                 .long("device")
+                .global(true)
                 .short('d')
                 .ignore_case(true)
-                .value_parser(EnumValueParser::<RgbDevice>::new()),
+                .value_parser(device_parser()),
+        )
+        .arg(
+            Arg::new("hid-path")
+                .help("HID path of the device, to select between identical controllers")
+                .long("hid-path")
+                .global(true)
+                .conflicts_with("serial"),
+        )
+        .arg(
+            Arg::new("serial")
+                .help("Serial number of the device, to select between identical controllers")
+                .long("serial")
+                .global(true),
+        )
+        .arg(
+            Arg::new("vid")
+                .help("Override the device's HID vendor ID for untested boards")
+                .long("vid")
+                .global(true)
+                .value_parser(parse_id),
+        )
+        .arg(
+            Arg::new("pid")
+                .help("Override the device's HID product ID for untested boards")
+                .long("pid")
+                .global(true)
+                .value_parser(parse_id),
+        )
+        .arg(
+            Arg::new("retries")
+                .help("Number of times a failed HID write is retried")
+                .long("retries")
+                .global(true)
+                .default_value("2")
+                .value_parser(value_parser!(u32)),
+        )
+        .arg(
+            Arg::new("retry-delay")
+                .help(
+                    "Delay before retrying a failed HID write in milliseconds, doubled after each \
+                     attempt",
+                )
+                .long("retry-delay")
+                .global(true)
+                .default_value("100")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("timeout")
+                .help(
+                    "Abort HID reads and writes taking longer than a time in milliseconds, 0 to \
+                     disable",
+                )
+                .long("timeout")
+                .global(true)
+                .default_value("5000")
+                .value_parser(value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("color")
+                .help("LED color in RGB [0xRRGGBB, color name or @name]")
+                .long("color")
+                .short('c')
+                .action(ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("hsv")
+                .help("LED color in HSV, with hue in degrees and percentages [H,S,V]")
+                .long("hsv")
+                .action(ArgAction::Append)
+                .global(true)
+                .value_parser(value_parser!(Hsv))
+                .conflicts_with_all(["color", "hsl"]),
+        )
+        .arg(
+            Arg::new("hsl")
+                .help("LED color in HSL, with hue in degrees and percentages [H,S,L]")
+                .long("hsl")
+                .action(ArgAction::Append)
+                .global(true)
+                .value_parser(value_parser!(Hsl))
+                .conflicts_with("color"),
+        )
+        .arg(
+            Arg::new("color2")
+                .help("Secondary LED color used by dual-color effects [0xRRGGBB]")
+                .long("color2")
+                .action(ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("palette")
+                .help("Colors rotated through by the cycle effect [COLOR,COLOR,...]")
+                .long("palette")
+                .action(ArgAction::Append)
+                .global(true)
+                .value_parser(value_parser!(Palette)),
         )
-        .arg(Arg::new("color").help("LED color in RGB [0xRRGGBB]").long("color").short('c'))
         .arg(
             Arg::new("effect")
                 .help("Color transition effect")
                 .long("effect")
+                .action(ArgAction::Append)
+                .global(true)
                 .short('e')
                 .ignore_case(true)
                 .value_parser(EnumValueParser::<Effect>::new()),
         )
+        .arg(
+            Arg::new("speed")
+                .help("Effect speed preset, overridden by explicit effect times")
+                .long("speed")
+                .action(ArgAction::Append)
+                .global(true)
+                .ignore_case(true)
+                .value_parser(EnumValueParser::<Speed>::new()),
+        )
         .arg(
             Arg::new("fade-in-time")
                 .help("Effect fade in time in milliseconds")
-                .long("fade-in-time"),
+                .long("fade-in-time")
+                .action(ArgAction::Append)
+                .global(true),
         )
         .arg(
             Arg::new("fade-out-time")
                 .help("Effect fade out time in milliseconds")
-                .long("fade-out-time"),
+                .long("fade-out-time")
+                .action(ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("hold-time")
+                .help("Effect hold time in milliseconds")
+                .long("hold-time")
+                .action(ArgAction::Append)
+                .global(true),
         )
-        .arg(Arg::new("hold-time").help("Effect hold time in milliseconds").long("hold-time"))
         .arg(
             Arg::new("max-brightness")
-                .help("Maximum brightness [possible values: 0..=255]")
+                .help("Maximum brightness [possible values: 0..=255, 0%..=100%]")
                 .long("max-brightness")
+                .action(ArgAction::Append)
+                .global(true)
                 .short('b'),
         )
         .arg(
             Arg::new("min-brightness")
-                .help("Minimum brightness used for non-static effects [possible values: 0..=255]")
-                .long("min-brightness"),
+                .help(
+                    "Minimum brightness used for non-static effects [possible values: 0..=255, \
+                     0%..=100%]",
+                )
+                .long("min-brightness")
+                .action(ArgAction::Append)
+                .global(true),
         )
         .arg(
             Arg::new("zone")
-                .help("Position of the LED")
+                .help("Position of the LED, repeat to configure multiple zones")
                 .long("zone")
+                .action(ArgAction::Append)
+                .global(true)
                 .short('z')
                 .ignore_case(true)
-                .value_parser(EnumValueParser::<Zone>::new()),
+                .value_parser(zone_parser()),
         )
-        .get_matches()
+        .args(reactive::args())
+}
+
+/// Parser for the builtin and user-defined device names.
+fn device_parser() -> impl TypedValueParser<Value = Device> {
+    let values: Vec<_> = Device::all().iter().map(Device::possible_value).collect();
+    PossibleValuesParser::new(values).map(|name| Device::from_str(&name).unwrap())
+}
+
+/// Parser for zone names and user-defined zone labels.
+///
+/// Labels depend on the device, so zones are resolved using `zone_map::zone`
+/// once the device is known.
+fn zone_parser() -> PossibleValuesParser {
+    let zones = Zone::value_variants().iter().filter_map(Zone::to_possible_value);
+    let labels = zone_map::all_labels().map(PossibleValue::new);
+    PossibleValuesParser::new(zones.chain(labels))
+}
+
+/// Parse a HID vendor or product ID in hex or decimal.
+fn parse_id(id: &str) -> Result<u16, String> {
+    let parsed = match id.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => u16::from_str(id),
+    };
+    parsed.map_err(|_| format!("invalid HID ID '{id}', expected 0xXXXX"))
+}
+
+/// Get the motherboard name reported by the firmware.
+fn board_name() -> Option<&'static str> {
+    static BOARD_NAME: OnceLock<Option<String>> = OnceLock::new();
+    BOARD_NAME
+        .get_or_init(|| {
+            let name = fs::read_to_string("/sys/class/dmi/id/board_name").ok()?;
+            Some(name.trim().to_uppercase())
+        })
+        .as_deref()
+}
+
+/// Get the rgbfusion configuration directory.
+fn config_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join(crate_name!()))
+}
+
+/// Get the XDG state directory.
+fn state_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(base.join(crate_name!()))
 }
 
 /// Convert a CLI option from the parameter string.
 #[inline]
-fn cli_from_str<T>(matches: &ArgMatches, name: &str) -> Option<Result<T, <T as FromStr>::Err>>
+fn cli_from_str<T>(args: &ZoneArgs, name: &str) -> Option<Result<T, <T as FromStr>::Err>>
 where
     T: FromStr,
 {
-    matches.get_one::<String>(name).map(|value| T::from_str(value))
+    args.get::<String>(name).map(|value| T::from_str(value))
 }
 
 /// Replace config value with the CLI parameter if it is present.
 #[inline]
-fn replace_from_str<T: FromStr>(option: &mut T, matches: &ArgMatches, name: &str) {
-    if let Some(Ok(value)) = cli_from_str(matches, name) {
+fn replace_from_str<T: FromStr>(option: &mut T, args: &ZoneArgs, name: &str) {
+    if let Some(Ok(value)) = cli_from_str(args, name) {
         *option = value;
     }
 }
 
 /// Read the color option from CLI or prompt for STDIN if not present.
-fn required_color<T: FromStr>(matches: &ArgMatches) -> T {
-    match cli_from_str(matches, "color") {
+fn required_color(args: &ZoneArgs) -> Rgb {
+    if let Some(Hsv(color)) = args.get::<Hsv>("hsv") {
+        return *color;
+    }
+    if let Some(Hsl(color)) = args.get::<Hsl>("hsl") {
+        return *color;
+    }
+
+    match cli_from_str(args, "color") {
         Some(Ok(value)) => return value,
-        Some(Err(_)) => eprintln!("\x1b[31mInvalid CLI color parameter.\x1b[0m\n"),
+        Some(Err(err)) => eprintln!("\x1b[31mInvalid CLI color parameter: {err}.\x1b[0m\n"),
         _ => (),
     }
 
     loop {
         // Query the user for the option.
-        print!("Please select a color (format: 0xRRGGBB):\n > ");
+        print!("Please select a color (format: 0xRRGGBB or color name):\n > ");
         let _ = io::stdout().flush();
 
         let input = stdin_nextline();
 
-        match T::from_str(&input) {
+        match Rgb::from_str(&input) {
             Ok(value) => {
-                println!("");
+                println!();
                 break value;
             },
-            Err(_) => eprintln!(
-                "\x1b[31mColor '{}' does not match format 0xRRGGBB, please try again.\x1b[0m\n",
-                input
-            ),
+            Err(err) => eprintln!("\x1b[31mInvalid color: {err}, please try again.\x1b[0m\n"),
         }
     }
 }
 
-/// Read an enum option from CLI or prompt for STDIN if not present.
-fn required_enum<'a, T>(matches: &'a ArgMatches, name: &str) -> &'a T
+/// Read the device option from CLI or detect it from the HID bus.
+fn required_device(matches: &ArgMatches) -> Result<Device, Box<dyn Error>> {
+    if let Some(device) = matches.get_one::<Device>("device") {
+        return Ok(*device);
+    }
+
+    let api = HidApi::new()?;
+    match Device::detect(&api).as_slice() {
+        [] => Err("no supported RGB device found, use `--device` to select one manually".into()),
+        [device] => Ok(*device),
+        devices => {
+            println!("Found multiple supported RGB devices.");
+            Ok(*select_variant("device", devices, |name| Device::from_str(name).ok()))
+        },
+    }
+}
+
+/// Read an enum option from CLI or prompt for one of the variants if not
+/// present.
+fn required_enum<'a, T>(args: &ZoneArgs<'a>, name: &str, variants: &'a [T]) -> &'a T
 where
-    T: ValueEnum + Debug + Copy + Sync + Send + 'static,
+    T: ValueEnum + Debug + PartialEq + Copy + Sync + Send + 'static,
 {
-    if let Some(value) = matches.get_one::<T>(name) {
-        return value;
+    match args.get::<T>(name) {
+        Some(value) => value,
+        None => select_variant(name, variants, |name| T::from_str(name, true).ok()),
     }
+}
 
+/// Read the zone option from CLI or prompt for a zone, accepting zone labels.
+fn required_zone(args: &ZoneArgs, device: Device) -> Result<Zone, Box<dyn Error>> {
+    let controller = device.controller();
+    match args.get::<String>("zone") {
+        Some(name) => Ok(zone_map::zone(name, Some(device))?),
+        None => {
+            let parse = |name: &str| zone_map::zone(name, Some(device)).ok();
+            Ok(*select_variant("zone", controller.supported_zones(), parse))
+        },
+    }
+}
+
+/// Prompt for STDIN until one of the variants is selected by number or name.
+fn select_variant<'a, T, F>(name: &str, variants: &'a [T], parse: F) -> &'a T
+where
+    T: Debug + PartialEq,
+    F: Fn(&str) -> Option<T>,
+{
     loop {
-        // Offer all available zones.
-        println!("[{}] Please select a number:", name);
-        let variants = T::value_variants();
+        // Offer all available variants.
+        println!("[{}] Please select a number or name:", name);
         for (i, variant) in variants.iter().enumerate() {
             println!("  [{}] {:?}", i, variant);
         }
@@ -476,12 +2181,13 @@ where
 
         let input = stdin_nextline();
 
-        match usize::from_str(&input).ok().and_then(|index| variants.get(index)) {
+        let by_name = || parse(&input).and_then(|parsed| variants.iter().find(|v| **v == parsed));
+        match usize::from_str(&input).ok().and_then(|index| variants.get(index)).or_else(by_name) {
             Some(variant) => {
-                println!("");
+                println!();
                 return variant;
             },
-            // Query again if the zone is not valid.
+            // Query again if the variant is not valid.
             _ => println!("\x1b[31mVariant '{}' does not exist, please try again.\x1b[0m\n", input),
         }
     }
@@ -504,6 +2210,68 @@ mod tests {
 
     #[test]
     fn testcolors_match_zones() {
-        assert_eq!(Zone::variants().len(), TESTCOLORS.len());
+        assert_eq!(Zone::value_variants().len(), TESTCOLORS.len());
+    }
+
+    #[test]
+    fn color_parsing() {
+        assert!(NAMED_COLORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        assert_eq!(Rgb::from_str("teal"), Ok(Rgb { r: 0x00, g: 0x80, b: 0x80 }));
+        assert_eq!(Rgb::from_str("Rebecca Purple"), Ok(Rgb { r: 0x66, g: 0x33, b: 0x99 }));
+        assert_eq!(Rgb::from_str("0xff8000"), Ok(Rgb { r: 0xff, g: 0x80, b: 0x00 }));
+        assert_eq!(Rgb::from_str("#FF8000"), Ok(Rgb { r: 0xff, g: 0x80, b: 0x00 }));
+        assert_eq!(Rgb::from_str("ff8000"), Ok(Rgb { r: 0xff, g: 0x80, b: 0x00 }));
+        assert_eq!(Rgb::from_str("#F0A"), Ok(Rgb { r: 0xff, g: 0x00, b: 0xaa }));
+        assert!(Rgb::from_str("0xff80").is_err());
+        assert!(Rgb::from_str("#+f0000").is_err());
+        assert!(Rgb::from_str("blurple").is_err());
+    }
+
+    #[test]
+    fn hsv_hsl_conversion() {
+        assert_eq!(Hsv::from_str("0,100,100"), Ok(Hsv(Rgb { r: 255, g: 0, b: 0 })));
+        assert_eq!(Hsv::from_str("210,80,100"), Ok(Hsv(Rgb { r: 51, g: 153, b: 255 })));
+        assert_eq!(Hsl::from_str("120,100,25"), Ok(Hsl(Rgb { r: 0, g: 128, b: 0 })));
+        assert_eq!(Hsl::from_str("0,0,100"), Ok(Hsl(Rgb { r: 255, g: 255, b: 255 })));
+        assert!(Hsv::from_str("361,0,0").is_err());
+        assert!(Hsl::from_str("0,50").is_err());
+    }
+
+    #[test]
+    fn brightness_parsing() {
+        assert_eq!(Brightness::from_str("128"), Ok(Brightness(128)));
+        assert_eq!(Brightness::from_str("50%"), Ok(Brightness(128)));
+        assert_eq!(Brightness::from_str("100%"), Ok(Brightness(255)));
+        assert!(Brightness::from_str("101%").is_err());
+        assert!(Brightness::from_str("256").is_err());
+
+        let color = Rgb { r: 255, g: 128, b: 0 };
+        assert_eq!(color.scale(Brightness(128)), Rgb { r: 128, g: 64, b: 0 });
+    }
+
+    #[test]
+    fn gradient_interpolation() {
+        let gradient = Gradient::from_str("0x0000ff..0xff0000").unwrap();
+
+        assert_eq!(gradient.from.interpolate(gradient.to, 0.), gradient.from);
+        assert_eq!(gradient.from.interpolate(gradient.to, 1.), gradient.to);
+        assert_eq!(gradient.from.interpolate(gradient.to, 0.5), Rgb { r: 128, g: 0, b: 128 });
+    }
+
+    #[test]
+    fn repeated_zone_args() {
+        let args = "rgbfusion -e pulse -c 0x0000ff -z io -c 0xff0000 -z cpu -e static";
+        let matches = command().get_matches_from(args.split(' '));
+        let groups = ZoneArgs::split(&matches);
+
+        let zones: Vec<_> = groups.iter().map(|args| args.get::<String>("zone")).collect();
+        assert_eq!(zones, [Some(&"io".into()), Some(&"cpu".into())]);
+
+        // Options apply to the preceding zone, falling back to shared options.
+        let colors: Vec<_> = groups.iter().map(|args| args.get::<String>("color")).collect();
+        assert_eq!(colors, [Some(&"0xff0000".into()), Some(&"0x0000ff".into())]);
+        let effects: Vec<_> = groups.iter().map(|args| args.get::<Effect>("effect")).collect();
+        assert_eq!(effects, [Some(&Effect::Pulse), Some(&Effect::Static)]);
     }
 }
@@ -0,0 +1,514 @@
+//! MQTT light integration for Home Assistant.
+//!
+//! Every zone of the device is announced as an MQTT light using the JSON
+//! schema and Home Assistant's MQTT discovery:
+//! https://www.home-assistant.io/integrations/light.mqtt/#json-schema.
+//!
+//! Only the subset of MQTT 3.1.1 required for QoS 0 messages is implemented.
+
+use std::error::Error;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{Arg, ArgMatches, Command, ValueEnum};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{zone_defaults, Brightness, Config, Device, Effect, Rgb, Zone};
+
+/// Interval for keeping the broker connection alive.
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// Maximum delay between reconnection attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const SUBSCRIBE: u8 = 0x82;
+const PINGREQ: u8 = 0xc0;
+const PINGRESP: u8 = 0xd0;
+
+/// Get clap CLI parameters for the mqtt subcommand.
+pub fn subcommand() -> Command {
+    Command::new("mqtt")
+        .about("Expose zones as Home Assistant MQTT lights")
+        .arg(
+            Arg::new("broker")
+                .help("MQTT broker address")
+                .long("broker")
+                .default_value("localhost:1883"),
+        )
+        .arg(Arg::new("username").help("MQTT username").long("username"))
+        .arg(Arg::new("password").help("MQTT password").long("password").requires("username"))
+        .arg(
+            Arg::new("topic-prefix")
+                .help("Prefix for state and command topics")
+                .long("topic-prefix")
+                .default_value("rgbfusion"),
+        )
+        .arg(
+            Arg::new("discovery-prefix")
+                .help("Home Assistant discovery prefix")
+                .long("discovery-prefix")
+                .default_value("homeassistant"),
+        )
+}
+
+/// Run the MQTT client until it is killed.
+pub fn mqtt(matches: &ArgMatches) {
+    let device = match crate::required_device(matches) {
+        Ok(device) => device,
+        Err(err) => {
+            eprintln!("\x1b[31mError:\x1b[0m {err}");
+            return;
+        },
+    };
+
+    let mut bridge = Bridge::new(matches, device);
+    let mut delay = Duration::from_secs(1);
+
+    // Reconnect with exponential backoff whenever the connection drops.
+    loop {
+        match bridge.connect() {
+            Ok(client) => {
+                delay = Duration::from_secs(1);
+                if let Err(err) = bridge.run(client) {
+                    eprintln!("MQTT connection lost: {err}");
+                }
+            },
+            Err(err) => eprintln!("MQTT connection failed: {err}"),
+        }
+
+        eprintln!("Reconnecting in {}s...", delay.as_secs());
+        thread::sleep(delay);
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+/// Bridge between MQTT lights and the RGB device.
+struct Bridge {
+    broker: String,
+    username: Option<String>,
+    password: Option<String>,
+    topic_prefix: String,
+    discovery_prefix: String,
+    device: Device,
+    lights: Vec<(Zone, LightState)>,
+}
+
+impl Bridge {
+    fn new(matches: &ArgMatches, device: Device) -> Self {
+        let lights = device
+            .controller()
+            .supported_zones()
+            .iter()
+            .map(|zone| (*zone, LightState::default()))
+            .collect();
+
+        Self {
+            discovery_prefix: matches.get_one::<String>("discovery-prefix").unwrap().clone(),
+            topic_prefix: matches.get_one::<String>("topic-prefix").unwrap().clone(),
+            broker: matches.get_one::<String>("broker").unwrap().clone(),
+            username: matches.get_one::<String>("username").cloned(),
+            password: matches.get_one::<String>("password").cloned(),
+            lights,
+            device,
+        }
+    }
+
+    /// Connect to the broker and announce all lights.
+    fn connect(&mut self) -> Result<Client, Box<dyn Error>> {
+        let mut client = Client::connect(
+            &self.broker,
+            &self.availability_topic(),
+            self.username.as_deref(),
+            self.password.as_deref(),
+        )?;
+
+        println!("Connected to MQTT broker {}", self.broker);
+
+        // Restore retained state from previous runs.
+        client.subscribe(&format!("{}/{:?}/+/state", self.topic_prefix, self.device))?;
+        client.subscribe(&format!("{}/{:?}/+/set", self.topic_prefix, self.device))?;
+
+        for (zone, _) in &self.lights {
+            client.publish(&self.discovery_topic(*zone), &self.discovery(*zone), true)?;
+        }
+        client.publish(&self.availability_topic(), "online", true)?;
+
+        Ok(client)
+    }
+
+    /// Process messages until the connection drops.
+    fn run(&mut self, mut client: Client) -> Result<(), Box<dyn Error>> {
+        let mut last_ping = Instant::now();
+        loop {
+            // Half-open connections are only noticed by missing ping responses.
+            if client.last_pong.lock().unwrap().elapsed() > KEEP_ALIVE {
+                return Err("broker stopped responding".into());
+            }
+
+            // Ping regardless of incoming messages, to keep tracking responses.
+            if last_ping.elapsed() >= KEEP_ALIVE / 2 {
+                client.ping()?;
+                last_ping = Instant::now();
+            }
+
+            let timeout = (KEEP_ALIVE / 2).saturating_sub(last_ping.elapsed());
+            let (topic, payload) = match client.messages.recv_timeout(timeout) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return Err("connection closed".into()),
+            };
+
+            let (zone, kind) = match self.parse_topic(&topic) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+
+            let message: LightMessage = match serde_json::from_slice(&payload) {
+                Ok(message) => message,
+                Err(err) => {
+                    eprintln!("Ignoring invalid message on {topic}: {err}");
+                    continue;
+                },
+            };
+
+            let state = match self.lights.iter_mut().find(|(light_zone, _)| *light_zone == zone) {
+                Some((_, state)) => state,
+                None => continue,
+            };
+            let previous = *state;
+            message.update(state);
+
+            // Retained states only restore the previous state without writing to the
+            // device.
+            if kind == "state" || *state == previous {
+                continue;
+            }
+
+            let config = state.config(self.device, zone);
+            match crate::apply_config(&config) {
+                Ok(()) => {
+                    let state = serde_json::to_string(state)?;
+                    client.publish(&self.state_topic(zone), &state, true)?;
+                },
+                Err(err) => {
+                    eprintln!("Unable to apply {zone:?}: {err}");
+                    *state = previous;
+                },
+            }
+        }
+    }
+
+    /// Get the zone and topic kind from a state or command topic.
+    fn parse_topic<'a>(&self, topic: &'a str) -> Option<(Zone, &'a str)> {
+        let prefix = format!("{}/{:?}/", self.topic_prefix, self.device);
+        let (zone, kind) = topic.strip_prefix(&prefix)?.split_once('/')?;
+        Some((Zone::from_str(zone, true).ok()?, kind))
+    }
+
+    /// Home Assistant discovery payload for a zone.
+    fn discovery(&self, zone: Zone) -> String {
+        let controller = self.device.controller();
+        let effects: Vec<_> = controller
+            .supported_effects()
+            .iter()
+            .filter(|effect| **effect != Effect::Off)
+            .filter_map(|effect| Some(effect.to_possible_value()?.get_name().to_owned()))
+            .collect();
+
+        json!({
+            "name": format!("{} {zone:?}", controller.name()),
+            "unique_id": self.unique_id(zone),
+            "schema": "json",
+            "command_topic": format!("{}/{:?}/{}/set", self.topic_prefix, self.device, zone_name(zone)),
+            "state_topic": self.state_topic(zone),
+            "availability_topic": self.availability_topic(),
+            "brightness": true,
+            "supported_color_modes": ["rgb"],
+            "effect": true,
+            "effect_list": effects,
+            "device": {
+                "identifiers": [format!("rgbfusion_{:?}", self.device)],
+                "name": controller.name(),
+            },
+        })
+        .to_string()
+    }
+
+    fn unique_id(&self, zone: Zone) -> String {
+        format!("rgbfusion_{:?}_{}", self.device, zone_name(zone))
+    }
+
+    fn discovery_topic(&self, zone: Zone) -> String {
+        format!("{}/light/{}/config", self.discovery_prefix, self.unique_id(zone))
+    }
+
+    fn state_topic(&self, zone: Zone) -> String {
+        format!("{}/{:?}/{}/state", self.topic_prefix, self.device, zone_name(zone))
+    }
+
+    fn availability_topic(&self) -> String {
+        format!("{}/{:?}/status", self.topic_prefix, self.device)
+    }
+}
+
+/// CLI name of a zone.
+fn zone_name(zone: Zone) -> String {
+    zone.to_possible_value().unwrap().get_name().to_owned()
+}
+
+/// State of a single MQTT light.
+#[derive(Serialize, PartialEq, Eq, Copy, Clone)]
+struct LightState {
+    #[serde(serialize_with = "serialize_on")]
+    state: bool,
+    color_mode: ColorMode,
+    color: JsonColor,
+    brightness: u8,
+    effect: Effect,
+}
+
+impl Default for LightState {
+    fn default() -> Self {
+        Self {
+            state: false,
+            color_mode: ColorMode::Rgb,
+            color: JsonColor { r: 0xff, g: 0xff, b: 0xff },
+            brightness: u8::MAX,
+            effect: Effect::Static,
+        }
+    }
+}
+
+impl LightState {
+    /// Convert the light state to an RGB config.
+    fn config(&self, device: Device, zone: Zone) -> Config {
+        let mut config = Config { device, zone, ..Default::default() };
+        zone_defaults::apply(&mut config);
+
+        config.effect = if self.state { self.effect } else { Effect::Off };
+        config.color = Rgb { r: self.color.r, g: self.color.g, b: self.color.b };
+        config.max_brightness = Brightness(self.brightness);
+        config
+    }
+}
+
+fn serialize_on<S: serde::Serializer>(state: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(if *state { "ON" } else { "OFF" })
+}
+
+/// Light command or retained state.
+#[derive(Deserialize)]
+struct LightMessage {
+    state: Option<String>,
+    color: Option<JsonColor>,
+    brightness: Option<u8>,
+    effect: Option<Effect>,
+}
+
+impl LightMessage {
+    /// Update the light state with all fields present in the message.
+    fn update(&self, state: &mut LightState) {
+        if let Some(on) = &self.state {
+            state.state = on.eq_ignore_ascii_case("on");
+        }
+        if let Some(color) = self.color {
+            state.color = color;
+        }
+        if let Some(brightness) = self.brightness {
+            state.brightness = brightness;
+        }
+        if let Some(effect) = self.effect {
+            state.effect = effect;
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone)]
+struct JsonColor {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+#[derive(Serialize, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+enum ColorMode {
+    Rgb,
+}
+
+/// Minimal MQTT 3.1.1 client.
+struct Client {
+    stream: TcpStream,
+    messages: Receiver<(String, Vec<u8>)>,
+    /// Time of the last PINGRESP, or the connection if none was received yet.
+    last_pong: Arc<Mutex<Instant>>,
+    packet_id: u16,
+}
+
+impl Client {
+    /// Connect to a broker, using `will_topic` to announce disconnects.
+    fn connect(
+        address: &str,
+        will_topic: &str,
+        username: Option<&str>,
+        password: Option<&str>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut stream = TcpStream::connect(address)?;
+
+        // Pings are answered well within this, unless the broker is gone.
+        stream.set_read_timeout(Some(KEEP_ALIVE * 2))?;
+
+        // Clean session with retained QoS 0 last will.
+        let mut flags = 0x02 | 0x04 | 0x20;
+        let mut payload = Vec::new();
+        put_string(&mut payload, &format!("rgbfusion-{}", std::process::id()));
+        put_string(&mut payload, will_topic);
+        put_string(&mut payload, "offline");
+        if let Some(username) = username {
+            flags |= 0x80;
+            put_string(&mut payload, username);
+        }
+        if let Some(password) = password {
+            flags |= 0x40;
+            put_string(&mut payload, password);
+        }
+
+        let mut data = Vec::new();
+        put_string(&mut data, "MQTT");
+        data.push(4);
+        data.push(flags);
+        data.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+        data.extend_from_slice(&payload);
+        write_packet(&mut stream, CONNECT, &data)?;
+
+        let (header, data) = read_packet(&mut stream)?;
+        match (header & 0xf0, data.get(1)) {
+            (CONNACK, Some(0)) => (),
+            (CONNACK, Some(code)) => {
+                return Err(format!("broker refused connection: {code}").into())
+            },
+            _ => return Err("unexpected response from broker".into()),
+        }
+
+        // Read incoming packets in the background, to allow sending pings while idle.
+        let (sender, messages) = mpsc::channel();
+        let last_pong = Arc::new(Mutex::new(Instant::now()));
+        let mut reader = stream.try_clone()?;
+        let reader_pong = last_pong.clone();
+        thread::spawn(move || {
+            while let Ok((header, data)) = read_packet(&mut reader) {
+                if header & 0xf0 == PINGRESP {
+                    *reader_pong.lock().unwrap() = Instant::now();
+                }
+
+                if header & 0xf0 != PUBLISH {
+                    continue;
+                }
+
+                if let Some(message) = parse_publish(header, &data) {
+                    if sender.send(message).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { stream, messages, last_pong, packet_id: 0 })
+    }
+
+    fn publish(&mut self, topic: &str, payload: &str, retain: bool) -> Result<(), Box<dyn Error>> {
+        let mut data = Vec::new();
+        put_string(&mut data, topic);
+        data.extend_from_slice(payload.as_bytes());
+        write_packet(&mut self.stream, PUBLISH | retain as u8, &data)
+    }
+
+    fn subscribe(&mut self, topic: &str) -> Result<(), Box<dyn Error>> {
+        self.packet_id = self.packet_id.wrapping_add(1).max(1);
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&self.packet_id.to_be_bytes());
+        put_string(&mut data, topic);
+        data.push(0);
+        write_packet(&mut self.stream, SUBSCRIBE, &data)
+    }
+
+    fn ping(&mut self) -> Result<(), Box<dyn Error>> {
+        write_packet(&mut self.stream, PINGREQ, &[])
+    }
+}
+
+/// Get topic and payload from a PUBLISH packet.
+fn parse_publish(header: u8, data: &[u8]) -> Option<(String, Vec<u8>)> {
+    let topic_len = u16::from_be_bytes([*data.first()?, *data.get(1)?]) as usize;
+    let topic = String::from_utf8(data.get(2..2 + topic_len)?.to_vec()).ok()?;
+
+    // Skip packet identifier for QoS > 0.
+    let mut offset = 2 + topic_len;
+    if header & 0x06 != 0 {
+        offset += 2;
+    }
+
+    Some((topic, data.get(offset..)?.to_vec()))
+}
+
+/// Write a length-prefixed UTF-8 string.
+fn put_string(buf: &mut Vec<u8>, string: &str) {
+    buf.extend_from_slice(&(string.len() as u16).to_be_bytes());
+    buf.extend_from_slice(string.as_bytes());
+}
+
+/// Write a packet with its fixed header.
+fn write_packet(stream: &mut TcpStream, header: u8, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut packet = vec![header];
+
+    // Remaining length is encoded with 7 bits per byte.
+    let mut len = data.len();
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+
+        if len == 0 {
+            break;
+        }
+    }
+
+    packet.extend_from_slice(data);
+    stream.write_all(&packet)?;
+
+    Ok(())
+}
+
+/// Read a packet, returning its header byte and data.
+fn read_packet(stream: &mut TcpStream) -> Result<(u8, Vec<u8>), Box<dyn Error>> {
+    let mut header = [0];
+    stream.read_exact(&mut header)?;
+
+    let mut len = 0;
+    for shift in (0..28).step_by(7) {
+        let mut byte = [0];
+        stream.read_exact(&mut byte)?;
+
+        len |= ((byte[0] & 0x7f) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+
+    let mut data = vec![0; len];
+    stream.read_exact(&mut data)?;
+
+    Ok((header[0], data))
+}
@@ -0,0 +1,156 @@
+//! MSI Mystic Light motherboard RGB control.
+//!
+//! Mystic Light boards are configured through a single 185-byte feature report
+//! holding the settings of every zone. Only the bytes of the configured zone
+//! are replaced, so other zones keep their current settings.
+
+use std::error::Error;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::controller::{HidController, Packet, Transport};
+use crate::{strip, Brightness, Config, Duration, Effect, Zone};
+
+/// ID of the configuration feature report.
+const REPORT_ID: u8 = 0x52;
+
+/// Offset of the 12V RGB header `JRGB1`.
+const JRGB1_OFFSET: u8 = 1;
+
+/// Offset of the addressable header `JRAINBOW1`.
+const JRAINBOW1_OFFSET: u8 = 31;
+
+/// Offset of the addressable header `JRAINBOW2`.
+const JRAINBOW2_OFFSET: u8 = 42;
+
+/// Offset of the first onboard LED.
+const ONBOARD_OFFSET: u8 = 74;
+
+/// Number of onboard LEDs.
+const ONBOARD_LEDS: usize = 10;
+
+/// Offset of the flag for persisting the configuration.
+const SAVE_OFFSET: u8 = 184;
+
+/// Highest brightness level.
+const MAX_BRIGHTNESS: u16 = 10;
+
+/// Color flag for using the configured colors instead of a rainbow.
+const FIXED_COLOR: u8 = 0x80;
+
+pub struct MsiMysticLight;
+
+impl HidController for MsiMysticLight {
+    fn name(&self) -> &str {
+        "MSI Mystic Light"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x1462)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x7C34)
+    }
+
+    fn product_ids(&self) -> Vec<u16> {
+        vec![0x7C34, 0x7C35, 0x7C36, 0x7C37, 0x7C56, 0x7C59, 0x7C60, 0x7C67]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io, Zone::Cpu, Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[
+            Effect::Off,
+            Effect::Static,
+            Effect::Pulse,
+            Effect::Flash,
+            Effect::Cycle,
+            Effect::Rainbow,
+            Effect::ChaseFade,
+            Effect::Chase,
+        ]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (offset, count) = match config.zone {
+            Zone::Io => (ONBOARD_OFFSET, ONBOARD_LEDS),
+            Zone::Cpu => (JRGB1_OFFSET, 1),
+            Zone::Header0 => (JRAINBOW1_OFFSET, 1),
+            Zone::Header1 => (JRAINBOW2_OFFSET, 1),
+            zone => return Err(format!("unsupported zone: {zone:?}").into()),
+        };
+
+        let mut buf = BytesMut::new();
+        buf.put_slice(&[REPORT_ID, offset]);
+        for _ in 0..count {
+            buf.put_slice(&zone_bytes(config));
+        }
+
+        // Persist the updated report across reboots.
+        let save = Bytes::copy_from_slice(&[REPORT_ID, SAVE_OFFSET, 0x01]);
+
+        Ok(vec![
+            Packet::new(Transport::PatchFeatureReport, buf.freeze()),
+            Packet::new(Transport::PatchFeatureReport, save),
+        ])
+    }
+}
+
+/// Convert a config to the report data of a single zone.
+fn zone_bytes(config: &Config) -> [u8; 10] {
+    let strip_type = strip::strip_type(config.device, config.zone);
+    let (color, _) = strip_type.encode(config.color);
+    let (color2, _) = strip_type.encode(config.color2);
+
+    let effect = effect_bytes(config.effect);
+    let speed = speed_bytes(config.fade_in_time);
+    let brightness = brightness_bytes(config.max_brightness);
+    let flags = match config.effect {
+        Effect::Cycle | Effect::Rainbow => 0x00,
+        _ => FIXED_COLOR,
+    };
+
+    [
+        effect,
+        color.r,
+        color.g,
+        color.b,
+        brightness << 2 | speed,
+        color2.r,
+        color2.g,
+        color2.b,
+        flags,
+        0x00,
+    ]
+}
+
+/// Convert effect type to Mystic Light format.
+fn effect_bytes(effect: Effect) -> u8 {
+    match effect {
+        Effect::Off => 0,
+        Effect::Static => 1,
+        Effect::Pulse => 2,
+        Effect::Flash => 3,
+        Effect::ChaseFade => 7,
+        Effect::Cycle => 22,
+        Effect::Chase => 24,
+        Effect::Rainbow => 25,
+    }
+}
+
+/// Convert effect duration to the board's speed, with 0 being the slowest.
+fn speed_bytes(duration: Duration) -> u8 {
+    match duration.0 {
+        1000.. => 0,
+        400..=999 => 1,
+        _ => 2,
+    }
+}
+
+/// Convert brightness to the board's brightness levels.
+fn brightness_bytes(brightness: Brightness) -> u8 {
+    (MAX_BRIGHTNESS * brightness.0 as u16 / u8::MAX as u16) as u8
+}
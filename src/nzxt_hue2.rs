@@ -0,0 +1,190 @@
+//! NZXT Hue 2 RGB control.
+//!
+//! The four channels are mapped to the `header0`, `header1`, `io` and `cpu`
+//! zones in order. Static colors are written in direct mode, which allows
+//! software effects to update the LEDs without flickering.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::controller::{HidController, Packet};
+use crate::{strip, Config, Duration, Effect, Rgb, Zone};
+
+/// Size of a single HID report, including the report ID.
+const PACKET_SIZE: usize = 65;
+
+/// Maximum number of LEDs on a channel.
+const MAX_LEDS: usize = 40;
+
+/// Colors fitting into a single direct mode packet.
+const DIRECT_LEDS_PER_PACKET: usize = 20;
+
+const DIRECT: u8 = 0x22;
+const EFFECT: u8 = 0x28;
+
+pub struct NzxtHue2;
+
+impl HidController for NzxtHue2 {
+    fn name(&self) -> &str {
+        "NZXT Hue 2"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x1E71)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x2001)
+    }
+
+    fn product_ids(&self) -> Vec<u16> {
+        vec![0x2001, 0x2002]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Header0, Zone::Header1, Zone::Io, Zone::Cpu]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[
+            Effect::Off,
+            Effect::Static,
+            Effect::Pulse,
+            Effect::Flash,
+            Effect::Cycle,
+            Effect::Rainbow,
+            Effect::ChaseFade,
+            Effect::Chase,
+        ]
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let strip_type = strip::strip_type(config.device, config.zone);
+        let (color, _) = strip_type.encode(config.color);
+        let (color2, _) = strip_type.encode(config.color2);
+
+        if config.effect == Effect::Static {
+            return direct_packets(config.zone, &[color; MAX_LEDS]);
+        }
+
+        let (color, color2) = match config.effect {
+            Effect::Off => Default::default(),
+            _ => (color, color2),
+        };
+
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+        buf.put_slice(&[0x00, EFFECT, 0x03, channel_bytes(config.zone)?]);
+
+        // Mode, forward direction and speed of the first step.
+        buf.put_slice(&[0x00, effect_bytes(config.effect), 0x00]);
+        buf.put_u8(speed_bytes(config.fade_in_time));
+
+        buf.put_slice(&[color.g, color.r, color.b]);
+        buf.put_slice(&[color2.g, color2.r, color2.b]);
+
+        buf.resize(PACKET_SIZE, 0);
+
+        Ok(vec![buf.freeze().into()])
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(MAX_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(direct_packets(zone, colors))
+    }
+}
+
+/// Convert individual LED colors to direct mode packets for a channel.
+///
+/// The last packet applies the colors of the entire channel at once.
+fn direct_packets(zone: Zone, colors: &[Rgb]) -> Result<Vec<Packet>, Box<dyn Error>> {
+    if colors.len() > MAX_LEDS {
+        return Err(format!("Hue 2 channels support at most {MAX_LEDS} LEDs").into());
+    }
+
+    let channel = channel_bytes(zone)?;
+
+    let mut packets = Vec::new();
+    for (i, chunk) in colors.chunks(DIRECT_LEDS_PER_PACKET).enumerate() {
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+        buf.put_slice(&[0x00, DIRECT, 0x10 | i as u8, channel, 0x00]);
+
+        // Colors are sent in GRB order.
+        for color in chunk {
+            buf.put_slice(&[color.g, color.r, color.b]);
+        }
+
+        buf.resize(PACKET_SIZE, 0);
+        packets.push(buf.freeze().into());
+    }
+
+    let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+    buf.put_slice(&[0x00, DIRECT, 0xa0, channel, 0x00, 0x01, 0x00, 0x00, 0x28, 0x00, 0x00]);
+    buf.put_slice(&[0x80, 0x00, 0x32, 0x00, 0x00, 0x01]);
+    buf.resize(PACKET_SIZE, 0);
+    packets.push(buf.freeze().into());
+
+    Ok(packets)
+}
+
+/// Convert zone to the controller's channel mask.
+fn channel_bytes(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Header0 => Ok(0x01),
+        Zone::Header1 => Ok(0x02),
+        Zone::Io => Ok(0x04),
+        Zone::Cpu => Ok(0x08),
+        zone => Err(format!("unsupported zone: {zone:?}").into()),
+    }
+}
+
+/// Convert effect type to Hue 2 format.
+fn effect_bytes(effect: Effect) -> u8 {
+    match effect {
+        Effect::Off | Effect::Static => 0x00,
+        Effect::Cycle => 0x01,
+        Effect::Rainbow => 0x02,
+        Effect::Chase => 0x03,
+        Effect::ChaseFade => 0x04,
+        Effect::Flash => 0x06,
+        Effect::Pulse => 0x07,
+    }
+}
+
+/// Convert effect duration to the controller's speed, with 0 being the
+/// slowest.
+fn speed_bytes(duration: Duration) -> u8 {
+    match duration.0 {
+        2000.. => 0,
+        1000..=1999 => 1,
+        500..=999 => 2,
+        250..=499 => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direct_mode_chunks() {
+        let colors = vec![Rgb { r: 1, g: 2, b: 3 }; 25];
+        let packets = direct_packets(Zone::Io, &colors).unwrap();
+
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[0].bytes[..8], [0x00, 0x22, 0x10, 0x04, 0x00, 2, 1, 3]);
+        assert_eq!(packets[1].bytes[..8], [0x00, 0x22, 0x11, 0x04, 0x00, 2, 1, 3]);
+        assert_eq!(packets[1].bytes[20..23], [0, 0, 0]);
+        assert_eq!(packets[2].bytes[..4], [0x00, 0x22, 0xa0, 0x04]);
+
+        assert!(direct_packets(Zone::Io, &[Rgb::default(); 41]).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! NZXT Smart Device V2 RGB control.
+//!
+//! The Smart Device V2 uses the Hue 2 protocol, but only has two channels.
+
+use std::error::Error;
+
+use crate::controller::{HidController, Packet};
+use crate::nzxt_hue2::NzxtHue2;
+use crate::{Config, Effect, Rgb, Zone};
+
+pub struct NzxtSmartDeviceV2;
+
+impl HidController for NzxtSmartDeviceV2 {
+    fn name(&self) -> &str {
+        "NZXT Smart Device V2"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x1E71)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x2006)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Header0, Zone::Header1]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        NzxtHue2.supported_effects()
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        NzxtHue2.config_bytes(config)
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        NzxtHue2.max_leds()
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        NzxtHue2.led_bytes(zone, colors)
+    }
+}
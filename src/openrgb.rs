@@ -0,0 +1,646 @@
+//! OpenRGB SDK network protocol.
+//!
+//! The protocol is documented at
+//! https://gitlab.com/CalcProgrammer1/OpenRGB/-/wikis/OpenRGB-SDK-Documentation.
+//!
+//! When serving the protocol, every supported device is exposed as a single
+//! controller, with one LED per zone and one mode per effect.
+//!
+//! As a client, configs are forwarded to all controllers of an OpenRGB server.
+
+use std::error::Error;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use clap::crate_version;
+
+use crate::daemon::Daemon;
+use crate::{zone_defaults, Config, Device, Effect, Rgb};
+
+/// Protocol version implemented by the server.
+const PROTOCOL_VERSION: u32 = 0;
+
+/// Maximum time to wait for an OpenRGB server response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Magic bytes at the start of every packet header.
+const MAGIC: &[u8; 4] = b"ORGB";
+
+/// Size of the packet header in bytes.
+const HEADER_SIZE: usize = 16;
+
+/// Maximum accepted size of a received packet's data.
+const MAX_PACKET_SIZE: usize = 64 * 1024;
+
+const REQUEST_CONTROLLER_COUNT: u32 = 0;
+const REQUEST_CONTROLLER_DATA: u32 = 1;
+const REQUEST_PROTOCOL_VERSION: u32 = 40;
+const SET_CLIENT_NAME: u32 = 50;
+const UPDATE_LEDS: u32 = 1050;
+const UPDATE_ZONE_LEDS: u32 = 1051;
+const UPDATE_SINGLE_LED: u32 = 1052;
+const SET_CUSTOM_MODE: u32 = 1100;
+const UPDATE_MODE: u32 = 1101;
+
+const DEVICE_TYPE_MOTHERBOARD: i32 = 0;
+const ZONE_TYPE_SINGLE: i32 = 0;
+const MODE_FLAG_HAS_PER_LED_COLOR: u32 = 1 << 5;
+const COLOR_MODE_NONE: u32 = 0;
+const COLOR_MODE_PER_LED: u32 = 1;
+const COLOR_MODE_MODE_SPECIFIC: u32 = 2;
+
+/// Start the OpenRGB SDK server in a background thread.
+pub fn spawn_server(address: &str, daemon: Arc<Mutex<Daemon>>) -> Result<(), Box<dyn Error>> {
+    let listener =
+        TcpListener::bind(address).map_err(|err| format!("unable to bind to {address}: {err}"))?;
+
+    let controllers: Vec<_> =
+        daemon.lock().unwrap().detected().iter().copied().map(ControllerState::new).collect();
+    let server = Arc::new(Server { daemon, controllers: Mutex::new(controllers) });
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    eprintln!("OpenRGB connection failed: {err}");
+                    continue;
+                },
+            };
+
+            let server = server.clone();
+            thread::spawn(move || {
+                if let Err(err) = server.handle_client(stream) {
+                    eprintln!("OpenRGB client error: {err}");
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+/// Shared OpenRGB server state.
+struct Server {
+    daemon: Arc<Mutex<Daemon>>,
+    controllers: Mutex<Vec<ControllerState>>,
+}
+
+impl Server {
+    /// Process packets until the client disconnects.
+    fn handle_client(&self, mut stream: TcpStream) -> Result<(), Box<dyn Error>> {
+        loop {
+            let mut header = [0; HEADER_SIZE];
+            match stream.read_exact(&mut header) {
+                Ok(()) => (),
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(err) => return Err(err.into()),
+            }
+
+            let header = Header::parse(&header)?;
+            if header.size as usize > MAX_PACKET_SIZE {
+                return Err(format!("packet size {} exceeds {MAX_PACKET_SIZE}", header.size).into());
+            }
+
+            let mut data = vec![0; header.size as usize];
+            stream.read_exact(&mut data)?;
+
+            if let Some(response) = self.handle_packet(&header, Bytes::from(data))? {
+                stream.write_all(&response)?;
+            }
+        }
+    }
+
+    /// Process a single packet, returning the response.
+    fn handle_packet(
+        &self,
+        header: &Header,
+        mut data: Bytes,
+    ) -> Result<Option<Bytes>, Box<dyn Error>> {
+        let mut controllers = self.controllers.lock().unwrap();
+
+        // Handle requests which are not directed at a specific controller.
+        match header.id {
+            REQUEST_CONTROLLER_COUNT => {
+                let count = (controllers.len() as u32).to_le_bytes();
+                return Ok(Some(packet(0, REQUEST_CONTROLLER_COUNT, &count)));
+            },
+            REQUEST_PROTOCOL_VERSION => {
+                let version = PROTOCOL_VERSION.to_le_bytes();
+                return Ok(Some(packet(0, REQUEST_PROTOCOL_VERSION, &version)));
+            },
+            SET_CLIENT_NAME => return Ok(None),
+            _ => (),
+        }
+
+        let controller = match controllers.get_mut(header.device_index as usize) {
+            Some(controller) => controller,
+            None => return Err(format!("invalid controller index: {}", header.device_index).into()),
+        };
+
+        match header.id {
+            REQUEST_CONTROLLER_DATA => {
+                let data = controller.data();
+                return Ok(Some(packet(header.device_index, REQUEST_CONTROLLER_DATA, &data)));
+            },
+            UPDATE_LEDS => {
+                let _size = read_u32(&mut data)?;
+                let count = read_u16(&mut data)? as usize;
+                for i in 0..count {
+                    let color = read_color(&mut data)?;
+                    if let Some(led) = controller.colors.get_mut(i) {
+                        *led = color;
+                    }
+                }
+            },
+            UPDATE_ZONE_LEDS => {
+                let _size = read_u32(&mut data)?;
+                let zone = read_u32(&mut data)? as usize;
+                let count = read_u16(&mut data)?;
+                if count > 0 {
+                    let color = read_color(&mut data)?;
+                    if let Some(led) = controller.colors.get_mut(zone) {
+                        *led = color;
+                    }
+                }
+            },
+            UPDATE_SINGLE_LED => {
+                let led = read_u32(&mut data)? as usize;
+                let color = read_color(&mut data)?;
+                if let Some(led) = controller.colors.get_mut(led) {
+                    *led = color;
+                }
+            },
+            SET_CUSTOM_MODE => controller.effect = Effect::Static,
+            UPDATE_MODE => {
+                let _size = read_u32(&mut data)?;
+                let mode = read_u32(&mut data)? as usize;
+                let effects = controller.device.controller().supported_effects().to_vec();
+                if let Some(effect) = effects.get(mode) {
+                    controller.effect = *effect;
+                }
+            },
+            // Ignore unsupported requests like zone resizing.
+            _ => return Ok(None),
+        }
+
+        // Don't block other clients while writing to the devices.
+        let pending = controller.pending();
+        drop(controllers);
+
+        for (i, config) in pending {
+            match self.daemon.lock().unwrap().write(&config) {
+                Ok(()) => {
+                    let mut controllers = self.controllers.lock().unwrap();
+                    let controller = &mut controllers[header.device_index as usize];
+                    controller.applied[i] = Some((config.effect, config.color));
+                },
+                Err(err) => eprintln!("Unable to apply OpenRGB update: {err}"),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Current state of an OpenRGB controller.
+struct ControllerState {
+    device: Device,
+    effect: Effect,
+    colors: Vec<Rgb>,
+    applied: Vec<Option<(Effect, Rgb)>>,
+}
+
+impl ControllerState {
+    fn new(device: Device) -> Self {
+        let zones = device.controller().supported_zones().len();
+        Self {
+            device,
+            effect: Effect::Static,
+            colors: vec![Rgb::default(); zones],
+            applied: vec![None; zones],
+        }
+    }
+
+    /// Get the configs of all zones which have changed since the last update,
+    /// with their zone index.
+    fn pending(&self) -> Vec<(usize, Config)> {
+        let controller = self.device.controller();
+        let zones = controller.supported_zones().iter().enumerate();
+        zones
+            .filter(|(i, _)| self.applied[*i] != Some((self.effect, self.colors[*i])))
+            .map(|(i, zone)| {
+                let mut config = Config { device: self.device, zone: *zone, ..Default::default() };
+                zone_defaults::apply(&mut config);
+                config.effect = self.effect;
+                config.color = self.colors[i];
+                (i, config)
+            })
+            .collect()
+    }
+
+    /// Serialize the controller description.
+    fn data(&self) -> Bytes {
+        let controller = self.device.controller();
+        let effects = controller.supported_effects();
+        let zones = controller.supported_zones();
+
+        let mut buf = BytesMut::new();
+
+        buf.put_i32_le(DEVICE_TYPE_MOTHERBOARD);
+        put_string(&mut buf, controller.name());
+        put_string(&mut buf, "rgbfusion");
+        put_string(&mut buf, crate_version!());
+        put_string(&mut buf, "");
+        put_string(&mut buf, &controller.location());
+
+        // Effects are exposed as modes.
+        buf.put_u16_le(effects.len() as u16);
+        let active_mode = effects.iter().position(|effect| *effect == self.effect).unwrap_or(0);
+        buf.put_i32_le(active_mode as i32);
+        for (i, effect) in effects.iter().enumerate() {
+            let (flags, color_mode) = match effect {
+                Effect::Off => (0, COLOR_MODE_NONE),
+                _ => (MODE_FLAG_HAS_PER_LED_COLOR, COLOR_MODE_PER_LED),
+            };
+
+            let mode = Mode {
+                name: format!("{effect:?}"),
+                value: i as i32,
+                flags,
+                color_mode,
+                ..Default::default()
+            };
+            mode.write(&mut buf);
+        }
+
+        // Every zone is a single LED.
+        buf.put_u16_le(zones.len() as u16);
+        for zone in zones {
+            put_string(&mut buf, &format!("{zone:?}"));
+            buf.put_i32_le(ZONE_TYPE_SINGLE);
+            // Minimum, maximum and current number of LEDs.
+            buf.put_u32_le(1);
+            buf.put_u32_le(1);
+            buf.put_u32_le(1);
+            // Matrix map size.
+            buf.put_u16_le(0);
+        }
+
+        buf.put_u16_le(zones.len() as u16);
+        for (i, zone) in zones.iter().enumerate() {
+            put_string(&mut buf, &format!("{zone:?}"));
+            buf.put_u32_le(i as u32);
+        }
+
+        buf.put_u16_le(self.colors.len() as u16);
+        for color in &self.colors {
+            put_color(&mut buf, *color);
+        }
+
+        size_prefixed(&buf)
+    }
+}
+
+/// Forward a config's color and effect to all controllers of an OpenRGB server.
+///
+/// Returns the names of all updated controllers.
+pub fn forward(address: &str, config: &Config) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut client = Client::connect(address)?;
+
+    let count = client.request(0, REQUEST_CONTROLLER_COUNT, &[])?;
+    let count = read_u32(&mut count.clone())?;
+
+    let mut names = Vec::new();
+    for index in 0..count {
+        let data = client.request(index, REQUEST_CONTROLLER_DATA, &[])?;
+        let controller = ControllerData::parse(data)?;
+        client.apply(index, &controller, config)?;
+        names.push(controller.name);
+    }
+
+    Ok(names)
+}
+
+/// OpenRGB SDK client.
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn connect(address: &str) -> Result<Self, Box<dyn Error>> {
+        let stream = TcpStream::connect(address)
+            .map_err(|err| format!("unable to connect to OpenRGB at {address}: {err}"))?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+
+        // Without protocol negotiation, the server falls back to version 0.
+        let mut client = Self { stream };
+        client.send(0, SET_CLIENT_NAME, b"rgbfusion\0")?;
+
+        Ok(client)
+    }
+
+    /// Apply a config to one of the server's controllers.
+    fn apply(
+        &mut self,
+        index: u32,
+        controller: &ControllerData,
+        config: &Config,
+    ) -> Result<(), Box<dyn Error>> {
+        let color = if config.effect == Effect::Off { Rgb::default() } else { config.color };
+
+        // Use the controller's own mode for non-static effects, if it has one.
+        let names = mode_names(config.effect);
+        let mode = controller
+            .modes
+            .iter()
+            .position(|mode| names.iter().any(|name| mode.name.eq_ignore_ascii_case(name)));
+
+        match mode {
+            Some(mode_index) if config.effect != Effect::Static => {
+                let mut mode = controller.modes[mode_index].clone();
+                if mode.color_mode == COLOR_MODE_MODE_SPECIFIC {
+                    mode.colors.iter_mut().for_each(|mode_color| *mode_color = color);
+                }
+
+                let mut buf = BytesMut::new();
+                buf.put_i32_le(mode_index as i32);
+                mode.write(&mut buf);
+                self.send(index, UPDATE_MODE, &size_prefixed(&buf))?;
+
+                if mode.color_mode != COLOR_MODE_PER_LED {
+                    return Ok(());
+                }
+            },
+            _ => self.send(index, SET_CUSTOM_MODE, &[])?,
+        }
+
+        let mut buf = BytesMut::new();
+        buf.put_u16_le(controller.led_count as u16);
+        for _ in 0..controller.led_count {
+            put_color(&mut buf, color);
+        }
+        self.send(index, UPDATE_LEDS, &size_prefixed(&buf))
+    }
+
+    /// Send a packet and wait for the server's response.
+    fn request(
+        &mut self,
+        device_index: u32,
+        id: u32,
+        data: &[u8],
+    ) -> Result<Bytes, Box<dyn Error>> {
+        self.send(device_index, id, data)?;
+
+        // Skip unrelated packets, like device list update notifications.
+        loop {
+            let mut header = [0; HEADER_SIZE];
+            self.stream.read_exact(&mut header)?;
+            let header = Header::parse(&header)?;
+            if header.size as usize > MAX_PACKET_SIZE {
+                return Err(format!("packet size {} exceeds {MAX_PACKET_SIZE}", header.size).into());
+            }
+
+            let mut data = vec![0; header.size as usize];
+            self.stream.read_exact(&mut data)?;
+
+            if header.id == id {
+                return Ok(Bytes::from(data));
+            }
+        }
+    }
+
+    /// Send a packet without waiting for a response.
+    fn send(&mut self, device_index: u32, id: u32, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        self.stream.write_all(&packet(device_index, id, data))?;
+        Ok(())
+    }
+}
+
+/// Parts of the OpenRGB controller description used by the client.
+struct ControllerData {
+    name: String,
+    modes: Vec<Mode>,
+    led_count: usize,
+}
+
+impl ControllerData {
+    fn parse(mut data: Bytes) -> Result<Self, Box<dyn Error>> {
+        let _size = read_u32(&mut data)?;
+        let _device_type = read_u32(&mut data)?;
+        let name = read_string(&mut data)?;
+        let _description = read_string(&mut data)?;
+        let _version = read_string(&mut data)?;
+        let _serial = read_string(&mut data)?;
+        let _location = read_string(&mut data)?;
+
+        let mode_count = read_u16(&mut data)?;
+        let _active_mode = read_u32(&mut data)?;
+        let modes = (0..mode_count).map(|_| Mode::parse(&mut data)).collect::<Result<_, _>>()?;
+
+        let zone_count = read_u16(&mut data)?;
+        for _ in 0..zone_count {
+            let _name = read_string(&mut data)?;
+            let _zone_type = read_u32(&mut data)?;
+            let _leds_min = read_u32(&mut data)?;
+            let _leds_max = read_u32(&mut data)?;
+            let _leds_count = read_u32(&mut data)?;
+
+            let matrix_size = read_u16(&mut data)? as usize;
+            if data.remaining() < matrix_size {
+                return Err("unexpected end of packet".into());
+            }
+            data.advance(matrix_size);
+        }
+
+        let led_count = read_u16(&mut data)? as usize;
+
+        Ok(Self { name, modes, led_count })
+    }
+}
+
+/// OpenRGB mode names commonly used for an effect.
+fn mode_names(effect: Effect) -> &'static [&'static str] {
+    match effect {
+        Effect::Off => &["Off"],
+        Effect::Static => &["Direct", "Static"],
+        Effect::Pulse => &["Breathing", "Pulse"],
+        Effect::Flash => &["Flashing", "Flash"],
+        Effect::Cycle => &["Spectrum Cycle", "Color Cycle", "Cycle"],
+        Effect::Rainbow => &["Rainbow Wave", "Rainbow"],
+        Effect::ChaseFade => &["Chase Fade", "ChaseFade"],
+        Effect::Chase => &["Chase"],
+    }
+}
+
+/// OpenRGB controller mode.
+#[derive(Default, Debug, Clone)]
+struct Mode {
+    name: String,
+    value: i32,
+    flags: u32,
+    speed_min: u32,
+    speed_max: u32,
+    colors_min: u32,
+    colors_max: u32,
+    speed: u32,
+    direction: u32,
+    color_mode: u32,
+    colors: Vec<Rgb>,
+}
+
+impl Mode {
+    fn parse(data: &mut Bytes) -> Result<Self, Box<dyn Error>> {
+        let name = read_string(data)?;
+        let value = read_u32(data)? as i32;
+        let flags = read_u32(data)?;
+        let speed_min = read_u32(data)?;
+        let speed_max = read_u32(data)?;
+        let colors_min = read_u32(data)?;
+        let colors_max = read_u32(data)?;
+        let speed = read_u32(data)?;
+        let direction = read_u32(data)?;
+        let color_mode = read_u32(data)?;
+
+        let color_count = read_u16(data)?;
+        let colors = (0..color_count).map(|_| read_color(data)).collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            color_mode,
+            colors_max,
+            colors_min,
+            direction,
+            speed_max,
+            speed_min,
+            colors,
+            flags,
+            speed,
+            value,
+            name,
+        })
+    }
+
+    fn write(&self, buf: &mut BytesMut) {
+        put_string(buf, &self.name);
+        buf.put_i32_le(self.value);
+        buf.put_u32_le(self.flags);
+        buf.put_u32_le(self.speed_min);
+        buf.put_u32_le(self.speed_max);
+        buf.put_u32_le(self.colors_min);
+        buf.put_u32_le(self.colors_max);
+        buf.put_u32_le(self.speed);
+        buf.put_u32_le(self.direction);
+        buf.put_u32_le(self.color_mode);
+        buf.put_u16_le(self.colors.len() as u16);
+        for color in &self.colors {
+            put_color(buf, *color);
+        }
+    }
+}
+
+/// OpenRGB packet header.
+struct Header {
+    device_index: u32,
+    id: u32,
+    size: u32,
+}
+
+impl Header {
+    fn parse(mut bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        if !bytes.starts_with(MAGIC) {
+            return Err("invalid packet magic".into());
+        }
+        bytes.advance(MAGIC.len());
+
+        let device_index = bytes.get_u32_le();
+        let id = bytes.get_u32_le();
+        let size = bytes.get_u32_le();
+
+        Ok(Self { device_index, id, size })
+    }
+}
+
+/// Create a packet with its header.
+fn packet(device_index: u32, id: u32, data: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(HEADER_SIZE + data.len());
+    buf.put_slice(MAGIC);
+    buf.put_u32_le(device_index);
+    buf.put_u32_le(id);
+    buf.put_u32_le(data.len() as u32);
+    buf.put_slice(data);
+    buf.freeze()
+}
+
+/// Prefix data with its size, including the size field itself.
+fn size_prefixed(data: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(data.len() + 4);
+    buf.put_u32_le(data.len() as u32 + 4);
+    buf.put_slice(data);
+    buf.freeze()
+}
+
+/// Write a length-prefixed, null-terminated string.
+fn put_string(buf: &mut BytesMut, string: &str) {
+    buf.put_u16_le(string.len() as u16 + 1);
+    buf.put_slice(string.as_bytes());
+    buf.put_u8(0);
+}
+
+/// Write a color in OpenRGB's `0x00BBGGRR` format.
+fn put_color(buf: &mut BytesMut, color: Rgb) {
+    buf.put_slice(&[color.r, color.g, color.b, 0]);
+}
+
+/// Read a length-prefixed, null-terminated string.
+fn read_string(data: &mut Bytes) -> Result<String, Box<dyn Error>> {
+    let len = read_u16(data)? as usize;
+    if data.remaining() < len {
+        return Err("unexpected end of packet".into());
+    }
+
+    let bytes = data.split_to(len);
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(&bytes);
+
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
+fn read_u16(data: &mut Bytes) -> Result<u16, Box<dyn Error>> {
+    if data.remaining() < 2 {
+        return Err("unexpected end of packet".into());
+    }
+    Ok(data.get_u16_le())
+}
+
+fn read_u32(data: &mut Bytes) -> Result<u32, Box<dyn Error>> {
+    if data.remaining() < 4 {
+        return Err("unexpected end of packet".into());
+    }
+    Ok(data.get_u32_le())
+}
+
+fn read_color(data: &mut Bytes) -> Result<Rgb, Box<dyn Error>> {
+    let [r, g, b, _] = read_u32(data)?.to_le_bytes();
+    Ok(Rgb { r, g, b })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RgbDevice;
+
+    #[test]
+    fn controller_data_roundtrip() {
+        let state = ControllerState::new(Device::Builtin(RgbDevice::Trx40));
+        let controller = ControllerData::parse(state.data()).unwrap();
+
+        assert_eq!(controller.name, "Gigabyte TRX40 Aorus Master");
+        assert_eq!(controller.led_count, 6);
+        assert_eq!(controller.modes.len(), 5);
+        assert_eq!(controller.modes[1].name, "Static");
+        assert_eq!(controller.modes[1].color_mode, COLOR_MODE_PER_LED);
+    }
+}
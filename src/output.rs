@@ -0,0 +1,120 @@
+//! Machine-readable command output.
+//!
+//! With `--output json`, command results are printed to stdout as one JSON
+//! object per line instead of colored prose. Prompts and progress messages are
+//! moved to stderr, so stdout can be consumed by scripts directly.
+
+use std::error::Error;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::{Config, Device};
+
+/// Selected output format.
+static FORMAT: OnceLock<Format> = OnceLock::new();
+
+/// Format of command results.
+#[derive(ValueEnum, Default, PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Set the output format for the rest of the process.
+pub fn init(format: Format) {
+    let _ = FORMAT.set(format);
+}
+
+/// Check if results should be printed as JSON.
+pub fn json() -> bool {
+    FORMAT.get() == Some(&Format::Json)
+}
+
+/// Print a result as a single line of JSON.
+pub fn print(value: &impl Serialize) {
+    match serde_json::to_string(value) {
+        Ok(json) => println!("{json}"),
+        Err(err) => eprintln!("\x1b[31mError:\x1b[0m unable to serialize output: {err}"),
+    }
+}
+
+/// Print a human-readable message, which goes to stderr in JSON mode.
+pub fn message(message: &str) {
+    if json() {
+        eprintln!("{message}");
+    } else {
+        println!("{message}");
+    }
+}
+
+/// Print a prompt without trailing newline, which goes to stderr in JSON mode.
+pub fn prompt(prompt: &str) {
+    if json() {
+        eprint!("{prompt}");
+    } else {
+        print!("{prompt}");
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Result of writing a config to its device.
+#[derive(Serialize)]
+pub struct ConfigResult<'a> {
+    device: Device,
+    #[serde(flatten)]
+    config: &'a Config,
+    applied: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    emulated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl<'a> ConfigResult<'a> {
+    /// Result of a config written to the device.
+    ///
+    /// Verification is `None` if it was disabled or isn't supported by the
+    /// controller.
+    pub fn new(
+        config: &'a Config,
+        result: &Result<(), Box<dyn Error>>,
+        verification: &Option<Result<(), Box<dyn Error>>>,
+    ) -> Self {
+        let error = match (result, verification) {
+            (Err(err), _) => Some(err.to_string()),
+            (_, Some(Err(err))) => Some(format!("verification failed: {err}")),
+            _ => None,
+        };
+
+        Self {
+            device: config.device,
+            applied: result.is_ok(),
+            verified: verification.as_ref().map(Result::is_ok),
+            emulated: false,
+            config,
+            error,
+        }
+    }
+
+    /// Result of a config emulated in software.
+    pub fn emulated(config: &'a Config) -> Self {
+        Self {
+            device: config.device,
+            applied: true,
+            emulated: true,
+            verified: None,
+            error: None,
+            config,
+        }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
@@ -0,0 +1,185 @@
+//! Udev rules granting non-root users access to RGB controllers.
+
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command as Process;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use hidapi::HidApi;
+
+use crate::{polkit, Device};
+
+/// Location of the installed udev rules.
+const RULES_PATH: &str = "/etc/udev/rules.d/60-rgbfusion.rules";
+
+/// Get clap CLI parameters for the setup-permissions subcommand.
+pub fn subcommand() -> Command {
+    Command::new("setup-permissions")
+        .about("Generate udev rules allowing access without root permissions")
+        .arg(
+            Arg::new("install")
+                .help(format!("Install the rules to {RULES_PATH} and reload udev"))
+                .long("install")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("polkit")
+                .help("Generate the polkit policy for `rgbfusion daemon --polkit` instead")
+                .long("polkit")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+/// Run the setup-permissions subcommand.
+pub fn setup_permissions(matches: &ArgMatches) {
+    if let Err(err) = setup(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Print or install the udev rules.
+fn setup(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    if matches.get_flag("polkit") {
+        return setup_polkit(matches);
+    }
+
+    // Without a selected device, grant access to all supported controllers.
+    let devices = match matches.get_one::<Device>("device") {
+        Some(device) => vec![*device],
+        None => Device::all(),
+    };
+    let rules = rules(&devices);
+
+    if !matches.get_flag("install") {
+        diagnose(&devices);
+
+        println!("Add the following rules to {RULES_PATH}:\n");
+        print!("{rules}");
+        println!("\nThen reload them with `udevadm control --reload-rules && udevadm trigger`,");
+        println!("or run `sudo rgbfusion setup-permissions --install` to do this automatically.");
+
+        return Ok(());
+    }
+
+    let path = Path::new(RULES_PATH);
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, rules).map_err(|err| format!("unable to write {RULES_PATH}: {err}"))?;
+
+    udevadm(&["control", "--reload-rules"])?;
+    udevadm(&["trigger", "--subsystem-match=hidraw", "--action=add"])?;
+
+    println!("\x1b[32mInstalled {RULES_PATH}.\x1b[0m");
+    println!("Devices are accessible to logged in users once the rules are applied.");
+
+    Ok(())
+}
+
+/// Print or install the polkit policy.
+fn setup_polkit(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let policy_path = polkit::POLICY_PATH;
+    if !matches.get_flag("install") {
+        println!("Add the following policy to {policy_path}:\n");
+        print!("{}", polkit::policy());
+        println!("\nOr run `sudo rgbfusion setup-permissions --polkit --install` to do this.");
+        return Ok(());
+    }
+
+    let path = Path::new(policy_path);
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, polkit::policy())
+        .map_err(|err| format!("unable to write {policy_path}: {err}"))?;
+
+    println!("\x1b[32mInstalled {policy_path}.\x1b[0m");
+
+    Ok(())
+}
+
+/// Generate udev rules for all devices.
+fn rules(devices: &[Device]) -> String {
+    let mut rules = String::new();
+    for device in devices {
+        let controller = device.controller();
+
+        // SMBus controllers aren't accessed through hidraw.
+        let (Some(vendor_id), Some(product_id)) = (controller.vendor_id(), controller.product_id())
+        else {
+            continue;
+        };
+
+        let rule = format!(
+            "# {}\nSUBSYSTEM==\"hidraw\", ATTRS{{idVendor}}==\"{vendor_id:04x}\", \
+             ATTRS{{idProduct}}==\"{product_id:04x}\", TAG+=\"uaccess\"\n",
+            controller.name(),
+        );
+
+        // Custom definitions might share their IDs with other controllers.
+        if !rules.contains(rule.lines().nth(1).unwrap()) {
+            rules.push_str(&rule);
+        }
+    }
+    rules
+}
+
+/// Report which connected devices are inaccessible to the current user.
+fn diagnose(devices: &[Device]) {
+    let api = match HidApi::new() {
+        Ok(api) => api,
+        Err(err) => return eprintln!("\x1b[33mWarning:\x1b[0m unable to access HID: {err}"),
+    };
+
+    let mut found = false;
+    for info in api.device_list() {
+        let device = match devices.iter().find(|device| device.matches(info)) {
+            Some(device) => device,
+            None => continue,
+        };
+
+        let path = Path::new(info.path().to_str().unwrap_or_default());
+        let status = match OpenOptions::new().read(true).write(true).open(path) {
+            Ok(_) => "\x1b[32maccessible\x1b[0m".into(),
+            Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+                "\x1b[31mpermission denied\x1b[0m".into()
+            },
+            Err(err) => format!("\x1b[33m{err}\x1b[0m"),
+        };
+        println!("{} ({}): {status}", device.controller().name(), path.display());
+
+        found = true;
+    }
+
+    if found {
+        println!();
+    }
+}
+
+/// Run a udevadm command.
+fn udevadm(args: &[&str]) -> Result<(), Box<dyn Error>> {
+    let status = Process::new("udevadm")
+        .args(args)
+        .status()
+        .map_err(|err| format!("unable to run udevadm: {err}"))?;
+
+    if !status.success() {
+        return Err(format!("udevadm {} failed: {status}", args.join(" ")).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RgbDevice;
+
+    #[test]
+    fn udev_rules() {
+        let device = Device::Builtin(RgbDevice::Trx40);
+        assert_eq!(
+            rules(&[device, device]),
+            "# Gigabyte TRX40 Aorus Master\nSUBSYSTEM==\"hidraw\", ATTRS{idVendor}==\"048d\", \
+             ATTRS{idProduct}==\"8297\", TAG+=\"uaccess\"\n"
+        );
+    }
+}
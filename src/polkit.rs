@@ -0,0 +1,101 @@
+//! Polkit authorization of unprivileged daemon clients.
+//!
+//! With `rgbfusion daemon --polkit`, the daemon's socket is accessible to all
+//! users, with requests of other users being checked against the
+//! `org.rgbfusion.set-color` polkit action using `pkcheck`.
+
+use std::error::Error;
+use std::os::fd::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::process::{Command, Stdio};
+use std::{fs, io, mem};
+
+/// Polkit action required for changing colors.
+pub const ACTION_ID: &str = "org.rgbfusion.set-color";
+
+/// Location of the installed polkit policy.
+pub const POLICY_PATH: &str = "/usr/share/polkit-1/actions/org.rgbfusion.policy";
+
+/// Generate the polkit policy, allowing users of active local sessions to
+/// change colors.
+pub fn policy() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE policyconfig PUBLIC "-//freedesktop//DTD PolicyKit Policy Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/PolicyKit/1/policyconfig.dtd">
+<policyconfig>
+  <action id="{ACTION_ID}">
+    <description>Change RGB lighting</description>
+    <message>Authentication is required to change RGB lighting</message>
+    <defaults>
+      <allow_any>auth_admin</allow_any>
+      <allow_inactive>auth_admin</allow_inactive>
+      <allow_active>yes</allow_active>
+    </defaults>
+  </action>
+</policyconfig>
+"#
+    )
+}
+
+/// Check if the process connected to a socket may change colors.
+///
+/// Clients running as the same user as the daemon are always authorized.
+pub fn authorize(stream: &UnixStream) -> Result<(), Box<dyn Error>> {
+    let credentials = peer_credentials(stream)?;
+    if credentials.uid == unsafe { libc::geteuid() } {
+        return Ok(());
+    }
+
+    // Identify the process by its start time, to prevent PID reuse attacks.
+    let start_time = process_start_time(credentials.pid)?;
+    let subject = format!("{},{start_time},{}", credentials.pid, credentials.uid);
+
+    let status = Command::new("pkcheck")
+        .args(["--action-id", ACTION_ID, "--process", &subject])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|err| format!("unable to start pkcheck: {err}"))?;
+
+    if !status.success() {
+        return Err(format!("not authorized for polkit action {ACTION_ID}").into());
+    }
+
+    Ok(())
+}
+
+/// Get the credentials of the process connected to a socket.
+fn peer_credentials(stream: &UnixStream) -> Result<libc::ucred, Box<dyn Error>> {
+    let mut credentials: libc::ucred = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::ucred>() as libc::socklen_t;
+
+    // SAFETY: The buffer matches the size of the `SO_PEERCRED` option.
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut credentials as *mut _ as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if result != 0 {
+        return Err(
+            format!("unable to get client credentials: {}", io::Error::last_os_error()).into()
+        );
+    }
+
+    Ok(credentials)
+}
+
+/// Get the start time of a process in clock ticks since boot.
+fn process_start_time(pid: libc::pid_t) -> Result<u64, Box<dyn Error>> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat"))?;
+
+    // The command name might contain spaces, so fields are counted from its end.
+    let fields = stat.rsplit_once(')').map(|(_, fields)| fields).unwrap_or_default();
+    let start_time = fields.split_whitespace().nth(19).ok_or("invalid process stat")?;
+
+    Ok(start_time.parse()?)
+}
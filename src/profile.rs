@@ -0,0 +1,280 @@
+//! Named lighting profiles.
+//!
+//! Profiles are stored as configuration files in
+//! `~/.config/rgbfusion/profiles/<name>.toml`. Saving zones of another device
+//! to an existing profile adds the device, so a single profile can configure
+//! every controller of a system.
+//!
+//! Profiles can be exported as JSON documents, which are imported again by
+//! users with the same hardware.
+
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+
+use crate::config_file::DeviceConfigs;
+use crate::{output, state, Config, Device};
+
+/// Get clap CLI parameters for the profile subcommand.
+pub fn subcommand() -> Command {
+    let name = Arg::new("name").help("Profile name").required(true);
+
+    Command::new("profile")
+        .about("Manage named lighting profiles")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("save").about("Add the zone configuration to a profile").arg(name.clone()),
+        )
+        .subcommand(
+            Command::new("apply").about("Apply all zones of a profile").arg(name.clone()).arg(
+                Arg::new("atomic")
+                    .help("Restore all devices if the profile can't be applied to one of them")
+                    .long("atomic")
+                    .action(ArgAction::SetTrue),
+            ),
+        )
+        .subcommand(Command::new("list").about("List all available profiles"))
+        .subcommand(Command::new("delete").about("Delete a profile").arg(name.clone()))
+        .subcommand(
+            Command::new("export").about("Export a profile as JSON").arg(name.clone()).arg(
+                Arg::new("file")
+                    .help("Output file, defaults to stdout")
+                    .value_parser(value_parser!(PathBuf)),
+            ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Import a profile from JSON")
+                .arg(name)
+                .arg(
+                    Arg::new("file")
+                        .help("Input file, defaults to stdin")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("force")
+                        .help("Replace an existing profile with the same name")
+                        .long("force")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+}
+
+/// Run the profile subcommand.
+pub fn profile(matches: &ArgMatches) {
+    let result = match matches.subcommand() {
+        Some(("save", matches)) => save(matches),
+        Some(("apply", matches)) => apply(matches),
+        Some(("list", _)) => list(),
+        Some(("delete", matches)) => delete(matches),
+        Some(("export", matches)) => export(matches),
+        Some(("import", matches)) => import(matches),
+        _ => unreachable!(),
+    };
+
+    if let Err(err) = result {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Store the CLI config in a profile.
+fn save(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let name = matches.get_one::<String>("name").unwrap();
+    let path = profile_path(name)?;
+
+    let configs = Config::from_cli(matches)?;
+    let device = configs[0].device;
+
+    let mut file = if path.exists() { DeviceConfigs::load(&path)? } else { Default::default() };
+
+    let count = configs.len();
+    for config in configs {
+        file.entry(device).insert(config);
+    }
+    file.save(&path)?;
+
+    let zones = if count == 1 { "zone" } else { "zones" };
+    println!("\x1b[32mSaved {zones} to profile '{name}'.\x1b[0m");
+
+    Ok(())
+}
+
+/// Apply all zones stored in a profile.
+///
+/// With `--atomic`, devices the profile was already applied to are restored to
+/// their previous state if any device fails.
+fn apply(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let name = matches.get_one::<String>("name").unwrap();
+    let devices = device_configs(name, matches)?;
+    let transition = crate::transition_time(matches);
+    let verify = matches.get_flag("verify");
+
+    let mut applied: Vec<(Device, Vec<Config>)> = Vec::new();
+    let mut failed = Vec::new();
+    for configs in devices.iter().filter(|configs| !configs.is_empty()) {
+        let device = configs[0].device;
+        if devices.len() > 1 {
+            output::message(&format!("Applying profile '{name}' to {device:?}."));
+        }
+
+        applied.push((device, state::load(device)));
+        let success = crate::apply_configs(configs, transition, verify);
+        crate::forward_openrgb(matches, configs);
+
+        if !success && matches.get_flag("atomic") {
+            rollback(&applied);
+            return Err(format!("unable to apply profile '{name}' to {device:?}").into());
+        } else if !success {
+            failed.push(format!("{device:?}"));
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(format!("unable to apply profile '{name}' to {}", failed.join(", ")).into());
+    }
+
+    Ok(())
+}
+
+/// Restore the previous state of devices, most recently applied first.
+fn rollback(applied: &[(Device, Vec<Config>)]) {
+    for (device, previous) in applied.iter().rev() {
+        if previous.is_empty() {
+            eprintln!("\x1b[31mError:\x1b[0m no previous state to restore for {device:?}");
+            continue;
+        }
+
+        output::message(&format!("Restoring previous state of {device:?}."));
+        crate::apply_configs(previous, None, false);
+    }
+}
+
+/// Load all zones stored in a profile.
+pub fn configs(name: &str, matches: &ArgMatches) -> Result<Vec<Config>, Box<dyn Error>> {
+    Ok(device_configs(name, matches)?.into_iter().flatten().collect())
+}
+
+/// Load all zones stored in a profile, grouped by device.
+fn device_configs(name: &str, matches: &ArgMatches) -> Result<Vec<Vec<Config>>, Box<dyn Error>> {
+    let path = profile_path(name)?;
+
+    if !path.exists() {
+        return Err(format!("profile '{name}' does not exist").into());
+    }
+
+    DeviceConfigs::load(&path)?.configs(matches)
+}
+
+/// Print the names of all profiles.
+fn list() -> Result<(), Box<dyn Error>> {
+    let mut names: Vec<_> = match fs::read_dir(profile_dir()?) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    names.sort();
+
+    if names.is_empty() {
+        println!("No profiles found.");
+    }
+
+    for name in names {
+        println!("{name}");
+    }
+
+    Ok(())
+}
+
+/// Remove a profile.
+fn delete(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let name = matches.get_one::<String>("name").unwrap();
+
+    fs::remove_file(profile_path(name)?)
+        .map_err(|err| format!("unable to delete profile '{name}': {err}"))?;
+
+    println!("\x1b[32mDeleted profile '{name}'.\x1b[0m");
+
+    Ok(())
+}
+
+/// Write a profile as JSON document.
+fn export(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let name = matches.get_one::<String>("name").unwrap();
+    let path = profile_path(name)?;
+
+    if !path.exists() {
+        return Err(format!("profile '{name}' does not exist").into());
+    }
+
+    let json = serde_json::to_string_pretty(&DeviceConfigs::load(&path)?)?;
+    match matches.get_one::<PathBuf>("file") {
+        Some(file) => fs::write(file, json + "\n")
+            .map_err(|err| format!("unable to write {}: {err}", file.display()))?,
+        None => println!("{json}"),
+    }
+
+    Ok(())
+}
+
+/// Store a JSON document as profile.
+fn import(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let name = matches.get_one::<String>("name").unwrap();
+    let path = profile_path(name)?;
+
+    if path.exists() && !matches.get_flag("force") {
+        return Err(format!("profile '{name}' already exists, use --force to replace it").into());
+    }
+
+    let json = match matches.get_one::<PathBuf>("file") {
+        Some(file) => fs::read_to_string(file)
+            .map_err(|err| format!("unable to read {}: {err}", file.display()))?,
+        None => {
+            let mut json = String::new();
+            io::stdin().read_to_string(&mut json)?;
+            json
+        },
+    };
+
+    let configs: DeviceConfigs =
+        serde_json::from_str(&json).map_err(|err| format!("invalid profile: {err}"))?;
+
+    if configs.files.is_empty() {
+        return Err("profile has no devices".into());
+    }
+
+    // Reject zones or effects the devices don't support before storing them.
+    for file in &configs.files {
+        let device = file.device.ok_or("device must be specified for every profile entry")?;
+        file.clone().into_configs(device)?;
+    }
+
+    configs.save(&path)?;
+
+    println!("\x1b[32mImported profile '{name}'.\x1b[0m");
+
+    Ok(())
+}
+
+/// Get the directory containing all profiles.
+fn profile_dir() -> Result<PathBuf, Box<dyn Error>> {
+    match crate::config_dir() {
+        Some(config_dir) => Ok(config_dir.join("profiles")),
+        None => Err("unable to locate config directory".into()),
+    }
+}
+
+/// Get the file path of a profile.
+pub fn profile_path(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(format!("invalid profile name: {name}").into());
+    }
+
+    Ok(profile_dir()?.join(format!("{name}.toml")))
+}
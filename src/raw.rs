@@ -0,0 +1,101 @@
+//! Raw packet access for protocol experiments.
+
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+use clap::builder::EnumValueParser;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use hidapi::HidApi;
+
+use crate::controller::Transport;
+use crate::{definition, dry_run, Device};
+
+/// Get clap CLI parameters for the raw subcommand.
+pub fn subcommand() -> Command {
+    Command::new("raw")
+        .about("Send raw packets to a HID device")
+        .arg(
+            Arg::new("hex")
+                .help("Packet bytes in hex, repeat to send multiple packets")
+                .long("hex")
+                .required(true)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("transport")
+                .help("HID transfer used for all packets")
+                .long("transport")
+                .default_value("write")
+                .value_parser(EnumValueParser::<Transport>::new()),
+        )
+}
+
+/// Run the raw subcommand.
+pub fn raw(matches: &ArgMatches) {
+    if let Err(err) = send(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Send all packets to the device.
+fn send(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let transport = *matches.get_one::<Transport>("transport").unwrap();
+    let packets = matches
+        .get_many::<String>("hex")
+        .unwrap()
+        .map(|hex| definition::parse_hex(&hex.replace(' ', "")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Use the IDs of the selected device, unless overridden.
+    let options = crate::hid_options();
+    let controller = matches.get_one::<Device>("device").map(Device::controller);
+    let vendor_id = options.vendor_id.or(controller.as_ref().and_then(|c| c.vendor_id()));
+    let product_id = options.product_id.or(controller.as_ref().and_then(|c| c.product_id()));
+    let (vendor_id, product_id) = match (vendor_id, product_id) {
+        (Some(vendor_id), Some(product_id)) => (vendor_id, product_id),
+        _ => return Err("raw packets require `--device` or `--vid` and `--pid`".into()),
+    };
+
+    let api = HidApi::new().map_err(|err| format!("unable to access HID: {err}"))?;
+    let device = options
+        .selector
+        .open(&api, vendor_id, product_id)
+        .map_err(|err| format!("unable to open device: {err} ({})", crate::PERMISSIONS_HINT))?;
+    let device = Arc::new(Mutex::new(device));
+
+    for mut packet in packets {
+        let device = device.clone();
+        match transport {
+            Transport::Write => {
+                let len = packet.len();
+                crate::with_timeout("writing packet", move || {
+                    device.lock().unwrap().write(&packet)
+                })?;
+                println!("Wrote {len} bytes.");
+            },
+            Transport::FeatureReport => {
+                let len = packet.len();
+                crate::with_timeout("writing packet", move || {
+                    device.lock().unwrap().send_feature_report(&packet)
+                })?;
+                println!("Sent feature report with {len} bytes.");
+            },
+            Transport::GetFeatureReport => {
+                let (len, packet) = crate::with_timeout("reading feature report", move || {
+                    let len = device.lock().unwrap().get_feature_report(&mut packet)?;
+                    Ok::<_, hidapi::HidError>((len, packet))
+                })?;
+                println!("Received feature report with {len} bytes:");
+                print!("{}", dry_run::hex_dump(&packet[..len]));
+            },
+            Transport::SmbusByte | Transport::SmbusWord | Transport::SmbusAddressedByte => {
+                return Err("raw packets can only be sent to HID devices".into());
+            },
+            Transport::PatchFeatureReport => {
+                return Err("raw packets can't patch feature reports".into());
+            },
+        }
+    }
+
+    Ok(())
+}
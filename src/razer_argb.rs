@@ -0,0 +1,176 @@
+//! Razer Chroma Addressable RGB Controller.
+//!
+//! The six ports of the hub are mapped to the `header0`, `header1`, `io`,
+//! `cpu`, `audio` and `chipset` zones in order.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::controller::{HidController, Packet, Transport};
+use crate::{strip, Config, Effect, Rgb, Zone};
+
+/// Size of a single feature report, including the report ID.
+const PACKET_SIZE: usize = 91;
+
+/// Maximum size of the command arguments.
+const MAX_ARGS_SIZE: usize = 80;
+
+/// Transaction ID expected by the ARGB controller.
+const TRANSACTION_ID: u8 = 0x3f;
+
+/// Command class of the extended matrix commands.
+const COMMAND_CLASS: u8 = 0x0f;
+
+const SET_EFFECT: u8 = 0x02;
+const SET_FRAME: u8 = 0x03;
+const SET_BRIGHTNESS: u8 = 0x04;
+
+/// Store settings across reboots.
+const VARSTORE: u8 = 0x01;
+
+/// Custom frame effect, showing the colors of the last frame.
+const CUSTOM_FRAME: u8 = 0x08;
+
+/// Colors fitting into a single frame packet.
+const LEDS_PER_PACKET: usize = 25;
+
+/// Maximum number of LEDs on a port.
+const MAX_LEDS: usize = 80;
+
+pub struct RazerArgb;
+
+impl HidController for RazerArgb {
+    fn name(&self) -> &str {
+        "Razer Chroma ARGB Controller"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x1532)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x0F1F)
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Header0, Zone::Header1, Zone::Io, Zone::Cpu, Zone::Audio, Zone::Chipset]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Pulse, Effect::Cycle, Effect::Rainbow]
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+        let port = port_bytes(config.zone);
+
+        let mut args = vec![VARSTORE, port];
+        match config.effect {
+            Effect::Off => args.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]),
+            Effect::Static => args.extend_from_slice(&[0x01, 0x00, 0x00, 0x01]),
+            Effect::Pulse => args.extend_from_slice(&[0x02, 0x01, 0x00, 0x01]),
+            Effect::Cycle => args.extend_from_slice(&[0x03, 0x00, 0x00, 0x00]),
+            Effect::Rainbow => args.extend_from_slice(&[0x04, 0x01, 0x28, 0x00]),
+            effect => return Err(format!("unsupported effect: {effect:?}").into()),
+        }
+        if matches!(config.effect, Effect::Static | Effect::Pulse) {
+            args.extend_from_slice(&[color.r, color.g, color.b]);
+        }
+
+        let brightness = [VARSTORE, port, config.max_brightness.0];
+
+        Ok(vec![report(SET_EFFECT, &args), report(SET_BRIGHTNESS, &brightness)])
+    }
+
+    fn max_leds(&self) -> Option<usize> {
+        Some(MAX_LEDS)
+    }
+
+    fn led_bytes(&self, zone: Zone, colors: &[Rgb]) -> Option<Result<Vec<Packet>, Box<dyn Error>>> {
+        Some(frame_packets(zone, colors))
+    }
+}
+
+/// Convert individual LED colors to custom frame packets for a port.
+///
+/// The frame is shown once all colors are uploaded.
+fn frame_packets(zone: Zone, colors: &[Rgb]) -> Result<Vec<Packet>, Box<dyn Error>> {
+    if colors.len() > MAX_LEDS {
+        return Err(format!("ports support at most {MAX_LEDS} LEDs").into());
+    }
+
+    let port = port_bytes(zone);
+
+    let mut packets = Vec::new();
+    for (i, chunk) in colors.chunks(LEDS_PER_PACKET).enumerate() {
+        // Port and range of LEDs.
+        let start = i * LEDS_PER_PACKET;
+        let mut args = vec![0x00, 0x00, port, start as u8, (start + chunk.len() - 1) as u8];
+
+        for color in chunk {
+            args.extend_from_slice(&[color.r, color.g, color.b]);
+        }
+
+        packets.push(report(SET_FRAME, &args));
+    }
+
+    // Switch the port to the uploaded frame, without storing it.
+    packets.push(report(SET_EFFECT, &[0x00, port, CUSTOM_FRAME, 0x00, 0x00, 0x00]));
+
+    Ok(packets)
+}
+
+/// Wrap command arguments in a Razer feature report.
+fn report(command: u8, args: &[u8]) -> Packet {
+    let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+
+    // Report ID, status, transaction ID, remaining packets and protocol type.
+    buf.put_slice(&[0x00, 0x00, TRANSACTION_ID, 0x00, 0x00, 0x00]);
+
+    // Command.
+    buf.put_slice(&[args.len() as u8, COMMAND_CLASS, command]);
+    buf.put_slice(args);
+    buf.resize(9 + MAX_ARGS_SIZE, 0);
+
+    // Checksum of everything following the transaction ID.
+    let crc = buf[3..].iter().fold(0, |crc, byte| crc ^ byte);
+    buf.put_slice(&[crc, 0x00]);
+
+    Packet::new(Transport::FeatureReport, buf.freeze())
+}
+
+/// Convert zone to the hub's port.
+fn port_bytes(zone: Zone) -> u8 {
+    match zone {
+        Zone::Header0 => 0x00,
+        Zone::Header1 => 0x01,
+        Zone::Io => 0x02,
+        Zone::Cpu => 0x03,
+        Zone::Audio => 0x04,
+        Zone::Chipset => 0x05,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_frame_chunks() {
+        let colors = vec![Rgb { r: 1, g: 2, b: 3 }; 30];
+        let packets = frame_packets(Zone::Io, &colors).unwrap();
+
+        assert_eq!(packets.len(), 3);
+        assert!(packets.iter().all(|packet| packet.bytes.len() == PACKET_SIZE));
+        assert_eq!(packets[0].bytes[6..17], [80, 0x0f, 0x03, 0, 0, 2, 0, 24, 1, 2, 3]);
+        assert_eq!(packets[1].bytes[6..14], [20, 0x0f, 0x03, 0, 0, 2, 25, 29]);
+        assert_eq!(packets[2].bytes[6..15], [6, 0x0f, 0x02, 0, 2, 0x08, 0, 0, 0]);
+
+        // Checksum over the entire report must cancel out.
+        let crc = packets[2].bytes[3..90].iter().fold(0, |crc, byte| crc ^ byte);
+        assert_eq!(crc, 0);
+
+        assert!(frame_packets(Zone::Io, &[Rgb::default(); 81]).is_err());
+    }
+}
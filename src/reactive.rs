@@ -0,0 +1,416 @@
+//! Lighting modes reacting to the system state.
+//!
+//! Instead of applying a config once, these modes keep running and
+//! continuously map a measurement onto a color gradient for one or more zones.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use std::{fs, thread};
+
+use clap::builder::EnumValueParser;
+use clap::{value_parser, Arg, ArgMatches, ValueEnum};
+use hidapi::HidApi;
+
+#[cfg(feature = "audio")]
+use crate::audio;
+use crate::controller::DeviceHandle;
+use crate::screen::{self, Region};
+use crate::{daemon, zone_defaults, Config, Device, Effect, Rgb, ZoneArgs};
+
+/// Time between two measurements.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default minimum change of the gradient position before updating the color.
+///
+/// This avoids flickering when a measurement oscillates around a value.
+const HYSTERESIS: f32 = 0.04;
+
+/// Hwmon sensors used for the CPU temperature by default.
+const CPU_SENSORS: [&str; 3] = ["k10temp", "zenpower", "coretemp"];
+
+/// Available reactive modes.
+#[derive(ValueEnum, PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Mode {
+    Temperature,
+    CpuLoad,
+    #[cfg(feature = "audio")]
+    Audio,
+    Screen,
+}
+
+/// Config property controlled by the measurement.
+#[derive(ValueEnum, PartialEq, Eq, Debug, Copy, Clone)]
+enum Target {
+    /// Interpolate between the low and high color.
+    Color,
+    /// Scale the brightness of the high color.
+    Brightness,
+}
+
+/// Get clap CLI parameters for reactive modes.
+pub fn args() -> Vec<Arg> {
+    let args = vec![
+        Arg::new("mode")
+            .help("Continuously update the zone color based on the system state")
+            .long("mode")
+            .ignore_case(true)
+            .value_parser(EnumValueParser::<Mode>::new())
+            .conflicts_with_all(["config", "color", "hsv", "hsl", "effect"]),
+        Arg::new("map-to")
+            .help("Map the measurement onto the zone's color or brightness")
+            .long("map-to")
+            .ignore_case(true)
+            .requires("mode")
+            .value_parser(EnumValueParser::<Target>::new())
+            .default_value("color"),
+        Arg::new("low-color")
+            .help("Gradient color for low values [0xRRGGBB]")
+            .long("low-color")
+            .requires("mode")
+            .default_value("0x0000ff"),
+        Arg::new("high-color")
+            .help("Gradient color for high values [0xRRGGBB]")
+            .long("high-color")
+            .requires("mode")
+            .default_value("0xff0000"),
+        Arg::new("sensor")
+            .help("Hwmon sensor name, optionally followed by a label [e.g. k10temp/Tctl]")
+            .long("sensor")
+            .requires("mode"),
+        Arg::new("low-temperature")
+            .help("Temperature in °C mapped to the low color")
+            .long("low-temperature")
+            .requires("mode")
+            .value_parser(value_parser!(f32))
+            .default_value("40"),
+        Arg::new("high-temperature")
+            .help("Temperature in °C mapped to the high color")
+            .long("high-temperature")
+            .requires("mode")
+            .value_parser(value_parser!(f32))
+            .default_value("90"),
+        Arg::new("fps")
+            .help("Maximum number of updates per second in the audio and screen modes")
+            .long("fps")
+            .requires("mode")
+            .value_parser(value_parser!(u16).range(1..=120))
+            .default_value("30"),
+        Arg::new("region")
+            .help("Screen region sampled by the screen mode [format: WxH+X+Y]")
+            .long("region")
+            .requires("mode")
+            .value_parser(value_parser!(Region)),
+    ];
+
+    #[cfg(feature = "audio")]
+    let args = [args, audio::args()].concat();
+
+    args
+}
+
+/// Run a reactive mode until it is killed.
+pub fn run(matches: &ArgMatches, mode: Mode) {
+    if let Err(err) = run_mode(matches, mode) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+fn run_mode(matches: &ArgMatches, mode: Mode) -> Result<(), Box<dyn Error>> {
+    let mut configs = zone_configs(matches, mode)?;
+    let low_color = color_arg(matches, "low-color")?;
+    let high_color = color_arg(matches, "high-color")?;
+
+    let mut source: Box<dyn Source> = match mode {
+        Mode::Temperature => Box::new(Temperature::new(matches)?),
+        Mode::CpuLoad => Box::new(CpuLoad::new()?),
+        #[cfg(feature = "audio")]
+        Mode::Audio => Box::new(audio::Audio::new(matches, configs.len())?),
+        Mode::Screen => return screen::run(matches, configs),
+    };
+    let target = *matches.get_one::<Target>("map-to").unwrap();
+    let max_brightness: Vec<_> = configs.iter().map(|config| config.max_brightness).collect();
+
+    let zones: Vec<_> = configs.iter().map(|config| format!("{:?}", config.zone)).collect();
+    println!("Updating {} based on {}, press Ctrl+C to stop.", zones.join(", "), source.name());
+
+    let mut writer = Writer::default();
+    let mut levels = vec![0.; configs.len()];
+    let mut applied: Vec<Option<f32>> = vec![None; configs.len()];
+    loop {
+        let start = Instant::now();
+        source.sample(&mut levels)?;
+
+        let updates = configs.iter_mut().zip(&max_brightness).zip(&levels).zip(&mut applied);
+        for (((config, max_brightness), level), applied) in updates {
+            let level = level.clamp(0., 1.);
+            if applied.is_some_and(|applied| (level - applied).abs() < source.hysteresis()) {
+                continue;
+            }
+
+            match target {
+                Target::Color => config.color = low_color.interpolate(high_color, level),
+                Target::Brightness => {
+                    config.color = high_color;
+                    config.max_brightness.0 = (max_brightness.0 as f32 * level).round() as u8;
+                },
+            }
+            match writer.write(config) {
+                Ok(()) => *applied = Some(level),
+                Err(err) => eprintln!("\x1b[31mError:\x1b[0m {err}"),
+            }
+        }
+
+        thread::sleep(source.interval().saturating_sub(start.elapsed()));
+    }
+}
+
+/// Get the static configs for all reactive zones.
+///
+/// Audio visualization and screen sync use every zone of the device unless a
+/// zone was specified explicitly.
+fn zone_configs(matches: &ArgMatches, mode: Mode) -> Result<Vec<Config>, Box<dyn Error>> {
+    let device = crate::required_device(matches)?;
+    let controller = device.controller();
+
+    let all_zones = match mode {
+        #[cfg(feature = "audio")]
+        Mode::Audio => !matches.contains_id("zone"),
+        Mode::Screen => !matches.contains_id("zone"),
+        _ => false,
+    };
+
+    let mut configs = Vec::new();
+    for args in ZoneArgs::split(matches) {
+        let zones = if all_zones {
+            controller.supported_zones().to_vec()
+        } else {
+            vec![crate::required_zone(&args, device)?]
+        };
+
+        for zone in zones {
+            let mut config = Config { device, zone, effect: Effect::Static, ..Default::default() };
+            zone_defaults::apply(&mut config);
+            crate::replace_from_str(&mut config.max_brightness, &args, "max-brightness");
+            controller.validate(&config)?;
+            configs.push(config);
+        }
+    }
+
+    Ok(configs)
+}
+
+/// Parse a color CLI option.
+fn color_arg(matches: &ArgMatches, name: &str) -> Result<Rgb, Box<dyn Error>> {
+    let value = matches.get_one::<String>(name).unwrap();
+    Rgb::from_str(value).map_err(|err| format!("invalid --{name}: {err}").into())
+}
+
+/// Measurement mapped onto the color gradient.
+pub trait Source {
+    /// Description of the measured value.
+    fn name(&self) -> String;
+
+    /// Measure the current gradient position of each zone in the range `0..=1`.
+    fn sample(&mut self, levels: &mut [f32]) -> Result<(), Box<dyn Error>>;
+
+    /// Time between two measurements.
+    fn interval(&self) -> Duration {
+        POLL_INTERVAL
+    }
+
+    /// Minimum change of the gradient position before the color is updated.
+    fn hysteresis(&self) -> f32 {
+        HYSTERESIS
+    }
+}
+
+/// Hwmon temperature sensor.
+struct Temperature {
+    name: String,
+    input: PathBuf,
+    low: f32,
+    high: f32,
+}
+
+impl Temperature {
+    fn new(matches: &ArgMatches) -> Result<Self, Box<dyn Error>> {
+        let low = *matches.get_one::<f32>("low-temperature").unwrap();
+        let high = *matches.get_one::<f32>("high-temperature").unwrap();
+        if low >= high {
+            return Err("--low-temperature must be below --high-temperature".into());
+        }
+
+        let (name, input) = match matches.get_one::<String>("sensor") {
+            Some(sensor) => {
+                let (name, label) = match sensor.split_once('/') {
+                    Some((name, label)) => (name, Some(label)),
+                    None => (sensor.as_str(), None),
+                };
+                let input = find_sensor(name, label)
+                    .ok_or_else(|| format!("temperature sensor {sensor} not found"))?;
+                (sensor.clone(), input)
+            },
+            None => CPU_SENSORS
+                .iter()
+                .find_map(|name| Some((name.to_string(), find_sensor(name, None)?)))
+                .ok_or("no CPU temperature sensor found, use `--sensor` to select one")?,
+        };
+
+        Ok(Self { name, input, low, high })
+    }
+}
+
+impl Source for Temperature {
+    fn name(&self) -> String {
+        format!("{} temperature", self.name)
+    }
+
+    fn sample(&mut self, levels: &mut [f32]) -> Result<(), Box<dyn Error>> {
+        let content = fs::read_to_string(&self.input)
+            .map_err(|err| format!("unable to read {}: {err}", self.input.display()))?;
+        let millidegrees = f32::from_str(content.trim())?;
+
+        levels.fill((millidegrees / 1000. - self.low) / (self.high - self.low));
+
+        Ok(())
+    }
+}
+
+/// Total CPU utilization.
+struct CpuLoad {
+    idle: u64,
+    total: u64,
+}
+
+impl CpuLoad {
+    fn new() -> Result<Self, Box<dyn Error>> {
+        let (idle, total) = cpu_times()?;
+        Ok(Self { idle, total })
+    }
+}
+
+impl Source for CpuLoad {
+    fn name(&self) -> String {
+        String::from("CPU load")
+    }
+
+    fn sample(&mut self, levels: &mut [f32]) -> Result<(), Box<dyn Error>> {
+        let (idle, total) = cpu_times()?;
+        let idle_delta = idle.saturating_sub(self.idle);
+        let total_delta = total.saturating_sub(self.total);
+        (self.idle, self.total) = (idle, total);
+
+        if total_delta > 0 {
+            levels.fill(1. - idle_delta as f32 / total_delta as f32);
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the idle and total CPU time from `/proc/stat`.
+fn cpu_times() -> Result<(u64, u64), Box<dyn Error>> {
+    let stat = fs::read_to_string("/proc/stat")
+        .map_err(|err| format!("unable to read /proc/stat: {err}"))?;
+    let line = stat.lines().find(|line| line.starts_with("cpu ")).ok_or("missing CPU times")?;
+
+    // Fields are user, nice, system, idle, iowait, irq, softirq and steal time,
+    // followed by guest times which are already included in user and nice.
+    let times = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .map(u64::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+    if times.len() < 5 {
+        return Err(format!("invalid CPU times: {line}").into());
+    }
+
+    Ok((times[3] + times[4], times.iter().sum()))
+}
+
+/// Find the input file of a hwmon temperature sensor.
+///
+/// Without a label, the sensor's first temperature input is used.
+fn find_sensor(name: &str, label: Option<&str>) -> Option<PathBuf> {
+    let mut hwmons: Vec<_> = fs::read_dir("/sys/class/hwmon").ok()?.flatten().collect();
+    hwmons.sort_by_key(|entry| entry.file_name());
+
+    hwmons.iter().map(|entry| entry.path()).find_map(|hwmon| {
+        let hwmon_name = fs::read_to_string(hwmon.join("name")).ok()?;
+        if hwmon_name.trim() != name {
+            return None;
+        }
+
+        (1..=32).map(|i| hwmon.join(format!("temp{i}_input"))).find(|input| {
+            input.exists()
+                && label.is_none_or(|label| sensor_label(input).as_deref() == Some(label))
+        })
+    })
+}
+
+/// Get the label of a hwmon input file.
+fn sensor_label(input: &Path) -> Option<String> {
+    let file_name = input.file_name()?.to_str()?;
+    let label = input.with_file_name(file_name.replace("_input", "_label"));
+    Some(fs::read_to_string(label).ok()?.trim().to_owned())
+}
+
+/// Config writer keeping the device open between updates.
+#[derive(Default)]
+pub struct Writer {
+    device: Option<(Device, DeviceHandle)>,
+}
+
+impl Writer {
+    pub fn write(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        // Let the daemon handle the device if it is running.
+        if let Some(result) = daemon::send(config) {
+            return result;
+        }
+
+        let result =
+            self.open(config.device).and_then(|device| crate::write_device(device, config));
+
+        // Reopen the device on the next update in case the handle became invalid.
+        if result.is_err() {
+            self.device = None;
+        }
+
+        result
+    }
+
+    /// Persist the configs written to a device across reboots.
+    pub fn commit(&mut self, device: Device) -> Result<(), Box<dyn Error>> {
+        if let Some(result) = daemon::commit(device) {
+            return result;
+        }
+
+        self.open(device).and_then(|handle| crate::commit_device(handle, device))
+    }
+
+    /// Read a written config back from its device.
+    ///
+    /// Returns `None` if the controller can't report its state.
+    pub fn verify(&mut self, config: &Config) -> Option<Result<(), Box<dyn Error>>> {
+        match self.open(config.device) {
+            Ok(device) => crate::verify_device(device, config),
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    /// Get the HID handle of a device, opening it if necessary.
+    fn open(&mut self, device: Device) -> Result<&DeviceHandle, Box<dyn Error>> {
+        match &self.device {
+            Some((open, _)) if *open == device => (),
+            _ => {
+                let api = HidApi::new().map_err(|err| format!("unable to access HID: {err}"))?;
+                self.device = Some((device, crate::open_device(&api, device)?));
+            },
+        }
+
+        Ok(&self.device.as_ref().unwrap().1)
+    }
+}
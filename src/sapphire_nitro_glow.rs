@@ -0,0 +1,113 @@
+//! Sapphire Nitro+ graphics card Nitro Glow control.
+//!
+//! The Nitro Glow controller is connected to one of the AMDGPU i2c adapters.
+//! The entire card is controlled through the IO zone.
+
+use std::error::Error;
+
+use bytes::Bytes;
+use hidapi::HidApi;
+
+use crate::controller::{DeviceHandle, HidController, Packet, Transport};
+use crate::smbus::{self, SmbusDevice};
+use crate::{strip, Config, Duration, Effect, HidSelector, Zone};
+
+/// Name prefixes of AMD graphics card i2c adapters.
+const ADAPTERS: &[&str] = &["AMDGPU DM i2c"];
+
+/// i2c address of the Nitro Glow controller.
+const ADDRESS: u8 = 0x55;
+
+const REG_MODE: u8 = 0x00;
+const REG_BRIGHTNESS: u8 = 0x01;
+const REG_SPEED: u8 = 0x03;
+const REG_RED: u8 = 0x0a;
+const REG_GREEN: u8 = 0x0b;
+const REG_BLUE: u8 = 0x0c;
+
+pub struct SapphireNitroGlow;
+
+impl HidController for SapphireNitroGlow {
+    fn name(&self) -> &str {
+        "Sapphire Nitro Glow"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    fn smbus_addresses(&self) -> &[u8] {
+        &[ADDRESS]
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Io]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[Effect::Off, Effect::Static, Effect::Cycle, Effect::Rainbow, Effect::Chase]
+    }
+
+    fn open(&self, _api: &HidApi, _selector: &HidSelector) -> Result<DeviceHandle, Box<dyn Error>> {
+        for adapter in smbus::adapters(ADAPTERS) {
+            let device = match SmbusDevice::open(&adapter, &[ADDRESS]) {
+                Ok(device) => device,
+                Err(_) => continue,
+            };
+
+            // Only the adapter with the controller acknowledges reads.
+            if device.read_byte_data(REG_MODE).is_ok() {
+                return Ok(DeviceHandle::Smbus(device));
+            }
+        }
+
+        Err("no Sapphire Nitro Glow controller found, is the `i2c-dev` module loaded?".into())
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+
+        // The card has no off mode, so it is emulated with zero brightness.
+        let (mode, brightness) = match config.effect {
+            Effect::Off => (effect_bytes(Effect::Static)?, 0),
+            effect => (effect_bytes(effect)?, config.max_brightness.0),
+        };
+
+        let writes = [
+            (REG_RED, color.r),
+            (REG_GREEN, color.g),
+            (REG_BLUE, color.b),
+            (REG_SPEED, speed_bytes(config.fade_in_time)),
+            (REG_BRIGHTNESS, brightness),
+            (REG_MODE, mode),
+        ];
+
+        Ok(writes.iter().map(|(reg, value)| register_packet(*reg, *value)).collect())
+    }
+}
+
+/// Convert a register write to an SMBus packet.
+fn register_packet(register: u8, value: u8) -> Packet {
+    Packet::new(Transport::SmbusByte, Bytes::copy_from_slice(&[register, value]))
+}
+
+/// Convert effect type to Nitro Glow format.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Rainbow => Ok(0x00),
+        Effect::Chase => Ok(0x01),
+        Effect::Cycle => Ok(0x02),
+        Effect::Static => Ok(0x04),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Convert effect duration to the card's speed, with higher values being
+/// slower.
+fn speed_bytes(duration: Duration) -> u8 {
+    (duration.0 / 40).min(u8::MAX as u16) as u8
+}
@@ -0,0 +1,328 @@
+//! Time-of-day profile scheduling.
+//!
+//! Schedules are TOML files switching between profiles at fixed times or
+//! relative to sunrise and sunset:
+//!
+//! ```toml
+//! # Location, only required for sunrise and sunset times.
+//! latitude = 52.52
+//! longitude = 13.40
+//!
+//! [[entry]]
+//! time = "sunrise"
+//! profile = "day"
+//!
+//! [[entry]]
+//! time = "22:00"
+//! profile = "evening"
+//!
+//! [[entry]]
+//! time = "sunset-30"
+//! profile = "dusk"
+//! ```
+//!
+//! The profile of the most recent entry is applied whenever it changes, with
+//! sunrise and sunset offsets given in minutes.
+
+use std::error::Error;
+use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fs, mem, thread};
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+
+use crate::profile;
+use crate::reactive::Writer;
+
+/// Time between two schedule checks.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Minutes per day.
+const DAY: i32 = 24 * 60;
+
+/// Get clap CLI parameters for the schedule subcommand.
+pub fn subcommand() -> Command {
+    Command::new("schedule").about("Apply profiles at configured times of day").arg(
+        Arg::new("file").help("Schedule file").required(true).value_parser(value_parser!(PathBuf)),
+    )
+}
+
+/// Run the schedule subcommand.
+pub fn schedule(matches: &ArgMatches) {
+    if let Err(err) = run(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Apply the scheduled profiles until rgbfusion is killed.
+fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let path = matches.get_one::<PathBuf>("file").unwrap();
+    let schedule = Schedule::load(path)?;
+    let transition = crate::transition_time(matches);
+    let verify = matches.get_flag("verify");
+
+    println!("Running schedule, press Ctrl+C to stop.");
+
+    let mut writer = Writer::default();
+    let mut applied: Option<&str> = None;
+    loop {
+        let profile = schedule.active(&LocalTime::now());
+
+        if let Some(name) = profile.filter(|_| profile != applied) {
+            println!("Applying profile '{name}'.");
+
+            match profile::configs(name, matches) {
+                Ok(configs) => {
+                    for config in
+                        crate::write_configs(&mut writer, &configs, transition, verify).emulated
+                    {
+                        eprintln!(
+                            "\x1b[31mError:\x1b[0m {:?} is emulated in software, which requires \
+                             `rgbfusion daemon`",
+                            config.effect
+                        );
+                    }
+                },
+                Err(err) => eprintln!("\x1b[31mError:\x1b[0m {err}"),
+            }
+
+            applied = profile;
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Schedule file format.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Schedule {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    #[serde(rename = "entry")]
+    entries: Vec<Entry>,
+}
+
+impl Schedule {
+    /// Load a schedule file.
+    fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("unable to read {}: {err}", path.display()))?;
+        let schedule: Self = toml::from_str(&content)
+            .map_err(|err| format!("invalid schedule {}: {err}", path.display()))?;
+
+        if schedule.entries.is_empty() {
+            return Err(format!("no entries in schedule {}", path.display()).into());
+        }
+
+        let solar = schedule.entries.iter().any(|entry| !matches!(entry.time, Time::Fixed(_)));
+        if solar && (schedule.latitude.is_none() || schedule.longitude.is_none()) {
+            return Err("sunrise and sunset times require a latitude and longitude".into());
+        }
+
+        Ok(schedule)
+    }
+
+    /// Get the profile of the most recent entry.
+    ///
+    /// Before the first entry of the day, the last entry of the previous day
+    /// is active.
+    fn active(&self, now: &LocalTime) -> Option<&str> {
+        let mut times: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|entry| Some((self.resolve(entry.time, now)?, entry.profile.as_str())))
+            .collect();
+        times.sort_by_key(|(time, _)| *time);
+
+        let previous = times.iter().rev().find(|(time, _)| *time <= now.minutes);
+        previous.or(times.last()).map(|(_, profile)| *profile)
+    }
+
+    /// Get the minute of the day for an entry's time.
+    ///
+    /// Returns `None` if the sun doesn't rise or set on this day.
+    fn resolve(&self, time: Time, now: &LocalTime) -> Option<i32> {
+        let (event, offset) = match time {
+            Time::Fixed(minutes) => return Some(minutes),
+            Time::Sunrise(offset) => (SolarEvent::Sunrise, offset),
+            Time::Sunset(offset) => (SolarEvent::Sunset, offset),
+        };
+
+        let (latitude, longitude) = (self.latitude?, self.longitude?);
+        let minutes = solar_event(event, latitude, longitude, now.day_of_year)?;
+        let local = minutes + now.utc_offset as f64 / 60. + offset as f64;
+
+        Some((local.round() as i32).rem_euclid(DAY))
+    }
+}
+
+/// Profile applied at a specific time.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    time: Time,
+    profile: String,
+}
+
+/// Time of a schedule entry.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum Time {
+    /// Minute of the day.
+    Fixed(i32),
+    /// Minutes relative to sunrise.
+    Sunrise(i32),
+    /// Minutes relative to sunset.
+    Sunset(i32),
+}
+
+impl FromStr for Time {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("time '{s}' does not match format HH:MM, sunrise±MM or sunset±MM");
+
+        let solar = |rest: &str| -> Result<i32, String> {
+            match rest {
+                "" => Ok(0),
+                _ if rest.starts_with(['+', '-']) => i32::from_str(rest).map_err(|_| error()),
+                _ => Err(error()),
+            }
+        };
+
+        if let Some(rest) = s.strip_prefix("sunrise") {
+            return Ok(Time::Sunrise(solar(rest)?));
+        }
+        if let Some(rest) = s.strip_prefix("sunset") {
+            return Ok(Time::Sunset(solar(rest)?));
+        }
+
+        let (hours, minutes) = s.split_once(':').ok_or_else(error)?;
+        match (u8::from_str(hours), u8::from_str(minutes)) {
+            (Ok(hours @ 0..=23), Ok(minutes @ 0..=59)) => {
+                Ok(Time::Fixed(hours as i32 * 60 + minutes as i32))
+            },
+            _ => Err(error()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Time {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let time = String::deserialize(deserializer)?;
+        Time::from_str(&time).map_err(D::Error::custom)
+    }
+}
+
+/// Current local time.
+pub struct LocalTime {
+    /// Minute of the day.
+    minutes: i32,
+    /// Zero-based day of the year.
+    day_of_year: i32,
+    /// Day of the week, starting with Sunday as 0.
+    pub weekday: i32,
+    /// Offset from UTC in seconds.
+    utc_offset: i64,
+}
+
+impl LocalTime {
+    pub fn now() -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let now = now.as_secs() as libc::time_t;
+
+        // SAFETY: `localtime_r` only writes to the provided `tm` struct.
+        let mut tm: libc::tm = unsafe { mem::zeroed() };
+        unsafe { libc::localtime_r(&now, &mut tm) };
+
+        Self {
+            minutes: tm.tm_hour * 60 + tm.tm_min,
+            day_of_year: tm.tm_yday,
+            weekday: tm.tm_wday,
+            utc_offset: tm.tm_gmtoff as i64,
+        }
+    }
+}
+
+/// Sun position used for schedule entries.
+#[derive(Copy, Clone)]
+enum SolarEvent {
+    Sunrise,
+    Sunset,
+}
+
+/// Calculate the UTC minute of the day for sunrise or sunset.
+///
+/// Uses the NOAA approximation, returning `None` during polar day or night.
+fn solar_event(event: SolarEvent, latitude: f64, longitude: f64, day_of_year: i32) -> Option<f64> {
+    let gamma = 2. * PI / 365. * day_of_year as f64;
+
+    // Equation of time in minutes.
+    let eqtime = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2. * gamma).cos()
+            - 0.040849 * (2. * gamma).sin());
+
+    // Solar declination in radians.
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2. * gamma).cos()
+        + 0.000907 * (2. * gamma).sin()
+        - 0.002697 * (3. * gamma).cos()
+        + 0.00148 * (3. * gamma).sin();
+
+    // Hour angle of the sun at the horizon, accounting for refraction.
+    let latitude = latitude.to_radians();
+    let cos_hour_angle = 90.833f64.to_radians().cos() / (latitude.cos() * declination.cos())
+        - latitude.tan() * declination.tan();
+    if !(-1.0..=1.).contains(&cos_hour_angle) {
+        return None;
+    }
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+
+    let hour_angle = match event {
+        SolarEvent::Sunrise => hour_angle,
+        SolarEvent::Sunset => -hour_angle,
+    };
+    Some(720. - 4. * (longitude + hour_angle) - eqtime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_entries() {
+        let schedule: Schedule = toml::from_str(
+            r#"
+                latitude = 52.52
+                longitude = 13.40
+
+                [[entry]]
+                time = "sunrise"
+                profile = "day"
+
+                [[entry]]
+                time = "22:00"
+                profile = "night"
+            "#,
+        )
+        .unwrap();
+
+        // Berlin on the summer solstice, sunrise is around 04:43 CEST.
+        let at = |minutes| LocalTime { minutes, day_of_year: 171, weekday: 5, utc_offset: 7200 };
+        let sunrise = schedule.resolve(Time::Sunrise(0), &at(0)).unwrap();
+        assert!((sunrise - (4 * 60 + 43)).abs() <= 5, "sunrise at {}", sunrise);
+
+        assert_eq!(schedule.active(&at(60)), Some("night"));
+        assert_eq!(schedule.active(&at(12 * 60)), Some("day"));
+        assert_eq!(schedule.active(&at(23 * 60)), Some("night"));
+
+        assert_eq!(Time::from_str("sunset-30"), Ok(Time::Sunset(-30)));
+        assert!(Time::from_str("24:00").is_err());
+    }
+}
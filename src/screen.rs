@@ -0,0 +1,286 @@
+//! Screen ambient light sync.
+//!
+//! The screen is captured with `grim` on wlroots compositors and with
+//! `ffmpeg`'s x11grab on X11. The dominant color of each frame is applied to
+//! all reactive zones.
+
+use std::error::Error;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command as Process, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+use std::{env, thread};
+
+use clap::ArgMatches;
+
+use crate::reactive::Writer;
+use crate::{Config, Rgb};
+
+/// Approximate number of pixels analyzed per frame.
+const SAMPLE_PIXELS: usize = 10_000;
+
+/// Pixels with no channel above this value are ignored, to skip black bars.
+const DARK_THRESHOLD: u8 = 16;
+
+/// Minimum channel difference before the zone color is updated.
+const COLOR_HYSTERESIS: u8 = 4;
+
+/// Rectangular region of the screen.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct Region {
+    width: u32,
+    height: u32,
+    x: u32,
+    y: u32,
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = || -> Option<Self> {
+            let (size, position) = s.split_once('+')?;
+            let (width, height) = size.split_once('x')?;
+            let (x, y) = position.split_once('+')?;
+            Some(Self {
+                width: width.parse().ok()?,
+                height: height.parse().ok()?,
+                x: x.parse().ok()?,
+                y: y.parse().ok()?,
+            })
+        };
+
+        match parse() {
+            Some(region) if region.width > 0 && region.height > 0 => Ok(region),
+            _ => Err(format!("invalid region {s:?}, expected WxH+X+Y")),
+        }
+    }
+}
+
+/// Continuously apply the dominant screen color to all zones.
+pub fn run(matches: &ArgMatches, mut configs: Vec<Config>) -> Result<(), Box<dyn Error>> {
+    let region = matches.get_one::<Region>("region").copied();
+    let fps = *matches.get_one::<u16>("fps").unwrap();
+    let interval = Duration::from_secs(1) / fps as u32;
+
+    let mut capture = Capture::new(region, fps)?;
+
+    let zones: Vec<_> = configs.iter().map(|config| format!("{:?}", config.zone)).collect();
+    println!("Updating {} based on the screen, press Ctrl+C to stop.", zones.join(", "));
+
+    let mut writer = Writer::default();
+    let mut applied: Option<Rgb> = None;
+    loop {
+        let start = Instant::now();
+        let color = capture.frame()?.dominant_color();
+
+        if applied.is_none_or(|applied| color_distance(applied, color) >= COLOR_HYSTERESIS) {
+            applied = Some(color);
+            for config in &mut configs {
+                config.color = color;
+                if let Err(err) = writer.write(config) {
+                    eprintln!("\x1b[31mError:\x1b[0m {err}");
+                    applied = None;
+                }
+            }
+        }
+
+        thread::sleep(interval.saturating_sub(start.elapsed()));
+    }
+}
+
+/// Largest difference between the channels of two colors.
+fn color_distance(a: Rgb, b: Rgb) -> u8 {
+    a.r.abs_diff(b.r).max(a.g.abs_diff(b.g)).max(a.b.abs_diff(b.b))
+}
+
+/// Screen capture backend.
+enum Capture {
+    /// Single screenshots using `grim`.
+    Wayland { region: Option<Region> },
+    /// Continuous PPM stream from `ffmpeg`, with only the latest frame being
+    /// kept.
+    X11 { receiver: Receiver<Result<Frame, String>>, _ffmpeg: Child },
+}
+
+impl Capture {
+    fn new(region: Option<Region>, fps: u16) -> Result<Self, Box<dyn Error>> {
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            return Ok(Capture::Wayland { region });
+        }
+
+        let display = env::var("DISPLAY").map_err(|_| "no Wayland or X11 display found")?;
+
+        let mut ffmpeg = Process::new("ffmpeg");
+        ffmpeg.args(["-loglevel", "error", "-f", "x11grab"]);
+        ffmpeg.args(["-framerate", &fps.to_string()]);
+        let input = match region {
+            Some(region) => {
+                ffmpeg.args(["-video_size", &format!("{}x{}", region.width, region.height)]);
+                format!("{display}+{},{}", region.x, region.y)
+            },
+            None => display,
+        };
+        let mut ffmpeg = ffmpeg
+            .args(["-i", &input, "-f", "image2pipe", "-vcodec", "ppm", "-"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("unable to start ffmpeg: {err}"))?;
+
+        let mut stdout = BufReader::new(ffmpeg.stdout.take().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || loop {
+            let frame = Frame::read(&mut stdout).map_err(|err| err.to_string());
+            let failed = frame.is_err();
+            if sender.send(frame).is_err() || failed {
+                break;
+            }
+        });
+
+        Ok(Capture::X11 { receiver, _ffmpeg: ffmpeg })
+    }
+
+    /// Get the most recent frame.
+    fn frame(&mut self) -> Result<Frame, Box<dyn Error>> {
+        match self {
+            Capture::Wayland { region } => {
+                let mut grim = Process::new("grim");
+                grim.args(["-t", "ppm"]);
+                if let Some(region) = region {
+                    let geometry =
+                        format!("{},{} {}x{}", region.x, region.y, region.width, region.height);
+                    grim.args(["-g", &geometry]);
+                }
+
+                let output = grim
+                    .arg("-")
+                    .stderr(Stdio::inherit())
+                    .output()
+                    .map_err(|err| format!("unable to run grim: {err}"))?;
+                if !output.status.success() {
+                    return Err(format!("grim failed: {}", output.status).into());
+                }
+
+                Frame::read(&mut output.stdout.as_slice())
+            },
+            Capture::X11 { receiver, .. } => {
+                let mut frame = receiver.recv().map_err(|_| "screen capture stopped")?;
+                while let Ok(newer) = receiver.try_recv() {
+                    frame = newer;
+                }
+                Ok(frame?)
+            },
+        }
+    }
+}
+
+/// Captured RGB image.
+struct Frame {
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Read a binary PPM image.
+    fn read(reader: &mut impl BufRead) -> Result<Self, Box<dyn Error>> {
+        let mut header = Vec::new();
+        while header.len() < 4 {
+            let token = read_token(reader)?;
+            if token.is_empty() {
+                return Err("unexpected end of image stream".into());
+            }
+            header.push(token);
+        }
+
+        let number = |token: &str| usize::from_str(token).map_err(|_| "invalid PPM header");
+        let (width, height, max) = (number(&header[1])?, number(&header[2])?, number(&header[3])?);
+        if header[0] != "P6" || max != 255 {
+            return Err("unsupported image format".into());
+        }
+
+        let mut pixels = vec![0; width * height * 3];
+        reader.read_exact(&mut pixels)?;
+
+        Ok(Self { pixels })
+    }
+
+    /// Get the average of the most common color bucket.
+    ///
+    /// Colors are bucketed by their 4 most significant bits per channel, so
+    /// small areas with a vibrant color don't get lost in the average.
+    fn dominant_color(&self) -> Rgb {
+        let bucket = |pixel: &[u8]| {
+            ((pixel[0] >> 4) as usize) << 8
+                | ((pixel[1] >> 4) as usize) << 4
+                | (pixel[2] >> 4) as usize
+        };
+
+        let pixel_count = self.pixels.len() / 3;
+        let step = (pixel_count / SAMPLE_PIXELS).max(1);
+        let samples = || self.pixels.chunks_exact(3).step_by(step);
+
+        // Ignore dark pixels, unless the entire screen is dark.
+        let is_bright = |pixel: &[u8]| pixel.iter().any(|channel| *channel > DARK_THRESHOLD);
+        let only_bright = samples().any(is_bright);
+        let pixels = || samples().filter(move |pixel| !only_bright || is_bright(pixel));
+
+        let mut counts = vec![0u32; 1 << 12];
+        for pixel in pixels() {
+            counts[bucket(pixel)] += 1;
+        }
+        let dominant = (0..counts.len()).max_by_key(|i| counts[*i]).unwrap_or(0);
+
+        let mut sum = [0u64; 3];
+        let mut count = 0;
+        for pixel in pixels().filter(|pixel| bucket(pixel) == dominant) {
+            for (sum, channel) in sum.iter_mut().zip(pixel) {
+                *sum += *channel as u64;
+            }
+            count += 1;
+        }
+
+        if count == 0 {
+            return Rgb::default();
+        }
+
+        Rgb { r: (sum[0] / count) as u8, g: (sum[1] / count) as u8, b: (sum[2] / count) as u8 }
+    }
+}
+
+/// Read a whitespace-separated PPM header token.
+fn read_token(reader: &mut impl BufRead) -> Result<String, Box<dyn Error>> {
+    let mut token = String::new();
+    let mut byte = [0];
+    loop {
+        if reader.read(&mut byte)? == 0 {
+            return Ok(token);
+        }
+
+        match byte[0] {
+            // Skip comments until the end of the line.
+            b'#' if token.is_empty() => {
+                reader.read_until(b'\n', &mut Vec::new())?;
+            },
+            byte if byte.is_ascii_whitespace() => {
+                if !token.is_empty() {
+                    return Ok(token);
+                }
+            },
+            byte => token.push(byte as char),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ppm_dominant_color() {
+        let mut image = b"P6\n# comment\n3 1\n255\n".to_vec();
+        image.extend_from_slice(&[0, 0, 0, 200, 10, 10, 206, 12, 14]);
+
+        let frame = Frame::read(&mut image.as_slice()).unwrap();
+        assert_eq!(frame.dominant_color(), Rgb { r: 203, g: 11, b: 12 });
+    }
+}
@@ -0,0 +1,106 @@
+//! Systemd service for restoring the lighting configuration at boot.
+//!
+//! Installing the service stores the CLI config in a state file and creates a
+//! oneshot unit which applies it with `rgbfusion --config <state file>`.
+
+use std::error::Error;
+use std::path::Path;
+use std::process::Command as Process;
+use std::{env, fs};
+
+use clap::{crate_name, Arg, ArgAction, ArgMatches, Command};
+
+use crate::config_file::ConfigFile;
+use crate::Config;
+
+/// Name of the installed systemd unit.
+const UNIT_NAME: &str = "rgbfusion.service";
+
+/// Get clap CLI parameters for the install-service subcommand.
+pub fn subcommand() -> Command {
+    Command::new("install-service")
+        .about("Install a systemd service restoring the zone at boot")
+        .arg(
+            Arg::new("user")
+                .help("Install a user service instead of a system service")
+                .long("user")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+/// Run the install-service subcommand.
+pub fn install_service(matches: &ArgMatches) {
+    if let Err(err) = install(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Store the CLI config and install the service.
+fn install(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let user = matches.get_flag("user");
+    let (state_path, unit_path) = if user {
+        let state_dir = crate::state_dir().ok_or("unable to locate state directory")?;
+        let config_dir = crate::config_dir().ok_or("unable to locate config directory")?;
+        // User units are stored in `$XDG_CONFIG_HOME/systemd/user`.
+        let unit_dir = config_dir.parent().unwrap().join("systemd/user");
+        (state_dir.join("boot.toml"), unit_dir.join(UNIT_NAME))
+    } else {
+        let state_dir = Path::new("/var/lib").join(crate_name!());
+        (state_dir.join("boot.toml"), Path::new("/etc/systemd/system").join(UNIT_NAME))
+    };
+
+    let configs = Config::from_cli(matches)?;
+    let device = configs[0].device;
+
+    // Add the zones to the existing configuration, unless the device changed.
+    let mut file = match ConfigFile::load(&state_path) {
+        Ok(file) if file.device == Some(device) => file,
+        _ => ConfigFile::new(device),
+    };
+    for config in configs {
+        file.insert(config);
+    }
+    file.save(&state_path)?;
+
+    let executable = env::current_exe()?;
+    fs::create_dir_all(unit_path.parent().unwrap())?;
+    fs::write(&unit_path, unit(&executable, &state_path, user))
+        .map_err(|err| format!("unable to write {}: {err}", unit_path.display()))?;
+
+    systemctl(user, &["daemon-reload"])?;
+    systemctl(user, &["enable", UNIT_NAME])?;
+
+    println!("\x1b[32mInstalled {}.\x1b[0m", unit_path.display());
+    println!("Zone configurations are restored at boot from {}.", state_path.display());
+
+    Ok(())
+}
+
+/// Generate the systemd unit.
+fn unit(executable: &Path, state_path: &Path, user: bool) -> String {
+    let target = if user { "default.target" } else { "multi-user.target" };
+    format!(
+        "[Unit]\nDescription=Restore RGB lighting \
+         configuration\n\n[Service]\nType=oneshot\nExecStart=\"{}\" --config \
+         \"{}\"\n\n[Install]\nWantedBy={target}\n",
+        executable.display(),
+        state_path.display(),
+    )
+}
+
+/// Run a systemctl command.
+fn systemctl(user: bool, args: &[&str]) -> Result<(), Box<dyn Error>> {
+    let mut command = Process::new("systemctl");
+    if user {
+        command.arg("--user");
+    }
+
+    let status =
+        command.args(args).status().map_err(|err| format!("unable to run systemctl: {err}"))?;
+
+    if !status.success() {
+        return Err(format!("systemctl {} failed: {status}", args.join(" ")).into());
+    }
+
+    Ok(())
+}
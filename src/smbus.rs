@@ -0,0 +1,148 @@
+//! SMBus access through the Linux i2c-dev interface.
+//!
+//! Requires the `i2c-dev` kernel module, which exposes every bus as
+//! `/dev/i2c-N`.
+
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+/// Select the target address of a bus.
+const I2C_SLAVE: u64 = 0x0703;
+
+/// Perform an SMBus transfer.
+const I2C_SMBUS: u64 = 0x0720;
+
+const I2C_SMBUS_READ: u8 = 1;
+const I2C_SMBUS_WRITE: u8 = 0;
+const I2C_SMBUS_BYTE_DATA: u32 = 2;
+const I2C_SMBUS_WORD_DATA: u32 = 3;
+
+/// Data of a single SMBus transfer.
+#[repr(C)]
+union SmbusData {
+    byte: u8,
+    word: u16,
+    block: [u8; 34],
+}
+
+/// Argument of the `I2C_SMBUS` ioctl.
+#[repr(C)]
+struct SmbusIoctlData {
+    read_write: u8,
+    command: u8,
+    size: u32,
+    data: *mut SmbusData,
+}
+
+/// Devices at fixed addresses of an SMBus adapter.
+///
+/// Writes are sent to every address, which allows controlling identical
+/// devices like DRAM sticks together.
+pub struct SmbusDevice {
+    file: File,
+    addresses: Vec<u8>,
+}
+
+impl SmbusDevice {
+    /// Open the devices at the addresses of a bus.
+    pub fn open(bus: &Path, addresses: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(bus)
+            .map_err(|err| format!("unable to open {}: {err}", bus.display()))?;
+
+        let device = Self { file, addresses: addresses.to_vec() };
+        for address in addresses {
+            device
+                .select(*address)
+                .map_err(|err| format!("unable to select address 0x{address:02x}: {err}"))?;
+        }
+
+        Ok(device)
+    }
+
+    /// Read a byte from a command register of the first device.
+    pub fn read_byte_data(&self, command: u8) -> io::Result<u8> {
+        let mut data = SmbusData { block: [0; 34] };
+        self.select(self.addresses[0])?;
+        self.transfer(I2C_SMBUS_READ, command, I2C_SMBUS_BYTE_DATA, &mut data)?;
+        Ok(unsafe { data.byte })
+    }
+
+    /// Write a byte to a command register of every device.
+    pub fn write_byte_data(&self, command: u8, value: u8) -> io::Result<()> {
+        for address in &self.addresses {
+            self.write_byte_data_to(*address, command, value)?;
+        }
+        Ok(())
+    }
+
+    /// Write a byte to a command register of a single device.
+    pub fn write_byte_data_to(&self, address: u8, command: u8, value: u8) -> io::Result<()> {
+        if !self.addresses.contains(&address) {
+            let message = format!("no device at address 0x{address:02x}");
+            return Err(io::Error::new(io::ErrorKind::NotFound, message));
+        }
+
+        let mut data = SmbusData { byte: value };
+        self.select(address)?;
+        self.transfer(I2C_SMBUS_WRITE, command, I2C_SMBUS_BYTE_DATA, &mut data)
+    }
+
+    /// Write a word to a command register of every device.
+    pub fn write_word_data(&self, command: u8, value: u16) -> io::Result<()> {
+        for address in &self.addresses {
+            let mut data = SmbusData { word: value };
+            self.select(*address)?;
+            self.transfer(I2C_SMBUS_WRITE, command, I2C_SMBUS_WORD_DATA, &mut data)?;
+        }
+        Ok(())
+    }
+
+    /// Direct transfers to a device address.
+    fn select(&self, address: u8) -> io::Result<()> {
+        let address = address as libc::c_ulong;
+        match unsafe { libc::ioctl(self.file.as_raw_fd(), I2C_SLAVE as _, address) } {
+            result if result < 0 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+
+    fn transfer(
+        &self,
+        read_write: u8,
+        command: u8,
+        size: u32,
+        data: &mut SmbusData,
+    ) -> io::Result<()> {
+        let mut args = SmbusIoctlData { read_write, command, size, data };
+        match unsafe { libc::ioctl(self.file.as_raw_fd(), I2C_SMBUS as _, &mut args) } {
+            result if result < 0 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Get the device nodes of all adapters whose name starts with a prefix.
+pub fn adapters(prefixes: &[&str]) -> Vec<PathBuf> {
+    let entries = match fs::read_dir("/sys/class/i2c-dev") {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut adapters: Vec<_> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = fs::read_to_string(entry.path().join("name")).unwrap_or_default();
+            prefixes.iter().any(|prefix| name.starts_with(prefix))
+        })
+        .map(|entry| Path::new("/dev").join(entry.file_name()))
+        .collect();
+    adapters.sort();
+
+    adapters
+}
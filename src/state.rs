@@ -0,0 +1,151 @@
+//! Last applied zone configs.
+//!
+//! Every config applied from the CLI is recorded in
+//! `~/.local/state/rgbfusion/state.toml`, keyed by device and zone, so
+//! transitions can start from the colors currently displayed.
+//!
+//! Previous states are kept in `history.toml` next to it, allowing changes to
+//! be undone.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config_file::{ConfigFile, DeviceConfigs};
+use crate::{Config, Device};
+
+/// Maximum number of previous states kept for undo.
+const HISTORY_LENGTH: usize = 10;
+
+/// Previously applied states, oldest first.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct History {
+    #[serde(default, rename = "state")]
+    states: Vec<ConfigFile>,
+}
+
+impl History {
+    fn load(path: &PathBuf) -> Result<Self, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)
+                .map_err(|err| format!("invalid history {}: {err}", path.display()))?),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    fn save(&self, path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        fs::write(path, toml::to_string(self)?)
+            .map_err(|err| format!("unable to write {}: {err}", path.display()).into())
+    }
+}
+
+/// Get the path of the state file.
+fn path() -> Option<PathBuf> {
+    Some(crate::state_dir()?.join("state.toml"))
+}
+
+/// Load the last applied configs of every device, least recently changed first.
+fn load_state(path: &Path) -> Result<DeviceConfigs, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(DeviceConfigs::default());
+    }
+
+    DeviceConfigs::load(path)
+}
+
+/// Get the path of the state history.
+fn history_path() -> Option<PathBuf> {
+    Some(crate::state_dir()?.join("history.toml"))
+}
+
+/// Get the last applied configs of a device.
+pub fn load(device: Device) -> Vec<Config> {
+    let state = path().and_then(|path| load_state(&path).ok());
+    let file = state.and_then(|mut state| state.take(device));
+
+    file.and_then(|file| file.into_configs(device).ok()).unwrap_or_default()
+}
+
+/// Get the configs of the most recently changed device.
+pub fn last() -> Option<(Device, Vec<Config>)> {
+    let file = load_state(&path()?).ok()?.files.pop()?;
+    let device = file.device?;
+    Some((device, file.into_configs(device).ok()?))
+}
+
+/// Record applied configs, replacing the previous state of their zones.
+pub fn record(configs: &[Config]) -> Result<(), Box<dyn Error>> {
+    let mut devices = Vec::new();
+    for config in configs {
+        if !devices.contains(&config.device) {
+            devices.push(config.device);
+        }
+    }
+
+    for device in devices {
+        record_device(device, configs)?;
+    }
+
+    Ok(())
+}
+
+/// Record the applied configs of a single device.
+fn record_device(device: Device, configs: &[Config]) -> Result<(), Box<dyn Error>> {
+    let path = path().ok_or("unable to locate state directory")?;
+    let mut state = load_state(&path)?;
+    let previous = state.take(device);
+    let mut file = previous.clone().unwrap_or_else(|| ConfigFile::new(device));
+
+    for config in configs.iter().filter(|config| config.device == device) {
+        file.insert(config.clone());
+    }
+
+    let changed = match &previous {
+        Some(previous) => toml::to_string(previous)? != toml::to_string(&file)?,
+        None => false,
+    };
+
+    state.files.push(file);
+    state.save(&path)?;
+
+    // Keep the replaced state for undo, unless nothing changed.
+    let previous = match previous {
+        Some(previous) if changed => previous,
+        _ => return Ok(()),
+    };
+
+    let history_path = history_path().ok_or("unable to locate state directory")?;
+    let mut history = History::load(&history_path)?;
+    history.states.push(previous);
+    let excess = history.states.len().saturating_sub(HISTORY_LENGTH);
+    history.states.drain(..excess);
+    history.save(&history_path)
+}
+
+/// Get the configs of the state before the last change.
+pub fn previous() -> Result<Vec<Config>, Box<dyn Error>> {
+    let history_path = history_path().ok_or("unable to locate state directory")?;
+    let file = History::load(&history_path)?.states.pop().ok_or("no previous state to undo")?;
+    let device = file.device.ok_or("previous state has no device")?;
+    file.into_configs(device)
+}
+
+/// Restore the state before the last change, removing it from the history.
+pub fn undo() -> Result<(), Box<dyn Error>> {
+    let path = path().ok_or("unable to locate state directory")?;
+    let history_path = history_path().ok_or("unable to locate state directory")?;
+
+    let mut history = History::load(&history_path)?;
+    let file = history.states.pop().ok_or("no previous state to undo")?;
+    let device = file.device.ok_or("previous state has no device")?;
+
+    let mut state = load_state(&path)?;
+    state.take(device);
+    state.files.push(file);
+    state.save(&path)?;
+
+    history.save(&history_path)
+}
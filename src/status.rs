@@ -0,0 +1,70 @@
+//! Report of the current lighting configuration.
+
+use std::error::Error;
+
+use clap::{ArgMatches, Command};
+
+use crate::output::{self, ConfigResult};
+use crate::reactive::Writer;
+use crate::{effect, state, Device, Palette};
+
+/// Get clap CLI parameters for the status subcommand.
+pub fn subcommand() -> Command {
+    Command::new("status").about("Show the last applied configuration of every zone")
+}
+
+/// Run the status subcommand.
+pub fn status(matches: &ArgMatches) {
+    if let Err(err) = print(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Print the last applied configs, read back from the device where possible.
+fn print(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let (device, configs) = match (matches.get_one::<Device>("device"), state::last()) {
+        (Some(device), _) => (*device, state::load(*device)),
+        (None, Some(last)) => last,
+        (None, None) => (Device::default(), Vec::new()),
+    };
+
+    if configs.is_empty() {
+        if !output::json() {
+            println!("No configuration has been applied yet.");
+        }
+        return Ok(());
+    }
+
+    if !output::json() {
+        println!("{} ({device:?})", device.controller().name());
+    }
+
+    // Only open the device for controllers which can report their state.
+    let read_back = device.controller().supports_read_back();
+
+    let mut writer = Writer::default();
+    for config in &configs {
+        let verification =
+            if read_back && !effect::is_emulated(config) { writer.verify(config) } else { None };
+
+        if output::json() {
+            output::print(&ConfigResult::new(config, &Ok(()), &verification));
+            continue;
+        }
+
+        let color = match config.palette.as_slice() {
+            [] => config.color.to_string(),
+            palette => Palette(palette.to_vec()).to_string(),
+        };
+        let verified = match verification {
+            Some(Ok(())) => "\x1b[32m(matches device)\x1b[0m".into(),
+            Some(Err(err)) => format!("\x1b[31m(read-back failed: {err})\x1b[0m"),
+            None => String::new(),
+        };
+        let zone = format!("{:?}", config.zone);
+        let effect = format!("{:?}", config.effect);
+        println!("  {zone:<8} {effect:<10} {color} {verified}");
+    }
+
+    Ok(())
+}
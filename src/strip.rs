@@ -0,0 +1,166 @@
+//! Per-zone LED strip channel layouts.
+//!
+//! Strips on ARGB headers don't all expect their channels in RGB order. The
+//! layout of each zone can be configured in `~/.config/rgbfusion/strips.toml`:
+//!
+//! ```toml
+//! [x670ef]
+//! header0 = "grb"
+//! ```
+//!
+//! RGBW strips show the white component of a color on their white LED, which
+//! requires a controller definition with a `{w}` placeholder.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{Device, Rgb, Zone};
+
+/// Channel layout of an LED strip.
+#[derive(ValueEnum, Serialize, Deserialize, PartialEq, Eq, Debug, Default, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum StripType {
+    #[default]
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+    Rgbw,
+    Grbw,
+}
+
+impl StripType {
+    /// Check if the strip has a separate white channel.
+    pub fn has_white(self) -> bool {
+        matches!(self, StripType::Rgbw | StripType::Grbw)
+    }
+
+    /// Map a color onto the strip's channels.
+    ///
+    /// Returns the color in the order expected by the controller, followed by
+    /// the value of the white channel.
+    pub fn encode(self, mut color: Rgb) -> (Rgb, u8) {
+        let mut white = 0;
+        if self.has_white() {
+            white = color.r.min(color.g).min(color.b);
+            color = Rgb { r: color.r - white, g: color.g - white, b: color.b - white };
+        }
+
+        let Rgb { r, g, b } = color;
+        let color = match self {
+            StripType::Rgb | StripType::Rgbw => color,
+            StripType::Rbg => Rgb { r, g: b, b: g },
+            StripType::Grb | StripType::Grbw => Rgb { r: g, g: r, b },
+            StripType::Gbr => Rgb { r: g, g: b, b: r },
+            StripType::Brg => Rgb { r: b, g: r, b: g },
+            StripType::Bgr => Rgb { r: b, g, b: r },
+        };
+
+        (color, white)
+    }
+}
+
+/// Strip connected to an addressable header, as configured on its controller.
+///
+/// The strip type is not part of the header configuration, since colors are
+/// already reordered using the zone's strip type.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Default, Copy, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HeaderConfig {
+    pub led_count: u16,
+}
+
+/// Strip types of every device, by zone name.
+type Strips = BTreeMap<String, BTreeMap<String, StripType>>;
+
+/// Strip type of a single zone.
+struct ZoneStrip {
+    device: Device,
+    zone: Zone,
+    strip_type: StripType,
+}
+
+/// Strip types loaded from the configuration directory.
+static STRIPS: OnceLock<Vec<ZoneStrip>> = OnceLock::new();
+
+/// Get the strip types of all zones.
+fn strips() -> &'static [ZoneStrip] {
+    STRIPS.get_or_init(|| match load() {
+        Ok(strips) => strips,
+        Err(err) => {
+            eprintln!("Ignoring strip configuration: {err}");
+            Vec::new()
+        },
+    })
+}
+
+/// Get the path of the strip configuration file.
+fn path() -> Option<PathBuf> {
+    Some(crate::config_dir()?.join("strips.toml"))
+}
+
+/// Read the strip configuration file.
+fn read() -> Result<Strips, Box<dyn Error>> {
+    match path().map(fs::read_to_string) {
+        Some(Ok(content)) => Ok(toml::from_str(&content)?),
+        _ => Ok(BTreeMap::new()),
+    }
+}
+
+/// Load the strip configuration file.
+fn load() -> Result<Vec<ZoneStrip>, Box<dyn Error>> {
+    let devices = read()?;
+
+    let mut strips = Vec::new();
+    for (device, zones) in devices {
+        let device = device.parse::<Device>()?;
+        for (zone, strip_type) in zones {
+            let zone = Zone::from_str(&zone, true).map_err(|_| format!("unknown zone: {zone}"))?;
+            strips.push(ZoneStrip { device, zone, strip_type });
+        }
+    }
+
+    Ok(strips)
+}
+
+/// Store the strip type connected to a zone.
+pub fn save(device: Device, zone: Zone, strip_type: StripType) -> Result<(), Box<dyn Error>> {
+    let path = path().ok_or("unable to locate config directory")?;
+
+    let mut devices = read()?;
+    let zone = zone.to_possible_value().unwrap().get_name().to_owned();
+    devices.entry(format!("{device:?}")).or_default().insert(zone, strip_type);
+
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, toml::to_string(&devices)?)
+        .map_err(|err| format!("unable to write {}: {err}", path.display()).into())
+}
+
+/// Get the strip type connected to a zone.
+pub fn strip_type(device: Device, zone: Zone) -> StripType {
+    let strip = strips().iter().find(|strip| strip.device == device && strip.zone == zone);
+    strip.map_or_else(StripType::default, |strip| strip.strip_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_order() {
+        let color = Rgb { r: 255, g: 128, b: 64 };
+
+        assert_eq!(StripType::Rgb.encode(color), (color, 0));
+        assert_eq!(StripType::Grb.encode(color), (Rgb { r: 128, g: 255, b: 64 }, 0));
+        assert_eq!(StripType::Bgr.encode(color), (Rgb { r: 64, g: 128, b: 255 }, 0));
+        assert_eq!(StripType::Grbw.encode(color), (Rgb { r: 64, g: 191, b: 0 }, 64));
+    }
+}
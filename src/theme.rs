@@ -0,0 +1,121 @@
+//! User-defined named colors.
+//!
+//! Colors and palettes can be named in `~/.config/rgbfusion/colors.toml`:
+//!
+//! ```toml
+//! accent = "0x4488ff"
+//! ocean = ["navy", "teal", "aquamarine"]
+//! ```
+//!
+//! These are referenced with an `@` prefix wherever a color or palette is
+//! expected, like `--color @accent` or `--palette @ocean`.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::Rgb;
+
+/// Named colors loaded from the configuration directory.
+static THEME: OnceLock<BTreeMap<String, Vec<Rgb>>> = OnceLock::new();
+
+/// Color file entry.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Color(String),
+    Palette(Vec<String>),
+}
+
+/// Get all user-defined colors.
+fn theme() -> &'static BTreeMap<String, Vec<Rgb>> {
+    THEME.get_or_init(|| match load() {
+        Ok(theme) => theme,
+        Err(err) => {
+            eprintln!("Ignoring named colors: {err}");
+            BTreeMap::new()
+        },
+    })
+}
+
+/// Load the color file.
+fn load() -> Result<BTreeMap<String, Vec<Rgb>>, Box<dyn Error>> {
+    let path = match crate::config_dir() {
+        Some(config_dir) => config_dir.join("colors.toml"),
+        None => return Ok(BTreeMap::new()),
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(content) => parse(&content),
+        Err(_) => Ok(BTreeMap::new()),
+    }
+}
+
+/// Parse named colors from their TOML representation.
+fn parse(content: &str) -> Result<BTreeMap<String, Vec<Rgb>>, Box<dyn Error>> {
+    let entries: BTreeMap<String, Entry> = toml::from_str(content)?;
+    entries
+        .into_iter()
+        .map(|(name, entry)| {
+            let colors = match entry {
+                Entry::Color(color) => vec![color],
+                Entry::Palette(colors) if colors.is_empty() => {
+                    return Err(format!("palette '{name}' has no colors").into());
+                },
+                Entry::Palette(colors) => colors,
+            };
+
+            let colors = colors
+                .iter()
+                .map(|color| match color.strip_prefix('@') {
+                    Some(_) => Err(format!("'{name}' can't reference other named colors")),
+                    None => Rgb::from_str(color),
+                })
+                .collect::<Result<_, _>>()?;
+
+            Ok((name, colors))
+        })
+        .collect()
+}
+
+/// Get all colors of a named color or palette.
+pub fn colors(name: &str) -> Result<&'static [Rgb], String> {
+    match theme().get(name) {
+        Some(colors) => Ok(colors),
+        None => Err(format!("unknown color '@{name}', not defined in colors.toml")),
+    }
+}
+
+/// Get a named color.
+pub fn color(name: &str) -> Result<Rgb, String> {
+    match colors(name)? {
+        [color] => Ok(*color),
+        _ => Err(format!("'@{name}' is a palette, not a single color")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_colors() {
+        let theme = parse(
+            r#"
+                accent = "0x4488ff"
+                ocean = ["navy", "teal"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(theme["accent"], vec![Rgb { r: 0x44, g: 0x88, b: 0xff }]);
+        assert_eq!(theme["ocean"], vec![Rgb::from_hex(0x000080), Rgb::from_hex(0x008080)]);
+
+        assert!(parse(r#"primary = "@accent""#).is_err());
+        assert!(parse("empty = []").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Thermaltake Riing Plus RGB control.
+//!
+//! The five ports of the controller are mapped to the `header0`, `header1`,
+//! `io`, `cpu` and `audio` zones in order.
+
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::controller::{HidController, Packet};
+use crate::{strip, Config, Duration, Effect, Zone};
+
+/// Size of a single HID report, including the report ID.
+const PACKET_SIZE: usize = 65;
+
+/// Number of LEDs colored on every port.
+const PORT_LEDS: usize = 20;
+
+/// Mode showing a single color on the whole port.
+const FULL_MODE: u8 = 0x19;
+
+pub struct ThermaltakeRiing;
+
+impl HidController for ThermaltakeRiing {
+    fn name(&self) -> &str {
+        "Thermaltake Riing Plus"
+    }
+
+    fn vendor_id(&self) -> Option<u16> {
+        Some(0x264A)
+    }
+
+    fn product_id(&self) -> Option<u16> {
+        Some(0x1FA5)
+    }
+
+    fn product_ids(&self) -> Vec<u16> {
+        // Each controller uses one of 16 IDs selected by its DIP switches.
+        (0x1FA5..=0x1FB4).collect()
+    }
+
+    fn supported_zones(&self) -> &[Zone] {
+        &[Zone::Header0, Zone::Header1, Zone::Io, Zone::Cpu, Zone::Audio]
+    }
+
+    fn supported_effects(&self) -> &[Effect] {
+        &[
+            Effect::Off,
+            Effect::Static,
+            Effect::Pulse,
+            Effect::Flash,
+            Effect::Cycle,
+            Effect::Rainbow,
+            Effect::ChaseFade,
+            Effect::Chase,
+        ]
+    }
+
+    fn supports_brightness(&self) -> bool {
+        false
+    }
+
+    fn config_bytes(&self, config: &Config) -> Result<Vec<Packet>, Box<dyn Error>> {
+        let (color, _) = strip::strip_type(config.device, config.zone).encode(config.color);
+
+        // The controller has no off mode, so it is emulated with a black color.
+        let (mode, color) = match config.effect {
+            Effect::Off => (FULL_MODE, Default::default()),
+            Effect::Static => (FULL_MODE, color),
+            effect => (effect_bytes(effect)? + speed_bytes(config.fade_in_time), color),
+        };
+
+        // Initialize the controller for receiving colors.
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+        buf.put_slice(&[0x00, 0xfe, 0x33]);
+        buf.resize(PACKET_SIZE, 0);
+        let init_packet = buf.freeze().into();
+
+        let mut buf = BytesMut::with_capacity(PACKET_SIZE);
+        buf.put_slice(&[0x00, 0x32, 0x52, port_bytes(config.zone)?, mode]);
+
+        // Colors are sent in GRB order.
+        for _ in 0..PORT_LEDS {
+            buf.put_slice(&[color.g, color.r, color.b]);
+        }
+
+        let color_packet = buf.freeze().into();
+
+        Ok(vec![init_packet, color_packet])
+    }
+}
+
+/// Convert zone to the controller's port.
+fn port_bytes(zone: Zone) -> Result<u8, Box<dyn Error>> {
+    match zone {
+        Zone::Header0 => Ok(0x01),
+        Zone::Header1 => Ok(0x02),
+        Zone::Io => Ok(0x03),
+        Zone::Cpu => Ok(0x04),
+        Zone::Audio => Ok(0x05),
+        zone => Err(format!("unsupported zone: {zone:?}").into()),
+    }
+}
+
+/// Convert effect type to Riing format, before adding the speed.
+fn effect_bytes(effect: Effect) -> Result<u8, Box<dyn Error>> {
+    match effect {
+        Effect::Rainbow => Ok(0x00),
+        Effect::Cycle => Ok(0x04),
+        Effect::ChaseFade => Ok(0x08),
+        Effect::Flash => Ok(0x0c),
+        Effect::Pulse => Ok(0x10),
+        Effect::Chase => Ok(0x14),
+        effect => Err(format!("unsupported effect: {effect:?}").into()),
+    }
+}
+
+/// Convert effect duration to the controller's speed, with 0 being the fastest.
+fn speed_bytes(duration: Duration) -> u8 {
+    match duration.0 {
+        1500.. => 3,
+        750..=1499 => 2,
+        250..=749 => 1,
+        _ => 0,
+    }
+}
@@ -0,0 +1,284 @@
+//! HID transcript recording and replay.
+//!
+//! With `--record <file>`, every packet written to a device is appended to a
+//! transcript file as JSON line:
+//!
+//! ```json
+//! {"timestamp":1700000000000,"device":"trx40","vendor_id":1165,"product_id":33431,"transport":"write","bytes":"cc2001..."}
+//! ```
+//!
+//! Transcripts can be sent to a device again with `rgbfusion replay <file>`,
+//! keeping the original timing between packets.
+
+use std::collections::hash_map::{Entry as MapEntry, HashMap};
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use clap::{value_parser, Arg, ArgMatches, Command};
+use hidapi::HidApi;
+use serde::{Deserialize, Serialize};
+
+use crate::controller::{DeviceHandle, Packet, Transport};
+use crate::{definition, dry_run, Device};
+
+/// Transcript file opened for recording.
+static RECORDING: OnceLock<Option<Mutex<File>>> = OnceLock::new();
+
+/// Get clap CLI parameters for the replay subcommand.
+pub fn subcommand() -> Command {
+    Command::new("replay").about("Send a recorded HID transcript to its devices").arg(
+        Arg::new("file")
+            .help("Transcript file")
+            .required(true)
+            .value_parser(value_parser!(PathBuf)),
+    )
+}
+
+/// Run the replay subcommand.
+pub fn replay(matches: &ArgMatches) {
+    if let Err(err) = play(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Send all packets of a transcript.
+fn play(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let path = matches.get_one::<PathBuf>("file").unwrap();
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("unable to read {}: {err}", path.display()))?;
+    let entries = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(Entry::from_line)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("invalid transcript {}: {err}", path.display()))?;
+
+    let options = crate::hid_options();
+    let api = HidApi::new().map_err(|err| format!("unable to access HID: {err}"))?;
+
+    let mut devices = HashMap::new();
+    let mut previous = None;
+    for entry in &entries {
+        // Keep the original delay between packets.
+        if let Some(previous) = previous {
+            thread::sleep(Duration::from_millis(entry.timestamp.saturating_sub(previous)));
+        }
+        previous = Some(entry.timestamp);
+
+        let packet = entry.packet()?;
+
+        if options.dry_run {
+            println!("{} ({:?}, {} bytes):", entry.device, packet.transport, packet.bytes.len());
+            print!("{}", dry_run::hex_dump(&packet.bytes));
+            continue;
+        }
+
+        let (Some(vendor_id), Some(product_id)) = (entry.vendor_id, entry.product_id) else {
+            return Err(format!("unable to replay {}: not a HID device", entry.device).into());
+        };
+
+        // Open every device only once.
+        let device = match devices.entry((vendor_id, product_id)) {
+            MapEntry::Occupied(device) => device.into_mut(),
+            MapEntry::Vacant(vacant) => {
+                let device = options
+                    .selector
+                    .open(&api, vendor_id, product_id)
+                    .map_err(|err| format!("unable to open {}: {err}", entry.device))?;
+                vacant.insert(DeviceHandle::hid(device))
+            },
+        };
+
+        crate::send_with_timeout(device, &packet)
+            .map_err(|err| format!("unable to write packet: {err}"))?;
+    }
+
+    println!("\x1b[32mReplayed {} packets.\x1b[0m", entries.len());
+
+    Ok(())
+}
+
+/// Packet written to a device.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    /// Milliseconds since the Unix epoch.
+    timestamp: u64,
+    device: String,
+    /// HID IDs, absent for SMBus devices.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vendor_id: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    product_id: Option<u16>,
+    transport: Transport,
+    /// Packet bytes in hex.
+    bytes: String,
+}
+
+impl Entry {
+    fn new(device: Device, packet: &Packet, timestamp: u64) -> Self {
+        let controller = device.controller();
+        Self {
+            timestamp,
+            device: format!("{device:?}"),
+            vendor_id: controller.vendor_id(),
+            product_id: controller.product_id(),
+            transport: packet.transport,
+            bytes: definition::hex(&packet.bytes),
+        }
+    }
+
+    /// Parse an entry from a transcript line.
+    fn from_line(line: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(line)
+    }
+
+    /// Convert the entry to a transcript line.
+    fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).unwrap();
+        line.push('\n');
+        line
+    }
+
+    /// Get the recorded packet.
+    fn packet(&self) -> Result<Packet, String> {
+        let bytes = definition::parse_hex(&self.bytes)?;
+        Ok(Packet::new(self.transport, Bytes::from(bytes)))
+    }
+}
+
+/// Append a packet written to a device to the transcript, if recording.
+pub fn record(device: Device, packet: &Packet) -> Result<(), Box<dyn Error>> {
+    let recording = RECORDING.get_or_init(|| {
+        let path = crate::hid_options().record.as_ref()?;
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Some(Mutex::new(file)),
+            Err(err) => {
+                eprintln!("\x1b[31mError:\x1b[0m unable to open {}: {err}", path.display());
+                None
+            },
+        }
+    });
+
+    let file = match recording {
+        Some(file) => file,
+        None => return Ok(()),
+    };
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let entry = Entry::new(device, packet, timestamp.as_millis() as u64);
+    file.lock().unwrap().write_all(entry.to_line().as_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::Path;
+
+    use clap::ValueEnum;
+
+    use super::*;
+    use crate::strip::HeaderConfig;
+    use crate::{Config, Rgb, RgbDevice, Zone};
+
+    /// Compare the encoding of all builtin controllers against their golden
+    /// transcripts in `tests/golden`.
+    ///
+    /// Transcripts cover effects, per-LED colors and header configuration.
+    ///
+    /// Run with `UPDATE_GOLDEN=1` to update the transcripts after intentional
+    /// protocol changes.
+    #[test]
+    fn golden_transcripts() {
+        for device in RgbDevice::value_variants() {
+            let device = Device::from(*device);
+            let controller = device.controller();
+
+            let mut transcript = String::new();
+            for zone in controller.supported_zones() {
+                for effect in controller.supported_effects() {
+                    let config = Config {
+                        device,
+                        zone: *zone,
+                        effect: *effect,
+                        color: Rgb::from_hex(0x123456),
+                        color2: Rgb::from_hex(0xabcdef),
+                        ..Default::default()
+                    };
+
+                    push_packets(
+                        &mut transcript,
+                        device,
+                        controller.config_bytes(&config).unwrap(),
+                    );
+                }
+
+                // Enough LEDs to span multiple packets on most controllers.
+                let led_count = controller.max_leds().unwrap_or(usize::MAX).min(40);
+                let colors: Vec<_> =
+                    (0..led_count as u8).map(|i| Rgb { r: i, g: 0x80, b: 0xff - i }).collect();
+                if let Some(Ok(packets)) = controller.led_bytes(*zone, &colors) {
+                    push_packets(&mut transcript, device, packets);
+                }
+            }
+
+            let headers: Vec<_> = [(Zone::Header0, 32), (Zone::Header1, 64)]
+                .iter()
+                .filter(|(zone, _)| controller.supported_zones().contains(zone))
+                .map(|&(zone, led_count)| (zone, HeaderConfig { led_count }))
+                .collect();
+            if let Some(packets) = controller.header_bytes(&headers) {
+                push_packets(&mut transcript, device, packets.unwrap());
+            }
+
+            push_packets(&mut transcript, device, controller.commit_bytes());
+
+            let golden = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/golden")
+                .join(format!("{device:?}.jsonl"));
+            if env::var_os("UPDATE_GOLDEN").is_some() {
+                fs::write(&golden, &transcript).unwrap();
+            }
+
+            let expected = fs::read_to_string(&golden).unwrap();
+            assert!(expected == transcript, "encoding of {:?} changed", device);
+        }
+    }
+
+    /// Check the IT8297 golden transcript against the packets OpenRGB's
+    /// RGBFusion2USBController sends for the same LEDs.
+    #[test]
+    fn openrgb_it8297() {
+        let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/it8297.jsonl");
+        let transcript = fs::read_to_string(golden).unwrap();
+        let packets: Vec<_> =
+            transcript.lines().map(|line| Entry::from_line(line).unwrap().bytes).collect();
+
+        // Packets are zero-padded to 64 bytes.
+        let packet = |bytes: &str| format!("{bytes:0<128}");
+
+        // DisableBuiltinEffect for D_LED1.
+        assert!(packets.contains(&packet("cc3201")));
+        // SetStripColors for the last two of 40 LEDs on D_LED1, at byte offset 114.
+        assert!(packets.contains(&packet("cc587200062680d92780d8")));
+        // SetLedCount with LEDS_32 on D_LED1 and LEDS_64 on D_LED2.
+        assert!(packets.contains(&packet("cc3410")));
+    }
+
+    /// Add packets to a transcript, ensuring they survive a roundtrip.
+    fn push_packets(transcript: &mut String, device: Device, packets: Vec<Packet>) {
+        for packet in packets {
+            let entry = Entry::new(device, &packet, 0);
+            assert_eq!(Entry::from_line(&entry.to_line()).unwrap(), entry);
+            transcript.push_str(&entry.to_line());
+        }
+    }
+}
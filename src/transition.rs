@@ -0,0 +1,88 @@
+//! Smooth transitions between zone configs.
+//!
+//! Instead of switching to a new config instantly, the color and brightness
+//! are faded from the last applied state using static colors, before the new
+//! config is written.
+
+use std::error::Error;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::effect::{self, FRAME_INTERVAL};
+use crate::reactive::Writer;
+use crate::{state, Brightness, Config, Effect, Rgb};
+
+/// Fade all zones from their last applied color to the new configs.
+///
+/// Zones without a known previous state are skipped.
+pub fn fade(
+    writer: &mut Writer,
+    configs: &[Config],
+    duration: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let device = match configs.first() {
+        Some(config) => config.device,
+        None => return Ok(()),
+    };
+
+    let previous = state::load(device);
+    let transitions: Vec<_> = configs
+        .iter()
+        .filter(|config| !effect::is_emulated(config))
+        .filter_map(|config| {
+            let from = previous.iter().find(|previous| previous.zone == config.zone)?;
+            Some((config, target(from, config), target(config, from)))
+        })
+        .collect();
+
+    if transitions.is_empty() || duration.is_zero() {
+        return Ok(());
+    }
+
+    let start = Instant::now();
+    loop {
+        let position = start.elapsed().as_secs_f32() / duration.as_secs_f32();
+        if position >= 1. {
+            return Ok(());
+        }
+
+        for (config, (from_color, from_brightness), (to_color, to_brightness)) in &transitions {
+            writer.write(&Config {
+                effect: Effect::Static,
+                color: from_color.interpolate(*to_color, position),
+                max_brightness: from_brightness.interpolate(*to_brightness, position),
+                ..(*config).clone()
+            })?;
+        }
+
+        thread::sleep(FRAME_INTERVAL);
+    }
+}
+
+/// Get the static color and brightness representing a config.
+///
+/// Disabled zones keep the color of the other end of the transition, so they
+/// only fade the brightness.
+fn target(config: &Config, other: &Config) -> (Rgb, Brightness) {
+    match (config.effect, other.effect) {
+        (Effect::Off, Effect::Off) => (Rgb::default(), Brightness(0)),
+        (Effect::Off, _) => (other.color, Brightness(0)),
+        _ => (config.color, config.max_brightness),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_from_off() {
+        let red = Rgb { r: 255, g: 0, b: 0 };
+        let off = Config { effect: Effect::Off, ..Default::default() };
+        let on = Config { color: red, max_brightness: Brightness(200), ..Default::default() };
+
+        assert_eq!(target(&off, &on), (red, Brightness(0)));
+        assert_eq!(target(&on, &off), (red, Brightness(200)));
+        assert_eq!(target(&off, &off), (Rgb::default(), Brightness(0)));
+    }
+}
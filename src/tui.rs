@@ -0,0 +1,342 @@
+//! Full-screen terminal interface for live experimentation.
+//!
+//! Every change is written to the hardware immediately, the final state of
+//! all modified zones is recorded once the interface is closed.
+
+use std::error::Error;
+use std::io::{self, Read, Write};
+use std::mem;
+
+use clap::{ArgMatches, Command};
+
+use crate::reactive::Writer;
+use crate::{state, zone_defaults, Brightness, Config, Device, Duration, Effect};
+
+/// Width of the slider bars in characters.
+const SLIDER_WIDTH: usize = 24;
+
+/// Longest configurable effect timing in milliseconds.
+const MAX_TIMING: u16 = 10000;
+
+/// Get clap CLI parameters for the tui subcommand.
+pub fn subcommand() -> Command {
+    Command::new("tui").about("Configure zones interactively with live preview")
+}
+
+/// Run the tui subcommand.
+pub fn tui(matches: &ArgMatches) {
+    if let Err(err) = run(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Editable config parameter.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum Field {
+    Effect,
+    Red,
+    Green,
+    Blue,
+    MaxBrightness,
+    MinBrightness,
+    FadeIn,
+    FadeOut,
+    Hold,
+}
+
+impl Field {
+    fn label(self) -> &'static str {
+        match self {
+            Field::Effect => "Effect",
+            Field::Red => "Red",
+            Field::Green => "Green",
+            Field::Blue => "Blue",
+            Field::MaxBrightness => "Max brightness",
+            Field::MinBrightness => "Min brightness",
+            Field::FadeIn => "Fade in",
+            Field::FadeOut => "Fade out",
+            Field::Hold => "Hold",
+        }
+    }
+}
+
+/// Key pressed by the user.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    NextZone,
+    PreviousZone,
+    Quit,
+    Other,
+}
+
+impl Key {
+    /// Parse the bytes of a single key press.
+    fn parse(bytes: &[u8]) -> Self {
+        match bytes {
+            b"\x1b[A" | b"k" => Key::Up,
+            b"\x1b[B" | b"j" => Key::Down,
+            b"\x1b[D" | b"h" => Key::Left,
+            b"\x1b[C" | b"l" => Key::Right,
+            b"\t" => Key::NextZone,
+            b"\x1b[Z" => Key::PreviousZone,
+            b"q" | b"\x1b" | b"\x03" => Key::Quit,
+            _ => Key::Other,
+        }
+    }
+}
+
+/// Interface state.
+struct Tui {
+    device: Device,
+    configs: Vec<Config>,
+    modified: Vec<bool>,
+    effects: Vec<Effect>,
+    fields: Vec<Field>,
+    zone: usize,
+    field: usize,
+    status: String,
+    writer: Writer,
+}
+
+impl Tui {
+    fn new(device: Device) -> Self {
+        let controller = device.controller();
+
+        // Start from the last applied state of every zone.
+        let applied = state::load(device);
+        let configs: Vec<_> = controller
+            .supported_zones()
+            .iter()
+            .map(|zone| match applied.iter().find(|config| config.zone == *zone) {
+                Some(config) => config.clone(),
+                None => {
+                    let mut config = Config { device, zone: *zone, ..Default::default() };
+                    zone_defaults::apply(&mut config);
+                    config
+                },
+            })
+            .collect();
+
+        let mut fields = vec![Field::Effect, Field::Red, Field::Green, Field::Blue];
+        if controller.supports_brightness() {
+            fields.extend_from_slice(&[Field::MaxBrightness, Field::MinBrightness]);
+        }
+        fields.extend_from_slice(&[Field::FadeIn, Field::FadeOut, Field::Hold]);
+
+        Self {
+            modified: vec![false; configs.len()],
+            effects: controller.supported_effects().to_vec(),
+            status: String::new(),
+            writer: Writer::default(),
+            zone: 0,
+            field: 0,
+            configs,
+            device,
+            fields,
+        }
+    }
+
+    /// Handle a key press, returning `false` once the interface is closed.
+    fn handle(&mut self, key: Key) -> bool {
+        let zones = self.configs.len();
+        match key {
+            Key::Up => self.field = (self.field + self.fields.len() - 1) % self.fields.len(),
+            Key::Down => self.field = (self.field + 1) % self.fields.len(),
+            Key::NextZone => self.zone = (self.zone + 1) % zones,
+            Key::PreviousZone => self.zone = (self.zone + zones - 1) % zones,
+            Key::Left => self.adjust(-1),
+            Key::Right => self.adjust(1),
+            Key::Quit => return false,
+            Key::Other => (),
+        }
+        true
+    }
+
+    /// Change the selected field and apply the result.
+    fn adjust(&mut self, direction: i32) {
+        let config = &mut self.configs[self.zone];
+        let step = |value: u8, step: i32| (value as i32 + step * direction).clamp(0, 255) as u8;
+        let timing = |value: Duration| {
+            let value = value.0 as i32 + 100 * direction;
+            Duration(value.clamp(0, MAX_TIMING as i32) as u16)
+        };
+
+        match self.fields[self.field] {
+            Field::Effect => {
+                let index = self.effects.iter().position(|effect| *effect == config.effect);
+                let len = self.effects.len() as i32;
+                let index = (index.unwrap_or(0) as i32 + direction).rem_euclid(len);
+                config.effect = self.effects[index as usize];
+            },
+            Field::Red => config.color.r = step(config.color.r, 5),
+            Field::Green => config.color.g = step(config.color.g, 5),
+            Field::Blue => config.color.b = step(config.color.b, 5),
+            Field::MaxBrightness => {
+                config.max_brightness = Brightness(step(config.max_brightness.0, 5));
+            },
+            Field::MinBrightness => {
+                config.min_brightness = Brightness(step(config.min_brightness.0, 5));
+            },
+            Field::FadeIn => config.fade_in_time = timing(config.fade_in_time),
+            Field::FadeOut => config.fade_out_time = timing(config.fade_out_time),
+            Field::Hold => config.hold_time = timing(config.hold_time),
+        }
+
+        // Colors edited here always replace a previously applied palette.
+        config.palette.clear();
+
+        self.status = match self.writer.write(config) {
+            Ok(()) => {
+                self.modified[self.zone] = true;
+                String::new()
+            },
+            Err(err) => format!("\x1b[31mError:\x1b[0m {err}"),
+        };
+    }
+
+    /// Render the interface.
+    fn draw(&self) -> String {
+        let mut screen = String::from("\x1b[H\x1b[2J");
+        screen.push_str(&format!("\x1b[1m{}\x1b[0m\n\n", self.device.controller().name()));
+
+        for (i, config) in self.configs.iter().enumerate() {
+            let cursor = if i == self.zone { ">" } else { " " };
+            screen.push_str(&format!(
+                " {cursor} {:<10} {} {} {:?}\n",
+                format!("{:?}", config.zone),
+                swatch(config),
+                config.color,
+                config.effect,
+            ));
+        }
+        screen.push('\n');
+
+        let config = &self.configs[self.zone];
+        for (i, field) in self.fields.iter().enumerate() {
+            let value = match field {
+                Field::Effect => format!("< {:?} >", config.effect),
+                Field::Red => slider(config.color.r as u16, 255),
+                Field::Green => slider(config.color.g as u16, 255),
+                Field::Blue => slider(config.color.b as u16, 255),
+                Field::MaxBrightness => slider(config.max_brightness.0 as u16, 255),
+                Field::MinBrightness => slider(config.min_brightness.0 as u16, 255),
+                Field::FadeIn => slider(config.fade_in_time.0, MAX_TIMING) + "ms",
+                Field::FadeOut => slider(config.fade_out_time.0, MAX_TIMING) + "ms",
+                Field::Hold => slider(config.hold_time.0, MAX_TIMING) + "ms",
+            };
+
+            let (start, end) = if i == self.field { ("\x1b[7m", "\x1b[0m") } else { ("", "") };
+            screen.push_str(&format!("   {start}{:<16}{end} {value}\n", field.label()));
+        }
+
+        screen.push_str("\n \x1b[2m↑/↓ select  ←/→ adjust  tab next zone  q quit\x1b[0m\n");
+        screen.push_str(&format!("\n {}", self.status));
+
+        screen
+    }
+}
+
+/// Render a colored block for a zone's current color.
+fn swatch(config: &Config) -> String {
+    let color = config.color;
+    format!("\x1b[48;2;{};{};{}m    \x1b[0m", color.r, color.g, color.b)
+}
+
+/// Render a horizontal slider.
+fn slider(value: u16, max: u16) -> String {
+    let filled = value as usize * SLIDER_WIDTH / max as usize;
+    format!("[{}{}] {value:>5}", "█".repeat(filled), " ".repeat(SLIDER_WIDTH - filled))
+}
+
+/// Terminal switched to raw mode, restored when dropped.
+struct RawTerminal {
+    original: libc::termios,
+}
+
+impl RawTerminal {
+    fn new() -> Result<Self, Box<dyn Error>> {
+        if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+            return Err("the tui requires an interactive terminal".into());
+        }
+
+        let mut original: libc::termios = unsafe { mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(
+                format!("unable to read terminal mode: {}", io::Error::last_os_error()).into()
+            );
+        }
+
+        // Read key presses immediately, without echo or signal handling.
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+        raw.c_iflag &= !(libc::IXON | libc::ICRNL);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(
+                format!("unable to set terminal mode: {}", io::Error::last_os_error()).into()
+            );
+        }
+
+        // Switch to the alternate screen and hide the cursor.
+        print!("\x1b[?1049h\x1b[?25l");
+
+        Ok(Self { original })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+    }
+}
+
+/// Run the interface until the user quits.
+fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let device = crate::required_device(matches)?;
+    let mut tui = Tui::new(device);
+
+    {
+        let _terminal = RawTerminal::new()?;
+        let mut stdout = io::stdout();
+        let mut stdin = io::stdin();
+        let mut buffer = [0; 8];
+
+        loop {
+            stdout.write_all(tui.draw().as_bytes())?;
+            stdout.flush()?;
+
+            let len = stdin.read(&mut buffer)?;
+            if len == 0 || !tui.handle(Key::parse(&buffer[..len])) {
+                break;
+            }
+        }
+    }
+
+    // Record modified zones so later transitions start from their colors.
+    let modified: Vec<_> =
+        tui.configs.into_iter().zip(tui.modified).filter_map(|(c, m)| m.then_some(c)).collect();
+    state::record(&modified)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_parsing() {
+        assert_eq!(Key::parse(b"\x1b[A"), Key::Up);
+        assert_eq!(Key::parse(b"l"), Key::Right);
+        assert_eq!(Key::parse(b"\x1b[Z"), Key::PreviousZone);
+        assert_eq!(Key::parse(b"\x1b"), Key::Quit);
+        assert_eq!(Key::parse(b"x"), Key::Other);
+    }
+}
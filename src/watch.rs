@@ -0,0 +1,120 @@
+//! Reapply a configuration file whenever it changes.
+//!
+//! The file's directory is monitored with inotify, since most editors replace
+//! files instead of writing to them directly.
+
+use std::error::Error;
+use std::ffi::CString;
+use std::fs;
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::fd::FromRawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+
+use crate::config_file::DeviceConfigs;
+use crate::reactive::Writer;
+
+/// Directory changes which might have modified the watched file.
+const EVENTS: u32 = libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO | libc::IN_CREATE;
+
+/// Size of the buffer for reading inotify events, large enough for any file
+/// name.
+const EVENT_BUFFER_SIZE: usize = 4096;
+
+/// Get clap CLI parameters for the watch subcommand.
+pub fn subcommand() -> Command {
+    Command::new("watch").about("Apply a configuration file whenever it changes").arg(
+        Arg::new("file")
+            .help("Configuration file")
+            .required(true)
+            .value_parser(value_parser!(PathBuf)),
+    )
+}
+
+/// Run the watch subcommand.
+pub fn watch(matches: &ArgMatches) {
+    if let Err(err) = run(matches) {
+        eprintln!("\x1b[31mError:\x1b[0m {err}");
+    }
+}
+
+/// Apply the file on every change until rgbfusion is killed.
+fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let path = matches.get_one::<PathBuf>("file").unwrap();
+    let mut inotify = Inotify::new(path)?;
+    let transition = crate::transition_time(matches);
+    let verify = matches.get_flag("verify");
+
+    println!("Watching {}, press Ctrl+C to stop.", path.display());
+
+    let mut writer = Writer::default();
+    let mut applied = None;
+    loop {
+        // Skip events which didn't change the content, like touching the file.
+        let content = fs::read_to_string(path).ok();
+        if content.is_some() && content != applied {
+            match DeviceConfigs::load(path).and_then(|file| file.configs(matches)) {
+                Ok(devices) => {
+                    let configs: Vec<_> = devices.into_iter().flatten().collect();
+                    println!("Applying {}.", path.display());
+                    for config in
+                        crate::write_configs(&mut writer, &configs, transition, verify).emulated
+                    {
+                        eprintln!(
+                            "\x1b[31mError:\x1b[0m {:?} is emulated in software, which requires \
+                             `rgbfusion daemon`",
+                            config.effect
+                        );
+                    }
+                },
+                Err(err) => eprintln!("\x1b[31mError:\x1b[0m {err}"),
+            }
+
+            applied = content;
+        }
+
+        inotify.wait()?;
+    }
+}
+
+/// Inotify watch on the parent directory of a file.
+struct Inotify {
+    file: File,
+}
+
+impl Inotify {
+    fn new(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(
+                format!("unable to initialize inotify: {}", io::Error::last_os_error()).into()
+            );
+        }
+        let file = unsafe { File::from_raw_fd(fd) };
+
+        let directory_path = CString::new(directory.as_os_str().as_bytes())?;
+        if unsafe { libc::inotify_add_watch(fd, directory_path.as_ptr(), EVENTS) } < 0 {
+            let err = io::Error::last_os_error();
+            return Err(format!("unable to watch {}: {err}", directory.display()).into());
+        }
+
+        Ok(Self { file })
+    }
+
+    /// Block until the next batch of directory changes.
+    fn wait(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut buf = [0; EVENT_BUFFER_SIZE];
+        match self.file.read(&mut buf)? {
+            0 => Err("inotify watch was closed".into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Per-zone defaults for CLI options.
+//!
+//! Options which should always be used for a zone can be configured in
+//! `~/.config/rgbfusion/defaults.toml`:
+//!
+//! ```toml
+//! [header0]
+//! min_brightness = 20
+//! hold_time = 1500
+//! ```
+//!
+//! Zones can be referenced by name or by their label. Options passed on the
+//! CLI take precedence over the defaults.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::{zone_map, Brightness, Config, Duration, Rgb};
+
+/// Defaults loaded from the configuration directory, by zone name or label.
+static DEFAULTS: OnceLock<Vec<(String, Defaults)>> = OnceLock::new();
+
+/// Default options of a single zone.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Defaults {
+    color2: Option<Rgb>,
+    max_brightness: Option<Brightness>,
+    min_brightness: Option<Brightness>,
+    fade_in_time: Option<Duration>,
+    fade_out_time: Option<Duration>,
+    hold_time: Option<Duration>,
+}
+
+/// Get the configured defaults of all zones.
+fn defaults() -> &'static [(String, Defaults)] {
+    DEFAULTS.get_or_init(|| match load() {
+        Ok(defaults) => defaults,
+        Err(err) => {
+            eprintln!("Ignoring zone defaults: {err}");
+            Vec::new()
+        },
+    })
+}
+
+/// Load the zone defaults file.
+fn load() -> Result<Vec<(String, Defaults)>, Box<dyn Error>> {
+    let path = match crate::config_dir() {
+        Some(config_dir) => config_dir.join("defaults.toml"),
+        None => return Ok(Vec::new()),
+    };
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let defaults: BTreeMap<String, Defaults> = toml::from_str(&content)?;
+    if let Some(name) = defaults.keys().find(|name| !zone_map::is_known(name)) {
+        return Err(format!("unknown zone: {name}").into());
+    }
+
+    Ok(defaults.into_iter().collect())
+}
+
+/// Apply the defaults of the config's zone.
+pub fn apply(config: &mut Config) {
+    let zone = config.zone;
+    let device = Some(config.device);
+    let defaults = defaults().iter().filter(|(name, _)| zone_map::zone(name, device) == Ok(zone));
+    for (_, defaults) in defaults {
+        replace(&mut config.color2, defaults.color2);
+        replace(&mut config.max_brightness, defaults.max_brightness);
+        replace(&mut config.min_brightness, defaults.min_brightness);
+        replace(&mut config.fade_in_time, defaults.fade_in_time);
+        replace(&mut config.fade_out_time, defaults.fade_out_time);
+        replace(&mut config.hold_time, defaults.hold_time);
+    }
+}
+
+/// Replace a value, if a default was configured.
+fn replace<T>(value: &mut T, default: Option<T>) {
+    if let Some(default) = default {
+        *value = default;
+    }
+}
@@ -0,0 +1,180 @@
+//! User-defined zone labels.
+//!
+//! `rgbfusion zonetest` asks what physically lit up for every zone, storing
+//! the answers in `~/.config/rgbfusion/zones.toml`:
+//!
+//! ```toml
+//! [trx40]
+//! "rear IO shield" = "io"
+//! "top ARGB header" = "header0"
+//! ```
+//!
+//! Aliases shared by all devices can be added in front of the device tables:
+//!
+//! ```toml
+//! case = "header1"
+//! waterblock = "header0"
+//! ```
+//!
+//! Labels and aliases are accepted wherever a zone is expected, like
+//! `-z "rear IO shield"`, including interactive prompts.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{Device, Zone};
+
+/// Zone labels of every device, and aliases shared by all of them.
+type ZoneMap = BTreeMap<String, Entry>;
+
+/// Entry of the zone map.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+enum Entry {
+    /// Alias for a zone of every device.
+    Alias(Zone),
+    /// Labels of a single device.
+    Labels(BTreeMap<String, Zone>),
+}
+
+/// Zone labels loaded from the configuration directory.
+static ZONE_MAP: OnceLock<ZoneMap> = OnceLock::new();
+
+/// Get the path of the zone map.
+fn path() -> Option<PathBuf> {
+    Some(crate::config_dir()?.join("zones.toml"))
+}
+
+/// Get the zone labels of all devices.
+fn zone_map() -> &'static ZoneMap {
+    ZONE_MAP.get_or_init(|| match load() {
+        Ok(zone_map) => zone_map,
+        Err(err) => {
+            eprintln!("Ignoring zone labels: {err}");
+            BTreeMap::new()
+        },
+    })
+}
+
+/// Load the zone map file.
+fn load() -> Result<ZoneMap, Box<dyn Error>> {
+    let content = match path().map(fs::read_to_string) {
+        Some(Ok(content)) => content,
+        _ => return Ok(BTreeMap::new()),
+    };
+
+    Ok(toml::from_str(&content)?)
+}
+
+/// Get all labels and aliases of all devices.
+pub fn all_labels() -> impl Iterator<Item = &'static str> {
+    all_zones().map(|(label, _)| label)
+}
+
+/// Get all aliases and labels with their zone, aliases first.
+fn all_zones() -> impl Iterator<Item = (&'static str, Zone)> {
+    let aliases = zone_map().iter().filter_map(|(alias, entry)| match entry {
+        Entry::Alias(zone) => Some((alias.as_str(), *zone)),
+        Entry::Labels(_) => None,
+    });
+    let labels = zone_map().values().filter_map(|entry| match entry {
+        Entry::Labels(labels) => Some(labels),
+        Entry::Alias(_) => None,
+    });
+
+    aliases.chain(labels.flatten().map(|(label, zone)| (label.as_str(), *zone)))
+}
+
+/// Check if a name is a zone, alias or label of any device.
+pub fn is_known(name: &str) -> bool {
+    Zone::from_str(name, true).is_ok() || all_labels().any(|label| label.eq_ignore_ascii_case(name))
+}
+
+/// Get the zone labels of a device.
+pub fn labels(device: Device) -> BTreeMap<String, Zone> {
+    match zone_map().get(&format!("{device:?}")) {
+        Some(Entry::Labels(labels)) => labels.clone(),
+        _ => BTreeMap::new(),
+    }
+}
+
+/// Resolve a zone name, alias or label.
+///
+/// Labels are looked up for the device, or for all devices if none is given.
+pub fn zone(name: &str, device: Option<Device>) -> Result<Zone, String> {
+    resolve(zone_map(), name, device)
+}
+
+/// Resolve a zone name, alias or label using a zone map.
+fn resolve(zone_map: &ZoneMap, name: &str, device: Option<Device>) -> Result<Zone, String> {
+    if let Ok(zone) = Zone::from_str(name, true) {
+        return Ok(zone);
+    }
+
+    let device = device.map(|device| format!("{device:?}"));
+    let mut zones = Vec::new();
+    for (key, entry) in zone_map {
+        match entry {
+            Entry::Alias(zone) if key.eq_ignore_ascii_case(name) => return Ok(*zone),
+            Entry::Labels(labels) if device.as_ref().is_none_or(|device| device == key) => {
+                let matching = labels.iter().filter(|(label, _)| label.eq_ignore_ascii_case(name));
+                zones.extend(matching.map(|(_, zone)| *zone));
+            },
+            _ => (),
+        }
+    }
+
+    match zones.first() {
+        Some(zone) if zones.iter().all(|other| other == zone) => Ok(*zone),
+        Some(_) => Err(format!("zone label '{name}' is ambiguous, select a device with --device")),
+        None => Err(format!("unknown zone: {name}")),
+    }
+}
+
+/// Replace the stored zone labels of a device.
+pub fn save(device: Device, labels: BTreeMap<String, Zone>) -> Result<PathBuf, Box<dyn Error>> {
+    let path = path().ok_or("unable to locate config directory")?;
+
+    // Reload the file to avoid discarding changes made since startup.
+    let mut zone_map = load()?;
+    zone_map.insert(format!("{device:?}"), Entry::Labels(labels));
+
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, toml::to_string(&zone_map)?)
+        .map_err(|err| format!("unable to write {}: {err}", path.display()))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RgbDevice;
+
+    #[test]
+    fn aliases_and_labels() {
+        let content = "case = \"header1\"\n\n[trx40]\n\"rear IO shield\" = \"io\"\n";
+        let zone_map: ZoneMap = toml::from_str(content).unwrap();
+
+        assert!(zone_map["case"] == Entry::Alias(Zone::Header1));
+        assert!(matches!(&zone_map["trx40"], Entry::Labels(labels) if labels.len() == 1));
+        assert_eq!(toml::to_string(&zone_map).unwrap(), content);
+    }
+
+    #[test]
+    fn device_labels() {
+        let content = "[trx40]\ntop = \"header0\"\n\n[z390]\ntop = \"header1\"\n";
+        let zone_map: ZoneMap = toml::from_str(content).unwrap();
+        let trx40 = Device::Builtin(RgbDevice::Trx40);
+
+        assert_eq!(resolve(&zone_map, "Top", Some(trx40)), Ok(Zone::Header0));
+        assert!(resolve(&zone_map, "top", None).unwrap_err().contains("ambiguous"));
+        assert!(resolve(&zone_map, "bottom", Some(trx40)).is_err());
+    }
+}